@@ -0,0 +1,282 @@
+//! Tests that drawing a frame doesn't allocate once the buffers it reuses have warmed up.
+//!
+//! The test binary's global allocator counts the allocations made by each thread, so that tests running in parallel
+//! don't disturb each other's counts. Only the thread drawing the frame is counted, so a tiled renderer's workers
+//! aren't.
+//!
+
+use crate::{
+    camera::Camera,
+    framebuffer::{compare_draw_order, BlendMode, DrawType, FrameBuffer, LineStyle, RenderTarget},
+    golden::{overlapping_meshes, projection},
+    lighting::{Attenuation, Light, Material},
+    mesh::{
+        geometry::{Point, Vector},
+        Mesh,
+    },
+    projection::Projection,
+    rasterizer::{AntiAliasing, RasterMethod, SampleCount, SamplePattern},
+    shader::{LitShader, ShadingMode},
+    shadow::{ShadowMap, ShadowSettings},
+    texture::{Filter, Texture},
+    tiled::TiledRenderer,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Allocator ///////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// The system allocator, counting every allocation and reallocation made by the current thread.
+///
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// Add an allocation to the current thread's count. Allocations made while the thread is being torn down aren't
+/// counted.
+///
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+/// Return the number of allocations the current thread makes while running a closure.
+///
+fn count_allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 120;
+
+/// The meshes, materials and buffers used to draw a frame, which are all created up front.
+///
+struct Scene {
+    meshes: [Mesh; 2],
+    pipes: [Mesh; 2],
+    materials: [Material; 2],
+    lights: [Light; 4],
+    shadow_maps: [Option<ShadowMap>; 4],
+    camera: Camera,
+    projection: Projection,
+    buffer: FrameBuffer,
+    renderer: Option<TiledRenderer>,
+}
+
+impl Scene {
+    /// Return the overlapping textured cube and sphere, with the sphere made transparent, lit by lights of every
+    /// kind. The directional and spot lights cast shadows. If tiled is true the meshes are drawn with a tiled
+    /// renderer.
+    ///
+    fn new(anti_aliasing: AntiAliasing, blend: BlendMode, tiled: bool) -> Scene {
+        let mut textured = Material::new([1.0, 1.0, 1.0]);
+        let mut texture = Texture::checkerboard(32, 4, [[255, 255, 255, 255], [255, 0, 0, 255]]);
+        texture.filter = Filter::Trilinear;
+        textured.texture = Some(texture);
+        let mut glass = Material::new([0.3, 0.3, 1.0]);
+        glass.opacity = 0.5;
+        glass.blend = blend;
+
+        let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+        buffer.set_anti_aliasing(anti_aliasing);
+
+        let mut cascaded = ShadowSettings::new(128, 1000.0);
+        cascaded.cascades = 3;
+        let shadow_maps = [
+            None,
+            None,
+            Some(ShadowMap::new(cascaded)),
+            Some(ShadowMap::new(ShadowSettings::new(128, 1000.0))),
+        ];
+
+        Scene {
+            meshes: overlapping_meshes(),
+            pipes: [Mesh::default(), Mesh::default()],
+            materials: [textured, glass],
+            lights: [
+                Light::Ambient {
+                    colour: [0.2, 0.2, 0.2],
+                },
+                Light::Point {
+                    position: Point::new([100, 100, 0]),
+                    colour: [0.4, 0.4, 0.4],
+                    attenuation: Attenuation::with_range(1000.0),
+                },
+                Light::Directional {
+                    direction: Vector::new([1, -1, 1]),
+                    colour: [0.4, 0.4, 0.4],
+                },
+                Light::Spot {
+                    position: Point::new([0, 200, 100]),
+                    direction: Vector::new([0, -1, 1]),
+                    colour: [0.4, 0.4, 0.4],
+                    attenuation: Attenuation::none(),
+                    inner_angle: 20.0,
+                    outer_angle: 30.0,
+                },
+            ],
+            shadow_maps,
+            camera: Camera::default(),
+            projection: projection(),
+            buffer,
+            renderer: tiled.then(|| TiledRenderer::new(2, 32)),
+        }
+    }
+
+    /// Run the meshes through the pipeline, render the shadow maps and draw the meshes, in the same way as the main
+    /// loop.
+    ///
+    fn draw_frame(&mut self, draw_type: DrawType, method: RasterMethod, shading: ShadingMode) {
+        self.buffer.clear();
+
+        let size = [WIDTH as f64, HEIGHT as f64];
+        for (mesh, pipe) in self.meshes.iter().zip(self.pipes.iter_mut()) {
+            mesh.run_pipeline_into(&self.camera, &self.projection, size, pipe);
+        }
+
+        let casters = [&self.meshes[0], &self.meshes[1]];
+        for (light, shadow_map) in self.lights.iter().zip(self.shadow_maps.iter_mut()) {
+            if let Some(shadow_map) = shadow_map {
+                shadow_map.render(light, &casters, &self.camera, &self.projection);
+            }
+        }
+
+        let [cube, sphere] = &mut self.pipes;
+        let mut meshes = [(cube, &self.materials[0]), (sphere, &self.materials[1])];
+        for (mesh, material) in meshes.iter_mut() {
+            if material.blend == BlendMode::Alpha {
+                mesh.sort_back_to_front();
+            }
+        }
+        meshes.sort_by(|a, b| compare_draw_order((a.0, a.1.blend), (b.0, b.1.blend)));
+
+        let line_style = LineStyle::new([255, 255, 255, 255]);
+        for (mesh, material) in &meshes {
+            let mut shader = LitShader::new(material, &self.lights, shading);
+            shader.shadows = &self.shadow_maps;
+            match &mut self.renderer {
+                Some(renderer) => renderer.draw_mesh(
+                    &mut self.buffer,
+                    mesh,
+                    draw_type,
+                    method,
+                    &shader,
+                    &line_style,
+                ),
+                None => self
+                    .buffer
+                    .draw_mesh(mesh, draw_type, method, &shader, &line_style),
+            }
+        }
+        self.buffer.composite_transparency();
+        self.buffer.resolve();
+    }
+}
+
+#[test]
+fn test_counting_allocator() {
+    let allocations = count_allocations(|| {
+        let values = Box::new([1, 2, 3]);
+        assert_eq!(values.len(), 3);
+    });
+    assert_eq!(allocations, 1);
+}
+
+#[test]
+fn test_frame_does_not_allocate() {
+    let configurations = [
+        (
+            AntiAliasing::Off,
+            DrawType::Fill,
+            RasterMethod::EdgeTable,
+            ShadingMode::Gouraud,
+            BlendMode::Alpha,
+            false,
+        ),
+        (
+            AntiAliasing::Off,
+            DrawType::HiddenLine,
+            RasterMethod::HalfSpace,
+            ShadingMode::Phong,
+            BlendMode::Additive,
+            false,
+        ),
+        (
+            AntiAliasing::Multisample(SampleCount::X4, SamplePattern::RotatedGrid),
+            DrawType::Fill,
+            RasterMethod::HalfSpace,
+            ShadingMode::Flat,
+            BlendMode::WeightedBlended,
+            false,
+        ),
+        (
+            AntiAliasing::Supersample(SampleCount::X2, SamplePattern::Grid),
+            DrawType::Both,
+            RasterMethod::EdgeTable,
+            ShadingMode::Gouraud,
+            BlendMode::Opaque,
+            false,
+        ),
+        (
+            AntiAliasing::Multisample(SampleCount::X2, SamplePattern::Grid),
+            DrawType::Both,
+            RasterMethod::HalfSpace,
+            ShadingMode::Phong,
+            BlendMode::WeightedBlended,
+            true,
+        ),
+    ];
+
+    for (anti_aliasing, draw_type, method, shading, blend, tiled) in configurations {
+        let mut scene = Scene::new(anti_aliasing, blend, tiled);
+
+        // The first frames grow the buffers to fit. A mesh's vertex attributes swap buffers with the varyings each
+        // frame, so it takes 2 frames for both to grow to hold the verticies added by clipping.
+        scene.draw_frame(draw_type, method, shading);
+        scene.draw_frame(draw_type, method, shading);
+
+        let allocations = count_allocations(|| scene.draw_frame(draw_type, method, shading));
+        assert_eq!(
+            allocations,
+            0,
+            "{:?} allocated",
+            (anti_aliasing, draw_type, method, shading, blend, tiled)
+        );
+        assert!(scene.buffer.frame().iter().any(|&channel| channel != 0));
+    }
+}
@@ -0,0 +1,227 @@
+//! Implementation of a camera, which the scene is viewed from.
+//!
+//! A camera has a position and orientation in world space, in the same way as a mesh. Its view matrix moves world
+//! space into view space, where the camera sits at the origin looking down +Z with +X to the right and +Y up. A camera
+//! with no rotation looks down world space's +Z axis.
+//!
+
+use crate::{
+    mesh::{
+        geometry::{
+            Dim::{X, Y, Z},
+            Point, Quaternion, Vector,
+        },
+        Matrix4X4,
+    },
+    physics::PhysicalState,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// A point of view in world space. The orientation is applied in the same order as a mesh's, so its x rotation tilts
+/// the camera up and down and its y rotation turns it from side to side.
+///
+#[derive(Clone)]
+pub struct Camera {
+    pub physics: PhysicalState,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Camera {
+    /// Return a new camera at the origin of world space looking down +Z.
+    ///
+    pub fn new() -> Camera {
+        Camera {
+            physics: PhysicalState::new(),
+        }
+    }
+
+    /// Return a new camera at an eye position looking towards a target, with the up vector pointing up the screen.
+    ///
+    pub fn look_at(eye: &Point<3>, target: &Point<3>, up: &Vector<3>) -> Camera {
+        let mut camera = Camera::new();
+        camera.physics.position = *eye;
+        camera.point_at(target, up);
+        camera
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera::new()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+impl Camera {
+    /// Return the camera's position in world space.
+    ///
+    pub fn position(&self) -> Point<3> {
+        self.physics.position
+    }
+
+    /// Turn the camera to look towards a target without moving it, with the up vector pointing up the screen.
+    /// The orientation is left unchanged if the target is at the camera's position.
+    ///
+    pub fn point_at(&mut self, target: &Point<3>, up: &Vector<3>) {
+        let forward = self.physics.position.vector_to(target).normalise();
+        if forward.magnitude() == 0.0 {
+            return;
+        }
+
+        // An up vector parallel to the view direction doesn't say which way is up, so fall back to another axis.
+        let mut right = up.cross(&forward).normalise();
+        if right.magnitude() < 1e-9 {
+            let other = if forward[Y].abs() < 0.99 {
+                Vector::new([0, 1, 0])
+            } else {
+                Vector::new([0, 0, 1])
+            };
+            right = other.cross(&forward).normalise();
+        }
+        let up = forward.cross(&right);
+
+        let rotation = Matrix4X4([
+            [right[X], right[Y], right[Z], 0.0],
+            [up[X], up[Y], up[Z], 0.0],
+            [forward[X], forward[Y], forward[Z], 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        self.physics
+            .set_rotation(&Quaternion::from_matrix(&rotation));
+    }
+
+    /// Return the matrix rotating view space directions into world space. Its rows are the camera's right, up and
+    /// forward directions.
+    ///
+    pub fn rotation(&self) -> Matrix4X4 {
+        self.physics.rotation_matrix()
+    }
+
+    /// Return the unit vector pointing to the right of the view in world space.
+    ///
+    pub fn right(&self) -> Vector<3> {
+        self.axis(0)
+    }
+
+    /// Return the unit vector pointing up the view in world space.
+    ///
+    pub fn up(&self) -> Vector<3> {
+        self.axis(1)
+    }
+
+    /// Return the unit vector the camera looks along in world space.
+    ///
+    pub fn forward(&self) -> Vector<3> {
+        self.axis(2)
+    }
+
+    /// Return the matrix moving world space into view space.
+    ///
+    pub fn view_matrix(&self) -> Matrix4X4 {
+        let position = self.physics.position;
+        Matrix4X4::new_look_at(&position, &(position + self.forward()), &self.up())
+    }
+
+    /// Return the view space coordinates of a world space point.
+    ///
+    pub fn to_view_space(&self, point: &Point<3>) -> Point<3> {
+        let offset = point.vector_from(&self.physics.position);
+        Point::new([
+            offset.dot(&self.right()),
+            offset.dot(&self.up()),
+            offset.dot(&self.forward()),
+        ])
+    }
+
+    /// Return the world space coordinates of a view space point.
+    ///
+    pub fn to_world_space(&self, point: &Point<3>) -> Point<3> {
+        self.physics.position
+            + ((self.right() * point[X]) + &(self.up() * point[Y]) + &(self.forward() * point[Z]))
+    }
+
+    /// Return a row of the rotation matrix, which is where one of view space's axes points in world space.
+    ///
+    fn axis(&self, row: usize) -> Vector<3> {
+        let [x, y, z, _] = self.rotation().0[row];
+        Vector::new([x, y, z])
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::Vertex;
+
+    fn assert_near(actual: &Point<3>, expected: [f64; 3]) {
+        for (actual, expected) in actual.into_iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-9, "{:?}", (actual, expected));
+        }
+    }
+
+    /// Return the view space coordinates of a world space point, using the camera's view matrix.
+    ///
+    fn view(camera: &Camera, point: [f64; 3]) -> Point<3> {
+        let vertex = Vertex::new([point[0], point[1], point[2], 1.0]) * camera.view_matrix();
+        vertex.demote()
+    }
+
+    #[test]
+    fn test_default_camera() {
+        let camera = Camera::default();
+        assert_near(&view(&camera, [10.0, 20.0, 30.0]), [10.0, 20.0, 30.0]);
+    }
+
+    #[test]
+    fn test_look_at() {
+        // Looking down -X from the right of a target, with +Z on the right of the screen.
+        let eye = Point::new([100, 0, 0]);
+        let camera = Camera::look_at(&eye, &Point::new([0, 0, 0]), &Vector::new([0, 1, 0]));
+        assert_near(&view(&camera, [0.0, 0.0, 0.0]), [0.0, 0.0, 100.0]);
+        assert_near(&view(&camera, [0.0, 10.0, 20.0]), [20.0, 10.0, 100.0]);
+
+        // Looking straight down, with an up vector along the view direction, still gives a valid orientation.
+        for up in [Vector::new([0, 0, 1]), Vector::new([0, -1, 0])] {
+            let camera = Camera::look_at(&Point::new([0, 100, 0]), &Point::new([0, 0, 0]), &up);
+            assert_near(&view(&camera, [0.0, 0.0, 0.0]), [0.0, 0.0, 100.0]);
+            assert!((camera.right().magnitude() - 1.0).abs() < 1e-9);
+            assert!(camera.right().dot(&camera.forward()).abs() < 1e-9);
+        }
+
+        // Rolled upside down.
+        let camera = Camera::look_at(
+            &Point::new([0, 0, -50]),
+            &Point::new([0, 0, 0]),
+            &Vector::new([0, -1, 0]),
+        );
+        assert_near(&view(&camera, [10.0, 10.0, 0.0]), [-10.0, -10.0, 50.0]);
+    }
+
+    #[test]
+    fn test_view_and_world_space() {
+        let camera = Camera::look_at(
+            &Point::new([30, 40, -50]),
+            &Point::new([-20, 10, 60]),
+            &Vector::new([0, 1, 0]),
+        );
+        let point = Point::new([5, -15, 25]);
+
+        let in_view = camera.to_view_space(&point);
+        assert_near(&in_view, view(&camera, [5.0, -15.0, 25.0]).0);
+        assert_near(&camera.to_world_space(&in_view), [5.0, -15.0, 25.0]);
+    }
+}
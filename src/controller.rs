@@ -0,0 +1,390 @@
+//! Implementation of interactive camera controllers, which move a camera in response to the keyboard and mouse.
+//!
+//! Controllers read the input state once a frame to move the camera, using actions rather than particular keys so
+//! that they follow the bindings. Anything held down, such as a movement key, moves the camera by its speed multiplied
+//! by the time since the last frame, so the camera moves at the same rate whatever the frame rate. Mouse motion and
+//! scrolling are accumulated between frames, so they don't depend on the frame rate either.
+//!
+
+use crate::{
+    camera::Camera,
+    input::{Action, InputState},
+    mesh::geometry::{
+        Dim::{X, Y, Z},
+        Orientation3D, Point, Vector,
+    },
+};
+use std::time::Duration;
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// The furthest the controllers tilt the camera up or down, in degrees. Looking straight up or down would leave the
+/// direction it's turned to undefined.
+const MAX_PITCH: f64 = 89.0;
+
+/// The actions the fly controller moves the camera with while the look action is held.
+const FLY_ACTIONS: [Action; 7] = [
+    Action::MoveForward,
+    Action::MoveBack,
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::MoveFast,
+];
+
+/// Trait for something that moves a camera in response to input.
+///
+pub trait CameraController {
+    /// Move a camera given the input since the last update and the time that has passed.
+    ///
+    fn update(&mut self, camera: &mut Camera, input: &InputState, elapsed: Duration);
+
+    /// Return the actions the controller is using, so that pressing the buttons bound to them doesn't trigger any of
+    /// the other actions those buttons are bound to. Controllers don't use any buttons bound to other actions by
+    /// default.
+    ///
+    fn used_actions(&self, _input: &InputState) -> &'static [Action] {
+        &[]
+    }
+}
+
+/// Flies the camera around, looking with the mouse and moving with the movement actions. It only looks around and
+/// moves while the look action is held.
+///
+pub struct FlyController {
+    /// How fast the camera moves, in world units per second.
+    pub speed: f64,
+    /// How many times faster the camera moves while the fast action is held.
+    pub fast_multiplier: f64,
+    /// How far the camera turns for each unit of mouse motion, in degrees.
+    pub sensitivity: f64,
+}
+
+/// Orbits the camera around a target, keeping it in the middle of the view. Dragging or nudging turns the camera
+/// around the target, panning moves the target across the view and scrolling zooms in and out.
+///
+pub struct OrbitController {
+    pub target: Point<3>,
+    /// How far the camera turns for each unit of mouse motion, in degrees.
+    pub sensitivity: f64,
+    /// How fast nudging turns the camera, in degrees per second.
+    pub turn_speed: f64,
+    /// How far the target moves for each unit of mouse motion, as a fraction of its distance from the camera.
+    pub pan_sensitivity: f64,
+    /// How much the distance to the target is multiplied by for each line scrolled towards the user.
+    pub zoom_factor: f64,
+    /// The closest the camera can zoom in to the target.
+    pub min_distance: f64,
+}
+
+/// Slides the camera across the view without turning it. Dragging drags the scene with the mouse, nudging moves the
+/// camera and scrolling moves it forwards and backwards.
+///
+pub struct PanController {
+    /// How far the camera moves for each unit of mouse motion, in world units.
+    pub sensitivity: f64,
+    /// How fast nudging moves the camera, in world units per second.
+    pub speed: f64,
+    /// How far the camera moves for each line scrolled, in world units.
+    pub dolly_speed: f64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl FlyController {
+    /// Return a new controller moving at the given speed in world units per second.
+    ///
+    pub fn new(speed: f64) -> FlyController {
+        FlyController {
+            speed,
+            fast_multiplier: 4.0,
+            sensitivity: 0.2,
+        }
+    }
+}
+
+impl OrbitController {
+    /// Return a new controller orbiting a target.
+    ///
+    pub fn new(target: Point<3>) -> OrbitController {
+        OrbitController {
+            target,
+            sensitivity: 0.3,
+            turn_speed: 90.0,
+            pan_sensitivity: 0.002,
+            zoom_factor: 0.9,
+            min_distance: 1.0,
+        }
+    }
+}
+
+impl PanController {
+    /// Return a new controller nudging the camera at the given speed in world units per second.
+    ///
+    pub fn new(speed: f64) -> PanController {
+        PanController {
+            sensitivity: 1.0,
+            speed,
+            dolly_speed: 20.0,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Return the angles, in degrees, a camera must be tilted up and turned to the left to look in a direction.
+///
+fn pitch_and_yaw(direction: &Vector<3>) -> (f64, f64) {
+    let direction = direction.normalise();
+    let pitch = direction[Y].clamp(-1.0, 1.0).asin().to_degrees();
+    let yaw = f64::atan2(-direction[X], direction[Z]).to_degrees();
+    (pitch, yaw)
+}
+
+/// Return a camera orientation tilted up and turned to the left by angles in degrees, with the horizon kept level.
+///
+fn level_orientation(pitch: f64, yaw: f64) -> Orientation3D {
+    Orientation3D::new(pitch.clamp(-MAX_PITCH, MAX_PITCH), yaw, 0.0)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl CameraController for FlyController {
+    fn update(&mut self, camera: &mut Camera, input: &InputState, elapsed: Duration) {
+        if !input.is_action_held(Action::Look) {
+            return;
+        }
+
+        // Moving the mouse right turns right, and moving it down looks down.
+        let [x, y] = input.mouse_delta();
+        let (pitch, yaw) = pitch_and_yaw(&camera.forward());
+        camera.physics.set_euler_angles(level_orientation(
+            pitch - (y * self.sensitivity),
+            yaw - (x * self.sensitivity),
+        ));
+
+        let direction = (camera.forward() * input.axis(Action::MoveForward, Action::MoveBack))
+            + &(camera.right() * input.axis(Action::MoveRight, Action::MoveLeft))
+            + &(Vector::new([0, 1, 0]) * input.axis(Action::MoveUp, Action::MoveDown));
+        let mut distance = self.speed * elapsed.as_secs_f64();
+        if input.is_action_held(Action::MoveFast) {
+            distance *= self.fast_multiplier;
+        }
+        camera
+            .physics
+            .position
+            .translate(&(direction.normalise() * distance));
+    }
+
+    fn used_actions(&self, input: &InputState) -> &'static [Action] {
+        if input.is_action_held(Action::Look) {
+            &FLY_ACTIONS
+        } else {
+            &[]
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn update(&mut self, camera: &mut Camera, input: &InputState, elapsed: Duration) {
+        let [x, y] = input.mouse_delta();
+        let offset = camera.position().vector_from(&self.target);
+
+        // Dragging the target moves the camera with it, so the view slides without turning.
+        if input.is_action_held(Action::Pan) {
+            let scale = offset.magnitude() * self.pan_sensitivity;
+            self.target += (camera.right() * (-x * scale)) + &(camera.up() * (y * scale));
+        }
+
+        // Turn around the target, with dragging right swinging the camera to the left of it.
+        let (mut pitch, mut yaw) = pitch_and_yaw(&-offset);
+        if input.is_action_held(Action::Drag) {
+            pitch -= y * self.sensitivity;
+            yaw -= x * self.sensitivity;
+        }
+        let turn = self.turn_speed * elapsed.as_secs_f64();
+        pitch -= input.axis(Action::NudgeUp, Action::NudgeDown) * turn;
+        yaw -= input.axis(Action::NudgeRight, Action::NudgeLeft) * turn;
+        camera
+            .physics
+            .set_euler_angles(level_orientation(pitch, yaw));
+
+        let distance =
+            (offset.magnitude() * self.zoom_factor.powf(input.scroll())).max(self.min_distance);
+        camera.physics.position = self.target + (camera.forward() * -distance);
+    }
+}
+
+impl CameraController for PanController {
+    fn update(&mut self, camera: &mut Camera, input: &InputState, elapsed: Duration) {
+        let (right, up, forward) = (camera.right(), camera.up(), camera.forward());
+
+        // Dragging moves the camera the opposite way, so the scene follows the mouse.
+        let [mut x, mut y] = [0.0; 2];
+        if input.is_action_held(Action::Drag) {
+            let [dx, dy] = input.mouse_delta();
+            x -= dx * self.sensitivity;
+            y += dy * self.sensitivity;
+        }
+        let distance = self.speed * elapsed.as_secs_f64();
+        x += input.axis(Action::NudgeRight, Action::NudgeLeft) * distance;
+        y += input.axis(Action::NudgeUp, Action::NudgeDown) * distance;
+
+        let offset = (right * x) + &(up * y) + &(forward * (input.scroll() * self.dolly_speed));
+        camera.physics.position.translate(&offset);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Button;
+    use winit::event::{MouseButton, VirtualKeyCode};
+
+    fn assert_near(actual: &Point<3>, expected: [f64; 3]) {
+        for (actual, expected) in actual.into_iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-6, "{:?}", (actual, expected));
+        }
+    }
+
+    #[test]
+    fn test_fly_frame_rate_independent() {
+        let mut input = InputState::default();
+        input.press(Button::Mouse(MouseButton::Right));
+        input.press(Button::Key(VirtualKeyCode::W));
+        input.press(Button::Key(VirtualKeyCode::D));
+        let mut controller = FlyController::new(100.0);
+
+        // A single second long frame moves as far as 10 short ones, diagonally forwards and to the right.
+        let mut slow = Camera::new();
+        controller.update(&mut slow, &input, Duration::from_secs(1));
+        let mut fast = Camera::new();
+        for _ in 0..10 {
+            controller.update(&mut fast, &input, Duration::from_millis(100));
+        }
+        let side = 100.0 / 2.0_f64.sqrt();
+        assert_near(&slow.position(), [side, 0.0, side]);
+        assert_near(&fast.position(), [side, 0.0, side]);
+
+        // Shift speeds it up, and nothing moves once the look button is released.
+        input.release(Button::Key(VirtualKeyCode::D));
+        input.press(Button::Key(VirtualKeyCode::LShift));
+        let mut camera = Camera::new();
+        controller.update(&mut camera, &input, Duration::from_secs(1));
+        assert_near(&camera.position(), [0.0, 0.0, 400.0]);
+        assert_eq!(controller.used_actions(&input), FLY_ACTIONS);
+
+        input.release(Button::Mouse(MouseButton::Right));
+        controller.update(&mut camera, &input, Duration::from_secs(1));
+        assert_near(&camera.position(), [0.0, 0.0, 400.0]);
+        assert!(controller.used_actions(&input).is_empty());
+    }
+
+    #[test]
+    fn test_fly_mouse_look() {
+        let mut input = InputState::default();
+        input.press(Button::Mouse(MouseButton::Right));
+        let mut controller = FlyController::new(100.0);
+        controller.sensitivity = 1.0;
+
+        // Moving the mouse right turns right.
+        let mut camera = Camera::new();
+        input.add_mouse_motion(90.0, 0.0);
+        controller.update(&mut camera, &input, Duration::ZERO);
+        assert_near(&Point::new(camera.forward().0), [1.0, 0.0, 0.0]);
+
+        // Moving it down looks down, but never straight down.
+        input.end_frame();
+        input.add_mouse_motion(0.0, 200.0);
+        controller.update(&mut camera, &input, Duration::ZERO);
+        let (pitch, yaw) = pitch_and_yaw(&camera.forward());
+        assert!((pitch + MAX_PITCH).abs() < 1e-6);
+        assert!((yaw + 90.0).abs() < 1e-6);
+        assert!(camera.right()[Y].abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_orbit() {
+        let target = Point::new([0, 0, 400]);
+        let mut camera = Camera::new();
+        let mut controller = OrbitController::new(target);
+        controller.sensitivity = 1.0;
+        let mut input = InputState::default();
+
+        // Dragging right swings the camera round to the left of the target, still looking at it.
+        input.press(Button::Mouse(MouseButton::Left));
+        input.add_mouse_motion(90.0, 0.0);
+        controller.update(&mut camera, &input, Duration::ZERO);
+        assert_near(&camera.position(), [-400.0, 0.0, 400.0]);
+        assert_near(&Point::new(camera.forward().0), [1.0, 0.0, 0.0]);
+
+        // Scrolling away from the user zooms in, and the arrow keys turn at a fixed rate.
+        input.release(Button::Mouse(MouseButton::Left));
+        input.end_frame();
+        input.add_scroll(2.0);
+        input.press(Button::Key(VirtualKeyCode::Left));
+        controller.update(&mut camera, &input, Duration::from_millis(500));
+        let distance = 400.0 * 0.81;
+        assert_near(
+            &camera.position(),
+            [
+                -distance / 2.0_f64.sqrt(),
+                0.0,
+                400.0 - distance / 2.0_f64.sqrt(),
+            ],
+        );
+        assert_near(
+            &(camera.position() + (camera.forward() * distance)),
+            [0.0, 0.0, 400.0],
+        );
+
+        // Panning moves the target and camera together.
+        input.release(Button::Key(VirtualKeyCode::Left));
+        input.end_frame();
+        input.press(Button::Mouse(MouseButton::Middle));
+        input.add_mouse_motion(0.0, 100.0);
+        let before = camera.position();
+        controller.update(&mut camera, &input, Duration::ZERO);
+        let moved = 100.0 * distance * controller.pan_sensitivity;
+        assert_near(&controller.target, [0.0, moved, 400.0]);
+        assert_near(
+            &camera.position(),
+            [before[X], before[Y] + moved, before[Z]],
+        );
+    }
+
+    #[test]
+    fn test_pan() {
+        let mut camera = Camera::new();
+        let mut controller = PanController::new(100.0);
+        let mut input = InputState::default();
+
+        // The scene follows the mouse, so the camera moves the other way, and scrolling moves it forwards.
+        input.press(Button::Mouse(MouseButton::Left));
+        input.add_mouse_motion(10.0, 20.0);
+        input.add_scroll(1.0);
+        controller.update(&mut camera, &input, Duration::ZERO);
+        assert_near(&camera.position(), [-10.0, 20.0, 20.0]);
+
+        // The arrow keys move it at a fixed rate, without turning it.
+        input.release(Button::Mouse(MouseButton::Left));
+        input.end_frame();
+        input.press(Button::Key(VirtualKeyCode::Up));
+        controller.update(&mut camera, &input, Duration::from_millis(250));
+        assert_near(&camera.position(), [-10.0, 45.0, 20.0]);
+        assert_near(&Point::new(camera.forward().0), [0.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,161 @@
+//! Implementation of distance fog, which fades fragments towards a colour the further they are from the camera.
+//!
+
+use crate::framebuffer::Colour;
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// How quickly fog thickens between its start and end distances.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum FogFalloff {
+    /// Thicken at a constant rate.
+    Linear,
+    /// Thicken quickly near the start, then level off.
+    Exponential,
+    /// Stay thin near the start, then thicken quickly.
+    ExponentialSquared,
+}
+
+/// Fog blending fragments towards a colour based on their view space distance. Fragments nearer than the start
+/// distance are clear and those at or beyond the end distance are hidden entirely, so setting the end to the far
+/// plane fades objects out before they're clipped.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Fog {
+    pub colour: [f64; 3],
+    pub falloff: FogFalloff,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// The fraction of a fragment's colour left visible by the exponential falloffs at the end distance, which is less
+/// than a single step of an 8 bit colour channel.
+///
+const END_VISIBILITY: f64 = 1.0 / 256.0;
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Fog {
+    /// Return new fog of the given colour, thickening between 2 view space distances.
+    ///
+    pub fn new(colour: [f64; 3], falloff: FogFalloff, start: f64, end: f64) -> Fog {
+        Fog {
+            colour,
+            falloff,
+            start,
+            end,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Fog {
+    /// Return the fraction of a fragment's colour left visible through the fog at a view space distance.
+    ///
+    pub fn visibility(&self, distance: f64) -> f64 {
+        let range = self.end - self.start;
+        let t = if range > 0.0 {
+            ((distance - self.start) / range).clamp(0.0, 1.0)
+        } else if distance < self.start {
+            0.0
+        } else {
+            1.0
+        };
+
+        match self.falloff {
+            FogFalloff::Linear => 1.0 - t,
+            FogFalloff::Exponential => END_VISIBILITY.powf(t),
+            FogFalloff::ExponentialSquared => END_VISIBILITY.powf(t * t),
+        }
+    }
+
+    /// Return a colour with its RGB channels blended towards the fog colour for a view space distance. Alpha is left
+    /// unchanged.
+    ///
+    pub fn apply(&self, colour: [f64; 4], distance: f64) -> [f64; 4] {
+        let visibility = self.visibility(distance);
+        let [r, g, b, a] = colour;
+        let [fog_r, fog_g, fog_b] = self.colour;
+
+        [
+            fog_r + ((r - fog_r) * visibility),
+            fog_g + ((g - fog_g) * visibility),
+            fog_b + ((b - fog_b) * visibility),
+            a,
+        ]
+    }
+
+    /// Return the fog colour as an opaque RGBA colour, so that a frame buffer can be cleared to it and the background
+    /// matches objects hidden by the fog.
+    ///
+    pub fn clear_colour(&self) -> Colour {
+        let [r, g, b] = self
+            .colour
+            .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+        [r, g, b, 255]
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visibility() {
+        for falloff in [
+            FogFalloff::Linear,
+            FogFalloff::Exponential,
+            FogFalloff::ExponentialSquared,
+        ] {
+            let fog = Fog::new([0.5, 0.5, 0.5], falloff, 100.0, 1000.0);
+
+            // Clear before the start and hidden by the end.
+            assert_eq!(fog.visibility(50.0), 1.0);
+            assert_eq!(fog.visibility(100.0), 1.0);
+            assert!(fog.visibility(1000.0) <= END_VISIBILITY);
+            assert!(fog.visibility(2000.0) <= END_VISIBILITY);
+
+            // Thickening with distance in between.
+            let mut last = 1.0;
+            for distance in (200..1000).step_by(100) {
+                let visibility = fog.visibility(distance as f64);
+                assert!(visibility < last, "{:?} at {}", falloff, distance);
+                last = visibility;
+            }
+        }
+
+        // Exponential fog thickens fastest near its start, while squared exponential fog starts off more gently.
+        let mut fog = Fog::new([0.5, 0.5, 0.5], FogFalloff::Linear, 0.0, 100.0);
+        assert!((fog.visibility(25.0) - 0.75).abs() < 1e-9);
+        fog.falloff = FogFalloff::Exponential;
+        assert!((fog.visibility(25.0) - 0.25).abs() < 1e-9);
+        fog.falloff = FogFalloff::ExponentialSquared;
+        assert!((fog.visibility(25.0) - 0.5f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_apply() {
+        let fog = Fog::new([0.2, 0.4, 0.6], FogFalloff::Linear, 0.0, 100.0);
+
+        let colour = fog.apply([1.0, 0.0, 1.0, 0.5], 25.0);
+        let expected = [0.8, 0.1, 0.9, 0.5];
+        for (channel, expected) in colour.iter().zip(expected) {
+            assert!((channel - expected).abs() < 1e-9);
+        }
+
+        assert_eq!(fog.apply([1.0, 0.0, 1.0, 0.5], 100.0), [0.2, 0.4, 0.6, 0.5]);
+        assert_eq!(fog.clear_colour(), [51, 102, 153, 255]);
+    }
+}
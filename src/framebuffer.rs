@@ -0,0 +1,1684 @@
+//! Implementation of a render target abstraction and an in-memory frame buffer that can be drawn into without the need
+//! for a window or GPU.
+//!
+
+use std::{cmp::Ordering, mem::ManuallyDrop};
+
+use crate::{
+    fog::Fog,
+    mesh::{EdgeList, Mesh, RefEdge, RefPoly, SCREEN_DEPTH},
+    rasterizer::{
+        rasterize_line, rasterize_triangle, rasterize_triangle_samples, AntiAliasing, EdgeTable,
+        Fragment, RasterMethod, Rect, MAX_SAMPLES,
+    },
+    shader::{FragmentInput, FragmentShader},
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// An RGBA colour.
+///
+pub type Colour = [u8; 4];
+
+/// How a fragment's colour is combined with the colour already stored for its pixel. Every mode other than opaque
+/// leaves the depth buffer untouched, so transparent polygons should be drawn after opaque ones.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum BlendMode {
+    /// Replace the stored colour and depth.
+    Opaque,
+    /// Mix the colour over the stored colour by its alpha. Polygons must be drawn from back to front.
+    Alpha,
+    /// Add the colour, scaled by its alpha, to the stored colour.
+    Additive,
+    /// Multiply the stored colour by the colour, which fades towards white as its alpha falls.
+    Multiply,
+    /// Accumulate the colour using weighted blended order-independent transparency, and composite the result over
+    /// the stored colour once everything has been drawn. Polygons can be drawn in any order, but the result is only
+    /// an approximation of alpha blending.
+    WeightedBlended,
+    /// Keep the stored colour and replace the stored depth. Used to fill the depth buffer in a depth prepass.
+    DepthOnly,
+}
+
+/// The comparison a fragment's depth must pass against the depth stored for its pixel to be drawn. Larger depths are
+/// closer to the camera.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum DepthTest {
+    /// Pass fragments closer than the stored depth.
+    Greater,
+    /// Pass fragments closer than or level with the stored depth, so that polygons can be drawn again over the depths
+    /// they left in a depth prepass.
+    GreaterEqual,
+}
+
+/// The style in which a mesh is drawn.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum DrawType {
+    /// Draw each edge of the mesh's polygons as a line.
+    Wireframe,
+    /// Fill each polygon.
+    Fill,
+    /// Fill each polygon and draw its edges over it.
+    Both,
+    /// Only draw the edges that aren't hidden behind the mesh's polygons or anything drawn before it.
+    HiddenLine,
+}
+
+/// How lines are drawn.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct LineStyle {
+    pub colour: Colour,
+    pub anti_aliased: bool,
+    /// Only draw the parts of a line in front of the depth already stored for each pixel.
+    pub depth_test: bool,
+    /// How far behind the stored depth a line can be and still pass the depth test, so that edges aren't hidden by
+    /// the polygons they belong to.
+    pub depth_bias: f64,
+}
+
+/// Trait for anything holding a colour and depth buffer that the rasterizer can draw into.
+/// Coordinates have their origin in the bottom left corner.
+///
+pub trait RenderTarget {
+    /// Return the width of the target in pixels.
+    ///
+    fn width(&self) -> u32;
+
+    /// Return the height of the target in pixels.
+    ///
+    fn height(&self) -> u32;
+
+    /// Return the colour of a pixel.
+    ///
+    fn get_pixel(&self, x: u32, y: u32) -> Colour;
+
+    /// Set the colour of a pixel.
+    ///
+    fn draw_pixel(&mut self, x: u32, y: u32, colour: Colour);
+
+    /// Return the depth stored for a pixel.
+    ///
+    fn get_depth(&self, x: u32, y: u32) -> f64;
+
+    /// Set the depth stored for a pixel.
+    ///
+    fn set_depth(&mut self, x: u32, y: u32, depth: f64);
+
+    /// Clear the colour and depth buffers.
+    ///
+    fn clear(&mut self);
+
+    /// Return the region of the target that can be drawn into. Nothing is drawn outside of it. This is the whole
+    /// target by default.
+    ///
+    fn region(&self) -> Rect {
+        Rect::new(0, 0, self.width(), self.height())
+    }
+
+    /// Return how the edges of polygons drawn into the target are anti-aliased. Targets only sample the centre of
+    /// each pixel by default.
+    ///
+    fn anti_aliasing(&self) -> AntiAliasing {
+        AntiAliasing::Off
+    }
+
+    /// Return the test fragments must pass against the stored depth to be drawn. Targets only draw fragments closer
+    /// than the stored depth by default.
+    ///
+    fn depth_test(&self) -> DepthTest {
+        DepthTest::Greater
+    }
+
+    /// Return the fog that fragments shaded into the target are blended towards. Targets have no fog by default.
+    ///
+    fn fog(&self) -> Option<Fog> {
+        None
+    }
+
+    /// Return the colour the target's colour buffer is cleared to. This is transparent black by default.
+    ///
+    fn clear_colour(&self) -> Colour {
+        [0, 0, 0, 0]
+    }
+
+    /// Return the depth stored for one of a pixel's samples.
+    ///
+    fn get_sample_depth(&self, x: u32, y: u32, _sample: usize) -> f64 {
+        self.get_depth(x, y)
+    }
+
+    /// Return the colour stored for one of a pixel's samples.
+    ///
+    fn get_sample(&self, x: u32, y: u32, _sample: usize) -> Colour {
+        self.get_pixel(x, y)
+    }
+
+    /// Set the colour and depth stored for one of a pixel's samples. Targets that aren't anti-aliased have a single
+    /// sample, 0.
+    ///
+    fn draw_sample(&mut self, x: u32, y: u32, _sample: usize, colour: Colour, depth: f64) {
+        self.draw_pixel(x, y, colour);
+        self.set_depth(x, y, depth);
+    }
+
+    /// Add a fragment's colour to the pixel's weighted blended transparency, given the fraction of the pixel it
+    /// covers. Targets without the buffers to accumulate transparency alpha blend the fragment straight away.
+    ///
+    fn accumulate(&mut self, x: u32, y: u32, colour: Colour, _depth: f64, _coverage: f64) {
+        let under = self.get_pixel(x, y);
+        self.draw_pixel(x, y, BlendMode::Alpha.apply(under, colour));
+    }
+
+    /// Composite the accumulated weighted blended transparency over the colour buffer and reset it. Should be called
+    /// once every mesh has been drawn.
+    ///
+    fn composite_transparency(&mut self) {}
+
+    /// Take the edge table used to fill polygons out of the target, so that its rows can be reused. Targets that
+    /// don't keep an edge table return a new empty one, which allocates its rows when it's built.
+    ///
+    fn take_edge_table(&mut self) -> EdgeTable {
+        EdgeTable::default()
+    }
+
+    /// Give back an edge table taken from the target once a polygon has been filled with it.
+    ///
+    fn restore_edge_table(&mut self, _edge_table: EdgeTable) {}
+
+    /// Take the list a mesh's edges are gathered into out of the target, so that its allocations can be reused.
+    /// Targets that don't keep an edge list return a new empty one.
+    ///
+    fn take_edge_list(&mut self) -> EdgeList {
+        EdgeList::default()
+    }
+
+    /// Give back an edge list taken from the target once a mesh's edges have been drawn from it.
+    ///
+    fn restore_edge_list(&mut self, _edge_list: EdgeList) {}
+
+    /// Draw a mesh that has been run through the pipeline. Polygons are filled using the fragment shader and edges
+    /// are drawn with the line style, depending on the draw type. Edges are always depth tested when they're drawn
+    /// along with polygons. Polygons are drawn in order, so meshes drawn with alpha blending should have their
+    /// polygons sorted from back to front first.
+    ///
+    fn draw_mesh<S>(
+        &mut self,
+        mesh: &Mesh,
+        draw_type: DrawType,
+        method: RasterMethod,
+        shader: &S,
+        line_style: &LineStyle,
+    ) where
+        S: FragmentShader,
+    {
+        let mut edges = self.take_edge_list();
+        match draw_type {
+            DrawType::Fill => edges.clear(),
+            _ => mesh.find_visible_edges(&mut edges),
+        }
+        self.draw_primitives(
+            mesh.iter_visible_polygons(),
+            mesh.iter_edges(&edges),
+            draw_type,
+            method,
+            shader,
+            line_style,
+        );
+        self.restore_edge_list(edges);
+    }
+
+    /// Fill the depth buffer with a mesh that has been run through the pipeline, leaving the colour buffer untouched.
+    /// Drawing the opaque meshes like this before shading them with the depth test set to GreaterEqual means only
+    /// their visible fragments are shaded.
+    ///
+    fn draw_depth(&mut self, mesh: &Mesh, method: RasterMethod) {
+        for polygon in mesh.iter_visible_polygons() {
+            self.fill_polygon(&polygon, method, BlendMode::DepthOnly, |_| {
+                Some([0, 0, 0, 0])
+            });
+        }
+    }
+
+    /// Draw a mesh's polygons and edges in the same way as draw_mesh. Used to draw part of a mesh.
+    ///
+    fn draw_primitives<'a, S, P, E>(
+        &mut self,
+        polygons: P,
+        edges: E,
+        draw_type: DrawType,
+        method: RasterMethod,
+        shader: &S,
+        line_style: &LineStyle,
+    ) where
+        S: FragmentShader,
+        P: Iterator<Item = RefPoly<'a>>,
+        E: Iterator<Item = RefEdge<'a>>,
+    {
+        match draw_type {
+            DrawType::Fill | DrawType::Both => {
+                for polygon in polygons {
+                    self.draw_polygon(polygon, method, shader);
+                }
+            }
+            DrawType::HiddenLine => {
+                // Fill the polygons with the clear colour so that only their depth shows.
+                let clear_colour = self.clear_colour();
+                for polygon in polygons {
+                    self.fill_polygon(&polygon, method, BlendMode::Opaque, |_| Some(clear_colour));
+                }
+            }
+            DrawType::Wireframe => {}
+        }
+
+        if draw_type != DrawType::Fill {
+            let line_style = LineStyle {
+                depth_test: line_style.depth_test || draw_type != DrawType::Wireframe,
+                ..*line_style
+            };
+            for edge in edges {
+                self.draw_line(&edge, &line_style);
+            }
+        }
+    }
+
+    /// Draw a polygon using rasterization, colouring each of its fragments with a fragment shader and blending them
+    /// with the shader's blend mode. Shaded fragments are blended towards the target's fog, if it has any, by their
+    /// distance from the camera.
+    ///
+    fn draw_polygon<S>(&mut self, polygon: RefPoly, method: RasterMethod, shader: &S)
+    where
+        S: FragmentShader,
+    {
+        let data = shader.prepare(&polygon);
+        let fog = self.fog();
+        let shade = |fragment: &Fragment| {
+            shader
+                .shade(&data, &FragmentInput::new(fragment, &polygon))
+                .map(|colour| match &fog {
+                    Some(fog) => fog.apply(colour, fragment.view_distance(&polygon)),
+                    None => colour,
+                })
+                .map(|colour| colour.map(|channel| (channel * 255.0) as u8))
+        };
+        self.fill_polygon(&polygon, method, shader.blend_mode(), shade);
+    }
+
+    /// Fill a polygon using a rasterization method, colouring each fragment with the shade closure and blending it
+    /// with the blend mode. Anti-aliased targets are always filled by the half-space rasterizer, as it's the only
+    /// one that can test sample positions.
+    ///
+    fn fill_polygon<F>(
+        &mut self,
+        polygon: &RefPoly,
+        method: RasterMethod,
+        blend: BlendMode,
+        shade: F,
+    ) where
+        F: Fn(&Fragment) -> Option<Colour>,
+    {
+        match (self.anti_aliasing(), method) {
+            (AntiAliasing::Off, RasterMethod::EdgeTable) => {
+                let mut edge_table = self.take_edge_table();
+                edge_table.build(*polygon);
+                self.fill_edge_table(polygon, &edge_table, blend, shade);
+                self.restore_edge_table(edge_table);
+            }
+            (AntiAliasing::Off, RasterMethod::HalfSpace) => {
+                self.fill_half_space(polygon, blend, shade)
+            }
+            (anti_aliasing, _) => self.fill_samples(polygon, anti_aliasing, blend, shade),
+        }
+    }
+
+    /// Draw a line along an edge between 2 screen space verticies. Lines never change the depth buffer, and are
+    /// blended towards the target's fog, if it has any, in the same way as polygons.
+    ///
+    fn draw_line(&mut self, edge: &RefEdge, style: &LineStyle) {
+        let (width, height, region) = (self.width(), self.height(), self.region());
+        let fog = self.fog();
+        let [from, to] = edge.verticies;
+
+        // Lines are clipped to the whole target so that the pixels they cover don't depend on the region.
+        rasterize_line(from, to, width, height, style.anti_aliased, |fragment| {
+            let (x, y) = (fragment.x, fragment.y);
+            if !region.contains(x, y) {
+                return;
+            }
+            if !style.depth_test || fragment.depth + style.depth_bias >= self.get_depth(x, y) {
+                let line_colour = match &fog {
+                    Some(fog) => fog
+                        .apply(
+                            style.colour.map(|channel| channel as f64 / 255.0),
+                            fragment.view_distance(edge),
+                        )
+                        .map(|channel| (channel * 255.0) as u8),
+                    None => style.colour,
+                };
+                let colour = blend(self.get_pixel(x, y), line_colour, fragment.coverage);
+                self.draw_pixel(x, y, colour);
+            }
+        });
+    }
+
+    /// Fill a polygon from the spans in its edge table, colouring each fragment with the shade closure.
+    ///
+    fn fill_edge_table<F>(
+        &mut self,
+        polygon: &RefPoly,
+        edge_table: &EdgeTable,
+        blend: BlendMode,
+        shade: F,
+    ) where
+        F: Fn(&Fragment) -> Option<Colour>,
+    {
+        let region = self.region();
+        let (left, right) = (region.x as i32, region.right() as i32);
+
+        // Find the first and last elements we want to iterate between in the edge table.
+        // We only want elements that will be within the region.
+        let ystart = edge_table.ymin.max(region.y as i32);
+        let yend = edge_table.ymax.min(region.top() as i32);
+        if yend <= ystart {
+            return;
+        }
+        let first = (ystart - edge_table.ymin) as usize;
+        let last = (yend - edge_table.ymin) as usize;
+
+        for (i, edges) in edge_table.iter_between(first, last).enumerate() {
+            let y = ystart + i as i32;
+            match edges.get_edges() {
+                Ok(edges) => {
+                    let xrange = {
+                        let edge1 = edges[0].x.clamp(left, right);
+                        let edge2 = edges[1].x.clamp(left, right);
+                        edge1..edge2
+                    };
+                    let span = (edges[1].x - edges[0].x) as f64;
+
+                    // interpolate Z and the vertex weights between the 2 edges.
+                    for x in xrange {
+                        let t = (x - edges[0].x) as f64 / span;
+                        let z = edges[0].z + ((edges[1].z - edges[0].z) * t);
+
+                        let mut weights = edges[0].weights;
+                        weights
+                            .iter_mut()
+                            .zip(edges[1].weights.iter())
+                            .for_each(|(weight, end)| *weight += (end - *weight) * t);
+
+                        let fragment = Fragment::new(x as u32, y as u32, z, weights, polygon);
+                        self.draw_fragment(&fragment, blend, &shade);
+                    }
+                }
+                // A row the polygon's edges don't cross has nothing to fill.
+                Err(_error) => continue,
+            };
+        }
+    }
+
+    /// Fill a triangle by testing pixels against its edge functions, colouring each fragment with the shade closure.
+    ///
+    fn fill_half_space<F>(&mut self, polygon: &RefPoly, blend: BlendMode, shade: F)
+    where
+        F: Fn(&Fragment) -> Option<Colour>,
+    {
+        let region = self.region();
+
+        rasterize_triangle(polygon, region, |fragment| {
+            self.draw_fragment(&fragment, blend, &shade);
+        });
+    }
+
+    /// Fill a triangle by testing the samples of each pixel against its edge functions, colouring them with the
+    /// shade closure. Supersampling shades every covered sample that passes the depth test. Multisampling shades each
+    /// pixel once, at the centre of its covered samples, and stores the colour in those that pass the depth test.
+    ///
+    fn fill_samples<F>(
+        &mut self,
+        polygon: &RefPoly,
+        anti_aliasing: AntiAliasing,
+        blend: BlendMode,
+        shade: F,
+    ) where
+        F: Fn(&Fragment) -> Option<Colour>,
+    {
+        let (region, depth_test) = (self.region(), self.depth_test());
+        let positions = anti_aliasing.sample_positions();
+        let per_sample = matches!(anti_aliasing, AntiAliasing::Supersample(..));
+
+        rasterize_triangle_samples(polygon, region, positions, |coverage| {
+            let (x, y) = (coverage.x, coverage.y);
+
+            let mut passed = 0;
+            for sample in iter_samples(coverage.mask) {
+                if depth_test.passes(coverage.depths[sample], self.get_sample_depth(x, y, sample)) {
+                    passed |= 1 << sample;
+                }
+            }
+            if passed == 0 {
+                return;
+            }
+
+            if per_sample {
+                for sample in iter_samples(passed) {
+                    let depth = coverage.depths[sample];
+                    let fragment = Fragment::new(x, y, depth, coverage.weights[sample], polygon);
+                    if let Some(colour) = shade(&fragment) {
+                        self.blend_sample(x, y, sample, colour, depth, blend);
+                    }
+                }
+            } else {
+                // Shading at the centre of the covered samples rather than the pixel stops attributes being
+                // extrapolated beyond the polygon's edges.
+                let count = coverage.mask.count_ones() as f64;
+                let (mut weights, mut depth) = ([0.0; 3], 0.0);
+                for sample in iter_samples(coverage.mask) {
+                    weights
+                        .iter_mut()
+                        .zip(coverage.weights[sample].iter())
+                        .for_each(|(weight, sample_weight)| *weight += sample_weight / count);
+                    depth += coverage.depths[sample] / count;
+                }
+
+                if let Some(colour) = shade(&Fragment::new(x, y, depth, weights, polygon)) {
+                    for sample in iter_samples(passed) {
+                        self.blend_sample(x, y, sample, colour, coverage.depths[sample], blend);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Draw a fragment if it passes the depth test against the depth already stored for its pixel. The fragment is
+    /// only shaded once it has passed the depth test, and is discarded if the shade closure returns None.
+    ///
+    fn draw_fragment<F>(&mut self, fragment: &Fragment, blend: BlendMode, shade: &F)
+    where
+        F: Fn(&Fragment) -> Option<Colour>,
+    {
+        let stored = self.get_depth(fragment.x, fragment.y);
+        if self.depth_test().passes(fragment.depth, stored) {
+            if let Some(colour) = shade(fragment) {
+                self.blend_sample(fragment.x, fragment.y, 0, colour, fragment.depth, blend);
+            }
+        }
+    }
+
+    /// Blend a fragment's colour into one of a pixel's samples. Only opaque and depth only fragments write their
+    /// depth.
+    ///
+    fn blend_sample(
+        &mut self,
+        x: u32,
+        y: u32,
+        sample: usize,
+        colour: Colour,
+        depth: f64,
+        blend: BlendMode,
+    ) {
+        match blend {
+            BlendMode::Opaque => self.draw_sample(x, y, sample, colour, depth),
+            BlendMode::DepthOnly => {
+                let under = self.get_sample(x, y, sample);
+                self.draw_sample(x, y, sample, under, depth);
+            }
+            BlendMode::WeightedBlended => {
+                let coverage = 1.0 / self.anti_aliasing().samples() as f64;
+                self.accumulate(x, y, colour, depth, coverage);
+            }
+            _ => {
+                let under = self.get_sample(x, y, sample);
+                let stored_depth = self.get_sample_depth(x, y, sample);
+                self.draw_sample(x, y, sample, blend.apply(under, colour), stored_depth);
+            }
+        }
+    }
+}
+
+/// Compare meshes that have been run through the pipeline by the order they should be drawn in, given the blend mode
+/// each is drawn with. Opaque meshes come first, from front to back so that hidden fragments fail the depth test
+/// before they're shaded, followed by transparent meshes from back to front.
+///
+pub fn compare_draw_order(a: (&Mesh, BlendMode), b: (&Mesh, BlendMode)) -> Ordering {
+    let (a_transparent, b_transparent) = (a.1.is_transparent(), b.1.is_transparent());
+    let (a_depth, b_depth) = (a.0.screen_depth(), b.0.screen_depth());
+
+    // Larger screen depths are closer to the camera.
+    a_transparent.cmp(&b_transparent).then_with(|| {
+        if a_transparent {
+            a_depth.total_cmp(&b_depth)
+        } else {
+            b_depth.total_cmp(&a_depth)
+        }
+    })
+}
+
+/// Release the rows a list of tiles borrows from a frame buffer, returning the tiles without tying them to it. The
+/// tiles keep the allocations of their row lists and edge tables, so they can be kept and split into again.
+///
+pub fn release_tiles<'b>(mut tiles: Vec<Tile<'_>>) -> Vec<Tile<'b>> {
+    for tile in tiles.iter_mut() {
+        tile.colour.clear();
+        tile.depth.clear();
+        tile.accumulation.clear();
+        tile.revealage.clear();
+    }
+
+    // SAFETY: The tiles no longer hold any rows, so nothing in them borrows the frame buffer. Only the lifetime of
+    // the tiles changes, which doesn't change their layout.
+    let mut tiles = ManuallyDrop::new(tiles);
+    unsafe { Vec::from_raw_parts(tiles.as_mut_ptr().cast(), tiles.len(), tiles.capacity()) }
+}
+
+/// Return the weight of a fragment's colour in weighted blended transparency. Closer and more opaque fragments have
+/// more weight, so they dominate the result as they would with sorted alpha blending.
+///
+fn transparency_weight(alpha: f64, depth: f64) -> f64 {
+    let closeness = (depth / SCREEN_DEPTH).clamp(0.0, 1.0);
+    alpha * (3e3 * closeness.powi(3)).max(1e-2)
+}
+
+/// Iterate over the indices of the samples set in a coverage mask.
+///
+fn iter_samples(mask: u32) -> impl Iterator<Item = usize> {
+    (0..MAX_SAMPLES).filter(move |sample| mask & (1 << sample) != 0)
+}
+
+/// Blend a colour over another, given the fraction of the pixel it covers.
+///
+fn blend(under: Colour, over: Colour, coverage: f64) -> Colour {
+    let mut colour = under;
+    colour
+        .iter_mut()
+        .zip(over.iter())
+        .for_each(|(under, &over)| {
+            *under = (*under as f64 + ((over as f64 - *under as f64) * coverage)).round() as u8
+        });
+    colour
+}
+
+/// A colour and depth buffer held in memory.
+/// The colour buffer is stored as rows of RGBA bytes, starting with the top row. Anti-aliased buffers store a colour
+/// and depth for each sample of a pixel, which are averaged into a separate resolved colour buffer. The buffers for
+/// weighted blended transparency are only allocated once something is drawn with it.
+///
+pub struct FrameBuffer {
+    width: u32,
+    height: u32,
+    anti_aliasing: AntiAliasing,
+    samples: usize,
+    depth_test: DepthTest,
+    fog: Option<Fog>,
+    clear_colour: Colour,
+
+    colour: Vec<u8>,
+    depth: Vec<f64>,
+    resolved: Vec<u8>,
+
+    /// The weighted sum of the premultiplied transparent colours drawn to each pixel, and of their alphas.
+    accumulation: Vec<[f64; 4]>,
+    /// The fraction of each pixel's stored colour still visible through the transparent colours drawn to it.
+    revealage: Vec<f64>,
+
+    /// The edge table polygons are filled from, kept between polygons to reuse its rows.
+    edge_table: EdgeTable,
+    /// The list meshes' edges are gathered into, kept between meshes to reuse its allocations.
+    edge_list: EdgeList,
+}
+
+/// A rectangular region of a frame buffer that can be drawn into independently of the rest of it, so that tiles can
+/// be drawn on separate threads. Tiles report the size of the whole buffer and use its coordinates, but nothing is
+/// drawn outside of their region.
+/// Each buffer holds a slice of every row of the region, starting with the top row.
+///
+pub struct Tile<'a> {
+    width: u32,
+    height: u32,
+    region: Rect,
+    anti_aliasing: AntiAliasing,
+    samples: usize,
+    depth_test: DepthTest,
+    fog: Option<Fog>,
+    clear_colour: Colour,
+
+    colour: Vec<&'a mut [u8]>,
+    depth: Vec<&'a mut [f64]>,
+    accumulation: Vec<&'a mut [[f64; 4]]>,
+    revealage: Vec<&'a mut [f64]>,
+    edge_table: EdgeTable,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl BlendMode {
+    /// Return the result of blending a colour over another.
+    ///
+    pub fn apply(&self, under: Colour, over: Colour) -> Colour {
+        let alpha = over[3] as f64 / 255.0;
+        let [r, g, b, _] = over;
+
+        match self {
+            BlendMode::Opaque => over,
+            BlendMode::DepthOnly => under,
+            BlendMode::Alpha | BlendMode::WeightedBlended => blend(under, [r, g, b, 255], alpha),
+            BlendMode::Additive => {
+                let mut colour = blend(under, [0, 0, 0, 255], alpha);
+                colour[0..3]
+                    .iter_mut()
+                    .zip(under.iter().zip(over.iter()))
+                    .for_each(|(channel, (&under, &over))| {
+                        *channel = (under as f64 + (over as f64 * alpha)).min(255.0).round() as u8
+                    });
+                colour
+            }
+            BlendMode::Multiply => {
+                let mut colour = under;
+                colour[0..3]
+                    .iter_mut()
+                    .zip(over.iter())
+                    .for_each(|(channel, &over)| {
+                        let factor = 1.0 - alpha + (over as f64 / 255.0 * alpha);
+                        *channel = (*channel as f64 * factor).round() as u8
+                    });
+                colour
+            }
+        }
+    }
+
+    /// Return true if the blend mode mixes colours with those already stored, rather than replacing them. Transparent
+    /// modes leave the depth buffer untouched.
+    ///
+    pub fn is_transparent(&self) -> bool {
+        !matches!(self, BlendMode::Opaque | BlendMode::DepthOnly)
+    }
+}
+
+impl DepthTest {
+    /// Return true if a depth passes the test against a stored depth.
+    ///
+    pub fn passes(&self, depth: f64, stored: f64) -> bool {
+        match self {
+            DepthTest::Greater => depth > stored,
+            DepthTest::GreaterEqual => depth >= stored,
+        }
+    }
+}
+
+impl LineStyle {
+    /// Return a new anti-aliased line style without depth testing.
+    ///
+    pub fn new(colour: Colour) -> LineStyle {
+        LineStyle {
+            colour,
+            anti_aliased: true,
+            depth_test: false,
+            depth_bias: 1.0,
+        }
+    }
+}
+
+impl FrameBuffer {
+    /// Return a new cleared frame buffer of the given size.
+    ///
+    pub fn new(width: u32, height: u32) -> FrameBuffer {
+        let size = (width * height) as usize;
+
+        FrameBuffer {
+            width,
+            height,
+            anti_aliasing: AntiAliasing::Off,
+            samples: 1,
+            depth_test: DepthTest::Greater,
+            fog: None,
+            clear_colour: [0, 0, 0, 0],
+            colour: vec![0; size * 4],
+            depth: vec![0.0; size],
+            resolved: Vec::new(),
+            accumulation: Vec::new(),
+            revealage: Vec::new(),
+            edge_table: EdgeTable::default(),
+            edge_list: EdgeList::default(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl FrameBuffer {
+    /// Resize the buffer. Its contents are cleared.
+    ///
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (anti_aliasing, depth_test) = (self.anti_aliasing, self.depth_test);
+        let (fog, clear_colour) = (self.fog, self.clear_colour);
+        *self = FrameBuffer::new(width, height);
+        self.depth_test = depth_test;
+        self.fog = fog;
+        self.clear_colour = clear_colour;
+        self.set_anti_aliasing(anti_aliasing);
+    }
+
+    /// Change the test fragments must pass against the stored depth to be drawn.
+    ///
+    pub fn set_depth_test(&mut self, depth_test: DepthTest) {
+        self.depth_test = depth_test;
+    }
+
+    /// Change the fog that shaded fragments are blended towards, or remove it.
+    ///
+    pub fn set_fog(&mut self, fog: Option<Fog>) {
+        self.fog = fog;
+    }
+
+    /// Change the colour the colour buffer is cleared to. Takes effect the next time the buffer is cleared.
+    ///
+    pub fn set_clear_colour(&mut self, colour: Colour) {
+        self.clear_colour = colour;
+    }
+
+    /// Change how the buffer is anti-aliased. Its contents are cleared.
+    ///
+    pub fn set_anti_aliasing(&mut self, anti_aliasing: AntiAliasing) {
+        let size = (self.width * self.height) as usize;
+        let samples = anti_aliasing.samples();
+
+        self.anti_aliasing = anti_aliasing;
+        self.samples = samples;
+        self.colour = vec![0; size * samples * 4];
+        self.depth = vec![0.0; size * samples];
+        self.resolved = if samples > 1 {
+            vec![0; size * 4]
+        } else {
+            Vec::new()
+        };
+        self.clear();
+    }
+
+    /// Average the samples of each pixel into the resolved colour buffer. Does nothing if the buffer isn't
+    /// anti-aliased.
+    ///
+    pub fn resolve(&mut self) {
+        if self.samples == 1 {
+            return;
+        }
+
+        let samples = self.samples;
+        for (pixel, colours) in self
+            .resolved
+            .chunks_exact_mut(4)
+            .zip(self.colour.chunks_exact(samples * 4))
+        {
+            pixel.copy_from_slice(&average(colours, samples));
+        }
+    }
+
+    /// Return the colour buffer as rows of RGBA bytes, starting with the top row. Anti-aliased buffers return the
+    /// colours from the last time they were resolved.
+    ///
+    pub fn frame(&self) -> &[u8] {
+        if self.samples == 1 {
+            &self.colour
+        } else {
+            &self.resolved
+        }
+    }
+
+    /// Split the buffer into tiles of up to the given size, starting from the bottom left and working along each row
+    /// of tiles. If transparency is true, the tiles can accumulate weighted blended transparency, and the buffers for
+    /// it are allocated if they haven't been already. The list of tiles is overwritten, reusing the allocations of
+    /// any tiles released into it.
+    ///
+    pub fn split_into_tiles<'a>(
+        &'a mut self,
+        tile_size: u32,
+        transparency: bool,
+        tiles: &mut Vec<Tile<'a>>,
+    ) {
+        assert!(tile_size > 0, "tiles must be at least 1 pixel wide");
+        if self.width == 0 || self.height == 0 {
+            tiles.clear();
+            return;
+        }
+        if transparency {
+            self.reserve_transparency();
+        }
+
+        let (width, height, samples) = (self.width, self.height, self.samples);
+        let (anti_aliasing, depth_test) = (self.anti_aliasing, self.depth_test);
+        let (fog, clear_colour) = (self.fog, self.clear_colour);
+        let columns = width.div_ceil(tile_size);
+        let rows = height.div_ceil(tile_size);
+
+        tiles.truncate((columns * rows) as usize);
+        tiles.resize_with((columns * rows) as usize, || Tile {
+            width,
+            height,
+            region: Rect::new(0, 0, 0, 0),
+            anti_aliasing,
+            samples,
+            depth_test,
+            fog,
+            clear_colour,
+            colour: Vec::new(),
+            depth: Vec::new(),
+            accumulation: Vec::new(),
+            revealage: Vec::new(),
+            edge_table: EdgeTable::default(),
+        });
+        for (i, tile) in (0..).zip(tiles.iter_mut()) {
+            let (x, y) = ((i % columns) * tile_size, (i / columns) * tile_size);
+            tile.width = width;
+            tile.height = height;
+            tile.region = Rect::new(x, y, tile_size.min(width - x), tile_size.min(height - y));
+            tile.anti_aliasing = anti_aliasing;
+            tile.samples = samples;
+            tile.depth_test = depth_test;
+            tile.fog = fog;
+            tile.clear_colour = clear_colour;
+            tile.colour.clear();
+            tile.depth.clear();
+            tile.accumulation.clear();
+            tile.revealage.clear();
+        }
+
+        // Rows are stored from the top down, and each is split between the tiles along it.
+        let (row_pixels, tile_pixels) = (width as usize, tile_size as usize);
+        let colour_rows = self.colour.chunks_exact_mut(row_pixels * samples * 4);
+        let depth_rows = self.depth.chunks_exact_mut(row_pixels * samples);
+        for (row, (colour, depth)) in colour_rows.zip(depth_rows).enumerate() {
+            let first = ((height as usize - (row + 1)) / tile_pixels) * columns as usize;
+            let segments = colour
+                .chunks_mut(tile_pixels * samples * 4)
+                .zip(depth.chunks_mut(tile_pixels * samples));
+            for (tile, (colour, depth)) in tiles[first..].iter_mut().zip(segments) {
+                tile.colour.push(colour);
+                tile.depth.push(depth);
+            }
+        }
+
+        if transparency {
+            let accumulation_rows = self.accumulation.chunks_exact_mut(row_pixels);
+            let revealage_rows = self.revealage.chunks_exact_mut(row_pixels);
+            for (row, (accumulation, revealage)) in
+                accumulation_rows.zip(revealage_rows).enumerate()
+            {
+                let first = ((height as usize - (row + 1)) / tile_pixels) * columns as usize;
+                let segments = accumulation
+                    .chunks_mut(tile_pixels)
+                    .zip(revealage.chunks_mut(tile_pixels));
+                for (tile, (accumulation, revealage)) in tiles[first..].iter_mut().zip(segments) {
+                    tile.accumulation.push(accumulation);
+                    tile.revealage.push(revealage);
+                }
+            }
+        }
+    }
+
+    /// Allocate the buffers for weighted blended transparency if they haven't been already.
+    ///
+    fn reserve_transparency(&mut self) {
+        if self.revealage.is_empty() {
+            let size = (self.width * self.height) as usize;
+            self.accumulation = vec![[0.0; 4]; size];
+            self.revealage = vec![1.0; size];
+        }
+    }
+
+    /// Return the colours of a pixel's samples, stored one after another.
+    ///
+    fn colours(&self, x: u32, y: u32) -> &[u8] {
+        let element = self.index(x, y) * 4;
+        &self.colour[element..(element + (self.samples * 4))]
+    }
+
+    /// Return the colours of a pixel's samples mutably.
+    ///
+    fn colours_mut(&mut self, x: u32, y: u32) -> &mut [u8] {
+        let element = self.index(x, y) * 4;
+        &mut self.colour[element..(element + (self.samples * 4))]
+    }
+
+    /// Return the depths of a pixel's samples.
+    ///
+    fn depths(&self, x: u32, y: u32) -> &[f64] {
+        let element = self.index(x, y);
+        &self.depth[element..(element + self.samples)]
+    }
+
+    /// Return the depths of a pixel's samples mutably.
+    ///
+    fn depths_mut(&mut self, x: u32, y: u32) -> &mut [f64] {
+        let element = self.index(x, y);
+        &mut self.depth[element..(element + self.samples)]
+    }
+
+    /// Return the index of a pixel's first sample within the depth buffer.
+    ///
+    fn index(&self, x: u32, y: u32) -> usize {
+        self.pixel_index(x, y) * self.samples
+    }
+
+    /// Return the index of a pixel within the buffers that store a single value for each pixel.
+    ///
+    fn pixel_index(&self, x: u32, y: u32) -> usize {
+        let y_invert = self.height - (y + 1);
+        ((y_invert * self.width) + x) as usize
+    }
+}
+
+impl Tile<'_> {
+    /// Return the colours of a pixel's samples, stored one after another.
+    ///
+    fn colours(&self, x: u32, y: u32) -> &[u8] {
+        let (row, column) = self.locate(x, y);
+        let element = column * self.samples * 4;
+        &self.colour[row][element..(element + (self.samples * 4))]
+    }
+
+    /// Return the colours of a pixel's samples mutably.
+    ///
+    fn colours_mut(&mut self, x: u32, y: u32) -> &mut [u8] {
+        let (row, column) = self.locate(x, y);
+        let element = column * self.samples * 4;
+        &mut self.colour[row][element..(element + (self.samples * 4))]
+    }
+
+    /// Return the depths of a pixel's samples.
+    ///
+    fn depths(&self, x: u32, y: u32) -> &[f64] {
+        let (row, column) = self.locate(x, y);
+        let element = column * self.samples;
+        &self.depth[row][element..(element + self.samples)]
+    }
+
+    /// Return the depths of a pixel's samples mutably.
+    ///
+    fn depths_mut(&mut self, x: u32, y: u32) -> &mut [f64] {
+        let (row, column) = self.locate(x, y);
+        let element = column * self.samples;
+        &mut self.depth[row][element..(element + self.samples)]
+    }
+
+    /// Return the row and column of a pixel within the tile's buffers.
+    ///
+    fn locate(&self, x: u32, y: u32) -> (usize, usize) {
+        debug_assert!(self.region.contains(x, y));
+        (
+            (self.region.top() - (y + 1)) as usize,
+            (x - self.region.x) as usize,
+        )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl RenderTarget for FrameBuffer {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Anti-aliased pixels return the average colour of their samples.
+    ///
+    fn get_pixel(&self, x: u32, y: u32) -> Colour {
+        average(self.colours(x, y), self.samples)
+    }
+
+    /// Anti-aliased pixels set the colour of every sample.
+    ///
+    fn draw_pixel(&mut self, x: u32, y: u32, colour: Colour) {
+        fill(self.colours_mut(x, y), colour);
+    }
+
+    /// Anti-aliased pixels return the depth of their furthest sample.
+    ///
+    fn get_depth(&self, x: u32, y: u32) -> f64 {
+        furthest(self.depths(x, y))
+    }
+
+    /// Anti-aliased pixels set the depth of every sample.
+    ///
+    fn set_depth(&mut self, x: u32, y: u32, depth: f64) {
+        self.depths_mut(x, y).fill(depth);
+    }
+
+    fn clear(&mut self) {
+        fill(&mut self.colour, self.clear_colour);
+        self.depth.fill(0.0);
+        fill(&mut self.resolved, self.clear_colour);
+        self.accumulation.fill([0.0; 4]);
+        self.revealage.fill(1.0);
+    }
+
+    fn anti_aliasing(&self) -> AntiAliasing {
+        self.anti_aliasing
+    }
+
+    fn depth_test(&self) -> DepthTest {
+        self.depth_test
+    }
+
+    fn fog(&self) -> Option<Fog> {
+        self.fog
+    }
+
+    fn clear_colour(&self) -> Colour {
+        self.clear_colour
+    }
+
+    fn get_sample(&self, x: u32, y: u32, sample: usize) -> Colour {
+        sample_colour(self.colours(x, y), sample)
+    }
+
+    fn get_sample_depth(&self, x: u32, y: u32, sample: usize) -> f64 {
+        self.depths(x, y)[sample]
+    }
+
+    fn draw_sample(&mut self, x: u32, y: u32, sample: usize, colour: Colour, depth: f64) {
+        self.colours_mut(x, y)[(sample * 4)..((sample * 4) + 4)].copy_from_slice(&colour);
+        self.depths_mut(x, y)[sample] = depth;
+    }
+
+    fn accumulate(&mut self, x: u32, y: u32, colour: Colour, depth: f64, coverage: f64) {
+        self.reserve_transparency();
+
+        let pixel = self.pixel_index(x, y);
+        add_transparency(
+            &mut self.accumulation[pixel],
+            &mut self.revealage[pixel],
+            colour,
+            depth,
+            coverage,
+        );
+    }
+
+    /// The average transparent colour of each pixel is blended over each of its samples.
+    ///
+    fn composite_transparency(&mut self) {
+        composite(
+            &mut self.colour,
+            &mut self.accumulation,
+            &mut self.revealage,
+            self.samples,
+        );
+    }
+
+    fn take_edge_table(&mut self) -> EdgeTable {
+        std::mem::take(&mut self.edge_table)
+    }
+
+    fn restore_edge_table(&mut self, edge_table: EdgeTable) {
+        self.edge_table = edge_table;
+    }
+
+    fn take_edge_list(&mut self) -> EdgeList {
+        std::mem::take(&mut self.edge_list)
+    }
+
+    fn restore_edge_list(&mut self, edge_list: EdgeList) {
+        self.edge_list = edge_list;
+    }
+}
+
+/// Tiles are cleared and composited separately from the rest of their frame buffer.
+///
+impl RenderTarget for Tile<'_> {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn get_pixel(&self, x: u32, y: u32) -> Colour {
+        average(self.colours(x, y), self.samples)
+    }
+
+    fn draw_pixel(&mut self, x: u32, y: u32, colour: Colour) {
+        fill(self.colours_mut(x, y), colour);
+    }
+
+    fn get_depth(&self, x: u32, y: u32) -> f64 {
+        furthest(self.depths(x, y))
+    }
+
+    fn set_depth(&mut self, x: u32, y: u32, depth: f64) {
+        self.depths_mut(x, y).fill(depth);
+    }
+
+    fn clear(&mut self) {
+        let clear_colour = self.clear_colour;
+        self.colour
+            .iter_mut()
+            .for_each(|row| fill(row, clear_colour));
+        self.depth.iter_mut().for_each(|row| row.fill(0.0));
+        self.accumulation
+            .iter_mut()
+            .for_each(|row| row.fill([0.0; 4]));
+        self.revealage.iter_mut().for_each(|row| row.fill(1.0));
+    }
+
+    fn region(&self) -> Rect {
+        self.region
+    }
+
+    fn anti_aliasing(&self) -> AntiAliasing {
+        self.anti_aliasing
+    }
+
+    fn depth_test(&self) -> DepthTest {
+        self.depth_test
+    }
+
+    fn fog(&self) -> Option<Fog> {
+        self.fog
+    }
+
+    fn clear_colour(&self) -> Colour {
+        self.clear_colour
+    }
+
+    fn get_sample(&self, x: u32, y: u32, sample: usize) -> Colour {
+        sample_colour(self.colours(x, y), sample)
+    }
+
+    fn get_sample_depth(&self, x: u32, y: u32, sample: usize) -> f64 {
+        self.depths(x, y)[sample]
+    }
+
+    fn draw_sample(&mut self, x: u32, y: u32, sample: usize, colour: Colour, depth: f64) {
+        self.colours_mut(x, y)[(sample * 4)..((sample * 4) + 4)].copy_from_slice(&colour);
+        self.depths_mut(x, y)[sample] = depth;
+    }
+
+    /// Panics if the tile was split from its frame buffer without transparency.
+    ///
+    fn accumulate(&mut self, x: u32, y: u32, colour: Colour, depth: f64, coverage: f64) {
+        assert!(
+            !self.revealage.is_empty(),
+            "the tile can't accumulate transparency"
+        );
+
+        let (row, column) = self.locate(x, y);
+        add_transparency(
+            &mut self.accumulation[row][column],
+            &mut self.revealage[row][column],
+            colour,
+            depth,
+            coverage,
+        );
+    }
+
+    fn composite_transparency(&mut self) {
+        let rows = self
+            .colour
+            .iter_mut()
+            .zip(self.accumulation.iter_mut().zip(self.revealage.iter_mut()));
+        for (colour, (accumulation, revealage)) in rows {
+            composite(colour, accumulation, revealage, self.samples);
+        }
+    }
+
+    fn take_edge_table(&mut self) -> EdgeTable {
+        std::mem::take(&mut self.edge_table)
+    }
+
+    fn restore_edge_table(&mut self, edge_table: EdgeTable) {
+        self.edge_table = edge_table;
+    }
+}
+
+/// Add a fragment's colour to a pixel's weighted blended transparency, given the fraction of the pixel it covers.
+///
+fn add_transparency(
+    accumulation: &mut [f64; 4],
+    revealage: &mut f64,
+    colour: Colour,
+    depth: f64,
+    coverage: f64,
+) {
+    let [r, g, b, alpha] = colour.map(|channel| channel as f64 / 255.0);
+    let weight = transparency_weight(alpha, depth) * coverage;
+
+    for (sum, channel) in accumulation
+        .iter_mut()
+        .zip([r * alpha, g * alpha, b * alpha, alpha])
+    {
+        *sum += channel * weight;
+    }
+    *revealage *= (1.0 - alpha).powf(coverage);
+}
+
+/// Blend the average transparent colour accumulated for each of a run of pixels over each of its samples, and reset
+/// the accumulated transparency.
+///
+fn composite(
+    colours: &mut [u8],
+    accumulation: &mut [[f64; 4]],
+    revealage: &mut [f64],
+    samples: usize,
+) {
+    let pixels = colours
+        .chunks_exact_mut(samples * 4)
+        .zip(accumulation.iter_mut().zip(revealage.iter_mut()));
+
+    for (colours, (accumulation, revealage)) in pixels {
+        if *revealage < 1.0 {
+            let total = accumulation[3].max(1e-5);
+            let [r, g, b] = [0, 1, 2].map(|i| (accumulation[i] / total * 255.0).min(255.0) as u8);
+            let over = [r, g, b, ((1.0 - *revealage) * 255.0).round() as u8];
+
+            for sample in colours.chunks_exact_mut(4) {
+                let under = sample_colour(sample, 0);
+                sample.copy_from_slice(&BlendMode::Alpha.apply(under, over));
+            }
+        }
+
+        *accumulation = [0.0; 4];
+        *revealage = 1.0;
+    }
+}
+
+/// Return the colour of one of a number of RGBA colours stored one after another.
+///
+fn sample_colour(colours: &[u8], sample: usize) -> Colour {
+    let mut colour = Colour::default();
+    colour.copy_from_slice(&colours[(sample * 4)..((sample * 4) + 4)]);
+    colour
+}
+
+/// Set every one of a number of RGBA colours stored one after another to the same colour.
+///
+fn fill(colours: &mut [u8], colour: Colour) {
+    colours
+        .chunks_exact_mut(4)
+        .for_each(|sample| sample.copy_from_slice(&colour));
+}
+
+/// Return the furthest of a number of depths.
+///
+fn furthest(depths: &[f64]) -> f64 {
+    depths.iter().copied().fold(f64::MAX, f64::min)
+}
+
+/// Return the average of a number of RGBA colours stored one after another, rounded to the nearest value.
+///
+fn average(colours: &[u8], count: usize) -> Colour {
+    let mut sums = [0; 4];
+    for colour in colours.chunks_exact(4) {
+        sums.iter_mut()
+            .zip(colour.iter())
+            .for_each(|(sum, &channel)| *sum += channel as usize);
+    }
+    sums.map(|sum| ((sum + (count / 2)) / count) as u8)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        camera::Camera,
+        fog::FogFalloff,
+        golden::{assert_draws_match, overlapping_scene},
+        lighting::{Light, Material},
+        mesh::{
+            geometry::{Point, Vector},
+            Vertex,
+        },
+        projection::{Frustum, Projection},
+        rasterizer::{SampleCount, SamplePattern},
+        shader::{LitShader, ShadingMode},
+    };
+
+    #[test]
+    fn test_pixel_origin() {
+        let mut buffer = FrameBuffer::new(4, 3);
+        buffer.draw_pixel(1, 0, [1, 2, 3, 4]);
+
+        assert_eq!(buffer.get_pixel(1, 0), [1, 2, 3, 4]);
+
+        // The bottom row is stored last.
+        let element = ((2 * 4) + 1) * 4;
+        assert_eq!(buffer.frame()[element..(element + 4)], [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_draw_line_depth_test() {
+        let mut buffer = FrameBuffer::new(8, 4);
+        buffer.set_depth(2, 1, 10.0);
+        buffer.set_depth(5, 1, 3.0);
+
+        let mut style = LineStyle::new([255, 255, 255, 255]);
+        style.anti_aliased = false;
+        style.depth_test = true;
+        let from = Vertex::new([0.5, 1.5, 5.0, 1.0]);
+        let to = Vertex::new([7.5, 1.5, 5.0, 1.0]);
+        let edge = RefEdge::new(&from, &to);
+        buffer.draw_line(&edge, &style);
+
+        // The line is hidden where the stored depth is closer, and doesn't change the depth buffer.
+        assert_eq!(buffer.get_pixel(2, 1), [0, 0, 0, 0]);
+        assert_eq!(buffer.get_pixel(5, 1), [255, 255, 255, 255]);
+        assert_eq!(buffer.get_depth(5, 1), 3.0);
+
+        // Lines just behind the stored depth are still drawn.
+        buffer.set_depth(2, 1, 5.5);
+        buffer.draw_line(&edge, &style);
+        assert_eq!(buffer.get_pixel(2, 1), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_blend() {
+        assert_eq!(
+            blend([0, 0, 0, 0], [255, 100, 0, 255], 0.5),
+            [128, 50, 0, 128]
+        );
+        assert_eq!(
+            blend([10, 20, 30, 40], [255, 255, 255, 255], 0.0),
+            [10, 20, 30, 40]
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut buffer = FrameBuffer::new(4, 3);
+        buffer.set_anti_aliasing(AntiAliasing::Multisample(
+            SampleCount::X4,
+            SamplePattern::Grid,
+        ));
+        buffer.draw_pixel(1, 0, [100, 100, 100, 255]);
+        buffer.draw_sample(1, 0, 2, [200, 0, 100, 255], 8.0);
+        buffer.draw_sample(2, 0, 0, [255, 255, 255, 255], 5.0);
+
+        // Pixels are the average of their samples, and only get as close as their furthest sample.
+        assert_eq!(buffer.get_pixel(1, 0), [125, 75, 100, 255]);
+        assert_eq!(buffer.get_pixel(2, 0), [64, 64, 64, 64]);
+        assert_eq!(buffer.get_depth(2, 0), 0.0);
+        assert_eq!(buffer.get_sample_depth(1, 0, 2), 8.0);
+
+        // The frame only changes when it's resolved.
+        let element = ((2 * 4) + 1) * 4;
+        assert_eq!(buffer.frame()[element..(element + 4)], [0, 0, 0, 0]);
+        buffer.resolve();
+        assert_eq!(buffer.frame()[element..(element + 4)], [125, 75, 100, 255]);
+    }
+
+    #[test]
+    fn test_multisample_shades_once() {
+        // A triangle covering the right half of the samples in the middle column of pixels.
+        let verts = [
+            Vertex::new([1.5, 0.0, 10.0, 1.0]),
+            Vertex::new([1.5, 3.0, 10.0, 1.0]),
+            Vertex::new([4.0, 0.0, 10.0, 1.0]),
+        ];
+        let normal = Vector::new([0, 0, -1]);
+        let polygon = RefPoly::new(&verts[0], &verts[1], &verts[2], &normal);
+
+        for (anti_aliasing, expected) in [
+            (
+                AntiAliasing::Supersample(SampleCount::X4, SamplePattern::Grid),
+                2,
+            ),
+            (
+                AntiAliasing::Multisample(SampleCount::X4, SamplePattern::Grid),
+                1,
+            ),
+        ] {
+            let mut buffer = FrameBuffer::new(4, 3);
+            buffer.set_anti_aliasing(anti_aliasing);
+
+            let shaded = std::cell::Cell::new(0);
+            let blend = BlendMode::Opaque;
+            buffer.fill_polygon(&polygon, RasterMethod::EdgeTable, blend, |fragment| {
+                if (fragment.x, fragment.y) == (1, 1) {
+                    shaded.set(shaded.get() + 1);
+                }
+                Some([255, 255, 255, 255])
+            });
+
+            // Half of the middle pixel's samples are covered, but it's only shaded once when multisampling. The
+            // result is the same either way.
+            assert_eq!(shaded.get(), expected);
+            assert_eq!(buffer.get_pixel(1, 1), [128, 128, 128, 128]);
+            assert_eq!(buffer.get_pixel(1, 2), [64, 64, 64, 64]);
+        }
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        let (under, over) = ([100, 200, 50, 255], [255, 0, 100, 128]);
+
+        assert_eq!(BlendMode::Opaque.apply(under, over), over);
+        assert_eq!(BlendMode::Alpha.apply(under, over), [178, 100, 75, 255]);
+        assert_eq!(BlendMode::Additive.apply(under, over), [228, 200, 100, 255]);
+        assert_eq!(BlendMode::Multiply.apply(under, over), [100, 100, 35, 255]);
+
+        // Transparent colours leave the stored colour alone.
+        for blend in [BlendMode::Alpha, BlendMode::Additive, BlendMode::Multiply] {
+            assert_eq!(blend.apply(under, [255, 0, 0, 0]), under);
+        }
+    }
+
+    #[test]
+    fn test_transparent_fragments_keep_depth() {
+        let verts = [
+            Vertex::new([0.0, 0.0, 10.0, 1.0]),
+            Vertex::new([0.0, 4.0, 10.0, 1.0]),
+            Vertex::new([4.0, 0.0, 10.0, 1.0]),
+        ];
+        let normal = Vector::new([0, 0, -1]);
+        let polygon = RefPoly::new(&verts[0], &verts[1], &verts[2], &normal);
+
+        let mut buffer = FrameBuffer::new(4, 4);
+        buffer.draw_pixel(0, 0, [0, 0, 255, 255]);
+        buffer.fill_polygon(&polygon, RasterMethod::HalfSpace, BlendMode::Alpha, |_| {
+            Some([255, 0, 0, 128])
+        });
+
+        assert_eq!(buffer.get_pixel(0, 0), [128, 0, 127, 255]);
+        assert_eq!(buffer.get_depth(0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_weighted_blended_is_order_independent() {
+        let fragments = [([255, 0, 0, 128], 300.0), ([0, 0, 255, 200], 100.0)];
+
+        let mut results = Vec::new();
+        for order in [[0, 1], [1, 0]] {
+            let mut buffer = FrameBuffer::new(2, 2);
+            buffer.draw_pixel(1, 1, [0, 255, 0, 255]);
+            for i in order {
+                let (colour, depth) = fragments[i];
+                buffer.accumulate(1, 1, colour, depth, 1.0);
+            }
+
+            // Nothing changes until the transparency is composited.
+            assert_eq!(buffer.get_pixel(1, 1), [0, 255, 0, 255]);
+            buffer.composite_transparency();
+            results.push(buffer.get_pixel(1, 1));
+        }
+        assert_eq!(results[0], results[1]);
+
+        // The closer red fragment dominates, and a little of the green background shows through both fragments.
+        let [r, g, b, _] = results[0];
+        assert!(r > 200 && b < 40);
+        assert!(g > 0 && g < 40);
+    }
+
+    #[test]
+    fn test_depth_prepass() {
+        let meshes = overlapping_scene();
+        let material = Material::new([0.0, 1.0, 0.0]);
+        let lights = [Light::Ambient {
+            colour: [1.0, 1.0, 1.0],
+        }];
+        let shader = LitShader::new(&material, &lights, ShadingMode::Flat);
+        let line_style = LineStyle::new([255, 255, 255, 255]);
+
+        for (anti_aliasing, method) in [
+            (AntiAliasing::Off, RasterMethod::EdgeTable),
+            (AntiAliasing::Off, RasterMethod::HalfSpace),
+            (
+                AntiAliasing::Multisample(SampleCount::X4, SamplePattern::RotatedGrid),
+                RasterMethod::HalfSpace,
+            ),
+        ] {
+            assert_draws_match(
+                (anti_aliasing, method),
+                anti_aliasing,
+                |direct| {
+                    for mesh in &meshes {
+                        direct.draw_mesh(mesh, DrawType::Fill, method, &shader, &line_style);
+                    }
+                },
+                |prepass| {
+                    // Filling the depth buffer leaves the colour buffer untouched.
+                    for mesh in &meshes {
+                        prepass.draw_depth(mesh, method);
+                    }
+                    assert!(prepass.colour.iter().all(|&channel| channel == 0));
+                    assert!(prepass.depth.iter().any(|&depth| depth > 0.0));
+
+                    // Shading over the depths left by the prepass gives the same image.
+                    prepass.set_depth_test(DepthTest::GreaterEqual);
+                    for mesh in &meshes {
+                        prepass.draw_mesh(mesh, DrawType::Fill, method, &shader, &line_style);
+                    }
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_fog() {
+        let projection = Projection::perspective(45.0, 4.0 / 3.0, 100.0, 1000.0);
+        assert_fog(&projection);
+    }
+
+    #[test]
+    fn test_orthographic_fog() {
+        // Fog follows the view space depth, which isn't the clip space w for orthographic projections.
+        let frustum = Frustum::Orthographic { height: 300.0 };
+        let projection = Projection::new(frustum, 4.0 / 3.0, 100.0, Some(1000.0));
+        assert_fog(&projection);
+    }
+
+    /// Check that fog hides, mixes with or leaves alone a cube in the middle of the view, depending on its distance.
+    ///
+    fn assert_fog(projection: &Projection) {
+        let mut cube = Mesh::default();
+        cube.load_cube(100.0);
+        cube.physics.position = Point::new([0, 0, 500]);
+        let cube = cube.run_pipeline(&Camera::default(), projection, [80.0, 60.0]);
+
+        let material = Material::new([0.0, 1.0, 0.0]);
+        let lights = [Light::Ambient {
+            colour: [1.0, 1.0, 1.0],
+        }];
+        let shader = LitShader::new(&material, &lights, ShadingMode::Flat);
+        let line_style = LineStyle::new([255, 255, 255, 255]);
+
+        let draw_buffer = |fog: Option<Fog>, draw_type: DrawType| {
+            let mut buffer = FrameBuffer::new(80, 60);
+            buffer.set_fog(fog);
+            buffer.draw_mesh(
+                &cube,
+                draw_type,
+                RasterMethod::HalfSpace,
+                &shader,
+                &line_style,
+            );
+            buffer
+        };
+        let draw = |fog: Option<Fog>| draw_buffer(fog, DrawType::Fill).get_pixel(40, 30);
+        let pixels = |buffer: &FrameBuffer| {
+            let (width, height) = (buffer.width(), buffer.height());
+            (0..height)
+                .flat_map(move |y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| buffer.get_pixel(x, y))
+                .filter(|&pixel| pixel != [0, 0, 0, 0])
+                .collect::<Vec<_>>()
+        };
+        let red = [1.0, 0.0, 0.0];
+
+        // Fog that ends in front of the cube hides it, and fog that starts behind it leaves it untouched.
+        assert_eq!(draw(None), [0, 255, 0, 255]);
+        assert_eq!(
+            draw(Some(Fog::new(red, FogFalloff::Linear, 100.0, 200.0))),
+            [255, 0, 0, 255]
+        );
+        assert_eq!(
+            draw(Some(Fog::new(red, FogFalloff::Linear, 900.0, 1000.0))),
+            [0, 255, 0, 255]
+        );
+
+        // Part of the way through the fog the colours are mixed.
+        let [r, g, b, a] = draw(Some(Fog::new(red, FogFalloff::Linear, 0.0, 1000.0)));
+        assert!(r > 0 && g > 0 && (254..=255).contains(&(r as u32 + g as u32)));
+        assert_eq!([b, a], [0, 255]);
+
+        // The fog follows the distance from the camera rather than the depth, so it's thicker towards the corners of
+        // the face nearest the camera, even though they're at the same depth as its centre.
+        let buffer = draw_buffer(
+            Some(Fog::new(red, FogFalloff::Linear, 440.0, 470.0)),
+            DrawType::Fill,
+        );
+        let centre = buffer.get_pixel(40, 30)[1];
+        let greens: Vec<u8> = pixels(&buffer).iter().map(|pixel| pixel[1]).collect();
+        assert!(greens.iter().all(|&green| green <= centre));
+        assert!(greens.iter().any(|&green| green + 20 < centre));
+
+        // Edges are fogged in the same way as polygons.
+        let edges = pixels(&draw_buffer(None, DrawType::Wireframe));
+        assert!(edges.iter().any(|pixel| pixel[1] > 0));
+        let fog = Fog::new(red, FogFalloff::Linear, 100.0, 200.0);
+        let edges = pixels(&draw_buffer(Some(fog), DrawType::Wireframe));
+        assert!(!edges.is_empty());
+        assert!(edges
+            .iter()
+            .all(|pixel| pixel[0] > 0 && pixel[1] == 0 && pixel[2] == 0));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut buffer = FrameBuffer::new(4, 3);
+        buffer.draw_pixel(3, 2, [255, 255, 255, 255]);
+        buffer.set_depth(3, 2, 10.0);
+
+        buffer.clear();
+        assert_eq!(buffer.get_pixel(3, 2), [0, 0, 0, 0]);
+        assert_eq!(buffer.get_depth(3, 2), 0.0);
+
+        // The clear colour is kept when the buffer is resized or its anti-aliasing changes.
+        buffer.set_clear_colour([10, 20, 30, 255]);
+        buffer.clear();
+        assert_eq!(buffer.get_pixel(3, 2), [10, 20, 30, 255]);
+        buffer.resize(8, 6);
+        buffer.set_anti_aliasing(AntiAliasing::Supersample(
+            SampleCount::X4,
+            SamplePattern::Grid,
+        ));
+        assert_eq!(buffer.get_pixel(7, 5), [10, 20, 30, 255]);
+        buffer.resolve();
+        assert_eq!(buffer.frame()[0..4], [10, 20, 30, 255]);
+    }
+}
@@ -0,0 +1,898 @@
+//! Golden image regression tests for the rasterizer.
+//!
+//! Reference scenes are rendered into an in-memory frame buffer and compared against the images checked in under
+//! `tests/golden`. Set `UPDATE_GOLDEN=1` to write new golden images instead of comparing against them. When a
+//! comparison fails the rendered frame and an image highlighting the differing pixels are written to
+//! `target/golden`.
+//!
+
+use crate::{
+    camera::Camera,
+    framebuffer::{compare_draw_order, BlendMode, DrawType, FrameBuffer, LineStyle, RenderTarget},
+    image::{ColourType, Image},
+    lighting::{Attenuation, Light, Material, ShadingModel},
+    mesh::{
+        geometry::{Orientation3D, Point, Vector},
+        Attribute, AttributeLayout, CullMode, Matrix4X4, Mesh, NormalWeighting, RefPoly, Vertex,
+        VertexAttributes,
+    },
+    projection::Projection,
+    rasterizer::{AntiAliasing, RasterMethod, SampleCount, SamplePattern},
+    shader::{
+        FragmentInput, FragmentShader, LitShader, NormalShader, ProjectionShader, ShadingMode,
+        ToonShader, VertexInput, VertexShader,
+    },
+    texture::{Filter, Texture, WrapMode},
+};
+use std::{fmt::Debug, path::PathBuf};
+
+////////////////////////////////////////////////////////////////////////////////
+// Harness /////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 120;
+
+/// The largest difference allowed between a rendered and golden colour channel.
+const TOLERANCE: u8 = 2;
+
+/// Return a cube mesh at the given position and orientation.
+///
+fn cube(edge_length: f64, position: [f64; 3], orientation: [f64; 3]) -> Mesh {
+    let mut cube = Mesh::default();
+    cube.load_cube(edge_length);
+    cube.physics.position = Point::new(position);
+    cube.physics.set_euler_angles(Orientation3D::new(
+        orientation[0],
+        orientation[1],
+        orientation[2],
+    ));
+    cube
+}
+
+/// Return a mesh with culling disabled, so that its inside shows wherever it's cut open.
+///
+fn double_sided(mut mesh: Mesh) -> Mesh {
+    mesh.culling.mode = CullMode::None;
+    mesh
+}
+
+/// Return a cube mesh with each corner given a different vertex colour.
+///
+fn coloured_cube(edge_length: f64, position: [f64; 3], orientation: [f64; 3]) -> Mesh {
+    let mut cube = cube(edge_length, position, orientation);
+    let colours = [
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0, 1.0],
+        [0.0, 0.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0, 1.0],
+        [1.0, 1.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [1.0, 1.0, 0.0, 1.0],
+    ];
+    cube.set_attributes(VertexAttributes::new(
+        AttributeLayout::new(&[Attribute::Colour]),
+        colours.concat(),
+    ));
+    cube
+}
+
+/// Return a sphere mesh with vertex normals.
+///
+fn sphere(radius: f64, position: [f64; 3], weighting: NormalWeighting) -> Mesh {
+    let mut sphere = Mesh::default();
+    sphere.load_sphere(radius, 8, 12);
+    sphere.compute_vertex_normals(60.0, weighting);
+    sphere.physics.position = Point::new(position);
+    sphere
+}
+
+/// Return a textured cube mesh at the given position and orientation.
+///
+fn textured_cube(
+    edge_length: f64,
+    texture_scale: f64,
+    position: [f64; 3],
+    orientation: [f64; 3],
+) -> Mesh {
+    let mut cube = Mesh::default();
+    cube.load_textured_cube(edge_length, texture_scale);
+    cube.physics.position = Point::new(position);
+    cube.physics.set_euler_angles(Orientation3D::new(
+        orientation[0],
+        orientation[1],
+        orientation[2],
+    ));
+    cube
+}
+
+/// Return a white material with a checkerboard texture sampled with the given filter and wrap mode.
+///
+fn checkerboard(filter: Filter, wrap: WrapMode) -> Material {
+    let mut texture = Texture::checkerboard(32, 4, [[255, 255, 255, 255], [255, 0, 0, 255]]);
+    texture.filter = filter;
+    texture.wrap = wrap;
+
+    let mut material = Material::new([1.0, 1.0, 1.0]);
+    material.texture = Some(texture);
+    material
+}
+
+/// Return the lights used by most scenes: a dim ambient light and a directional light from just above and to the
+/// left of the camera.
+///
+fn default_lights() -> [Light; 2] {
+    [
+        Light::Ambient {
+            colour: [0.2, 0.2, 0.2],
+        },
+        Light::Directional {
+            direction: Vector::new([0.5, -0.5, 1.0]),
+            colour: [0.8, 0.8, 0.8],
+        },
+    ]
+}
+
+/// Render meshes into a new frame buffer with a green material and the default lights.
+///
+fn render(meshes: &[Mesh], method: RasterMethod) -> FrameBuffer {
+    render_lit(
+        meshes,
+        method,
+        ShadingMode::Flat,
+        &Material::new([0.0, 1.0, 0.0]),
+        &default_lights(),
+    )
+}
+
+/// Render meshes into a new frame buffer lit by the given material and lights.
+///
+fn render_lit(
+    meshes: &[Mesh],
+    method: RasterMethod,
+    shading: ShadingMode,
+    material: &Material,
+    lights: &[Light],
+) -> FrameBuffer {
+    render_shaded(
+        meshes,
+        method,
+        &ProjectionShader::with_projection(&projection()),
+        &LitShader::new(material, lights, shading),
+    )
+}
+
+/// Render meshes into a new frame buffer with the given vertex and fragment shaders.
+///
+fn render_shaded<V, F>(
+    meshes: &[Mesh],
+    method: RasterMethod,
+    vertex_shader: &V,
+    fragment_shader: &F,
+) -> FrameBuffer
+where
+    V: VertexShader,
+    F: FragmentShader,
+{
+    render_styled(
+        meshes,
+        method,
+        vertex_shader,
+        fragment_shader,
+        DrawType::Fill,
+        &LineStyle::new([255, 255, 255, 255]),
+    )
+}
+
+/// Render meshes into a new frame buffer with the given shaders, draw type and line style.
+///
+fn render_styled<V, F>(
+    meshes: &[Mesh],
+    method: RasterMethod,
+    vertex_shader: &V,
+    fragment_shader: &F,
+    draw_type: DrawType,
+    line_style: &LineStyle,
+) -> FrameBuffer
+where
+    V: VertexShader,
+    F: FragmentShader,
+{
+    let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+    for mesh in meshes {
+        let mesh = mesh.run_pipeline_with_shader(
+            vertex_shader,
+            &Camera::default(),
+            [WIDTH as f64, HEIGHT as f64],
+        );
+        buffer.draw_mesh(&mesh, draw_type, method, fragment_shader, line_style);
+    }
+    buffer
+}
+
+/// Return the same projection as the graphics window.
+///
+pub(crate) fn projection() -> Projection {
+    Projection::perspective(45.0, WIDTH as f64 / HEIGHT as f64, 100.0, 1000.0)
+}
+
+/// Return a textured cube and a sphere that overlap each other and the edges of the screen. Shared with the tests
+/// that check a scene comes out the same however it's drawn.
+///
+pub(crate) fn overlapping_meshes() -> [Mesh; 2] {
+    [
+        textured_cube(100.0, 1.5, [-20.0, 10.0, 300.0], [30.0, 20.0, 10.0]),
+        sphere(60.0, [40.0, -20.0, 280.0], NormalWeighting::Angle),
+    ]
+}
+
+/// Return the overlapping meshes run through the pipeline.
+///
+pub(crate) fn overlapping_scene() -> [Mesh; 2] {
+    overlapping_meshes().map(|mesh| {
+        mesh.run_pipeline(
+            &Camera::default(),
+            &projection(),
+            [WIDTH as f64, HEIGHT as f64],
+        )
+    })
+}
+
+/// Draw into 2 new frame buffers with the same anti-aliasing, one directly and one some other way, and check that
+/// something was drawn and that every sample's colour and depth are identical. Both buffers are resolved once
+/// they've been drawn into.
+///
+pub(crate) fn assert_draws_match<D, O>(
+    case: impl Debug,
+    anti_aliasing: AntiAliasing,
+    draw_direct: D,
+    draw_other: O,
+) where
+    D: FnOnce(&mut FrameBuffer),
+    O: FnOnce(&mut FrameBuffer),
+{
+    let mut direct = FrameBuffer::new(WIDTH, HEIGHT);
+    let mut other = FrameBuffer::new(WIDTH, HEIGHT);
+    direct.set_anti_aliasing(anti_aliasing);
+    other.set_anti_aliasing(anti_aliasing);
+    draw_direct(&mut direct);
+    draw_other(&mut other);
+    direct.resolve();
+    other.resolve();
+
+    assert!(direct.frame().iter().any(|&channel| channel != 0));
+    assert!(
+        direct.frame() == other.frame(),
+        "colours differ for {:?}",
+        case
+    );
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            for sample in 0..anti_aliasing.samples() {
+                assert_eq!(
+                    direct.get_sample(x, y, sample),
+                    other.get_sample(x, y, sample),
+                    "sample colours differ for {:?}",
+                    case
+                );
+                assert_eq!(
+                    direct.get_sample_depth(x, y, sample).to_bits(),
+                    other.get_sample_depth(x, y, sample).to_bits(),
+                    "depths differ for {:?}",
+                    case
+                );
+            }
+        }
+    }
+}
+
+/// Compare a frame against its golden image, panicking if any pixel differs by more than the tolerance.
+///
+fn assert_golden(name: &str, buffer: &FrameBuffer) {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let golden_path = root.join("tests/golden").join(format!("{}.ppm", name));
+    let frame = Image::from_colour(buffer);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+        frame.save(&golden_path).unwrap();
+        return;
+    }
+
+    let golden = match Image::open(&golden_path) {
+        Ok(golden) => golden,
+        Err(error) => panic!(
+            "Failed to open {}: {}. Run with UPDATE_GOLDEN=1 to create it.",
+            golden_path.display(),
+            error
+        ),
+    };
+    assert_eq!(
+        (golden.width(), golden.height()),
+        (frame.width(), frame.height()),
+        "Golden image {} has different dimensions",
+        name
+    );
+
+    // Build an image with differing pixels in red over a darkened copy of the golden image.
+    let mut mismatches = 0;
+    let mut diff = Vec::with_capacity(frame.data().len());
+    for (actual, expected) in frame.data().chunks(4).zip(golden.data().chunks(4)) {
+        let differs = actual[0..3]
+            .iter()
+            .zip(&expected[0..3])
+            .any(|(&a, &e)| a.abs_diff(e) > TOLERANCE);
+
+        if differs {
+            mismatches += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            diff.extend(expected[0..3].iter().map(|channel| channel / 4));
+            diff.push(255);
+        }
+    }
+
+    if mismatches > 0 {
+        let output = root.join("target/golden");
+        std::fs::create_dir_all(&output).unwrap();
+        frame
+            .save(output.join(format!("{}.actual.png", name)))
+            .unwrap();
+        Image::new(frame.width(), frame.height(), ColourType::Rgba, diff)
+            .save(output.join(format!("{}.diff.png", name)))
+            .unwrap();
+
+        panic!(
+            "{} pixels differ from golden image {}. See {} for the diff.",
+            mismatches,
+            name,
+            output.display()
+        );
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_cube_front() {
+    let buffer = render(
+        &[cube(100.0, [0.0, 0.0, 400.0], [0.0, 0.0, 0.0])],
+        RasterMethod::EdgeTable,
+    );
+    assert_golden("cube_front", &buffer);
+}
+
+#[test]
+fn test_cube_rotated() {
+    let buffer = render(
+        &[cube(100.0, [0.0, 0.0, 400.0], [30.0, 45.0, 0.0])],
+        RasterMethod::EdgeTable,
+    );
+    assert_golden("cube_rotated", &buffer);
+}
+
+#[test]
+fn test_cube_tumbling() {
+    let buffer = render(
+        &[cube(100.0, [0.0, 0.0, 400.0], [60.0, 36.0, 180.0])],
+        RasterMethod::EdgeTable,
+    );
+    assert_golden("cube_tumbling", &buffer);
+}
+
+#[test]
+fn test_overlapping_cubes() {
+    let buffer = render(
+        &[
+            cube(100.0, [-30.0, 0.0, 450.0], [20.0, 30.0, 0.0]),
+            cube(80.0, [30.0, 10.0, 380.0], [45.0, 10.0, 30.0]),
+        ],
+        RasterMethod::EdgeTable,
+    );
+    assert_golden("overlapping_cubes", &buffer);
+}
+
+#[test]
+fn test_cube_partly_off_screen() {
+    let buffer = render(
+        &[
+            cube(100.0, [-300.0, 0.0, 400.0], [15.0, 30.0, 0.0]),
+            cube(100.0, [60.0, 230.0, 400.0], [40.0, 0.0, 20.0]),
+        ],
+        RasterMethod::EdgeTable,
+    );
+    assert_golden("cube_partly_off_screen", &buffer);
+}
+
+#[test]
+fn test_cube_crossing_near_plane() {
+    let buffer = render(
+        &[double_sided(cube(
+            300.0,
+            [0.0, 0.0, 200.0],
+            [0.0, 30.0, 10.0],
+        ))],
+        RasterMethod::EdgeTable,
+    );
+    assert_golden("cube_crossing_near_plane", &buffer);
+}
+
+#[test]
+fn test_cube_covering_screen() {
+    let buffer = render(
+        &[cube(600.0, [0.0, 0.0, 500.0], [0.0, 0.0, 0.0])],
+        RasterMethod::EdgeTable,
+    );
+    assert_golden("cube_covering_screen", &buffer);
+}
+
+#[test]
+fn test_half_space_cube_rotated() {
+    let buffer = render(
+        &[cube(100.0, [0.0, 0.0, 400.0], [30.0, 45.0, 0.0])],
+        RasterMethod::HalfSpace,
+    );
+    assert_golden("half_space_cube_rotated", &buffer);
+}
+
+#[test]
+fn test_half_space_overlapping_cubes() {
+    let buffer = render(
+        &[
+            cube(100.0, [-30.0, 0.0, 450.0], [20.0, 30.0, 0.0]),
+            cube(80.0, [30.0, 10.0, 380.0], [45.0, 10.0, 30.0]),
+        ],
+        RasterMethod::HalfSpace,
+    );
+    assert_golden("half_space_overlapping_cubes", &buffer);
+}
+
+#[test]
+fn test_half_space_cube_partly_off_screen() {
+    let buffer = render(
+        &[
+            cube(100.0, [-300.0, 0.0, 400.0], [15.0, 30.0, 0.0]),
+            cube(100.0, [60.0, 230.0, 400.0], [40.0, 0.0, 20.0]),
+        ],
+        RasterMethod::HalfSpace,
+    );
+    assert_golden("half_space_cube_partly_off_screen", &buffer);
+}
+
+#[test]
+fn test_half_space_cube_covering_screen() {
+    let buffer = render(
+        &[cube(600.0, [0.0, 0.0, 500.0], [0.0, 0.0, 0.0])],
+        RasterMethod::HalfSpace,
+    );
+    assert_golden("half_space_cube_covering_screen", &buffer);
+}
+
+#[test]
+fn test_cull_front_faces() {
+    // Only the inside of the far faces is drawn, lit from behind.
+    let mut mesh = coloured_cube(100.0, [0.0, 0.0, 400.0], [30.0, 45.0, 0.0]);
+    mesh.culling.mode = CullMode::Front;
+
+    let buffer = render(&[mesh], RasterMethod::HalfSpace);
+    assert_golden("cull_front_faces", &buffer);
+}
+
+#[test]
+fn test_vertex_colours() {
+    let buffer = render(
+        &[coloured_cube(100.0, [0.0, 0.0, 400.0], [30.0, 45.0, 0.0])],
+        RasterMethod::EdgeTable,
+    );
+    assert_golden("vertex_colours", &buffer);
+}
+
+#[test]
+fn test_vertex_colours_crossing_near_plane() {
+    let buffer = render(
+        &[double_sided(coloured_cube(
+            300.0,
+            [0.0, 0.0, 200.0],
+            [0.0, 30.0, 10.0],
+        ))],
+        RasterMethod::HalfSpace,
+    );
+    assert_golden("vertex_colours_crossing_near_plane", &buffer);
+}
+
+#[test]
+fn test_texture_filters() {
+    for (filter, name) in [
+        (Filter::Nearest, "texture_nearest"),
+        (Filter::Bilinear, "texture_bilinear"),
+        (Filter::Trilinear, "texture_trilinear"),
+    ] {
+        let material = checkerboard(filter, WrapMode::Repeat);
+        let buffer = render_lit(
+            &[textured_cube(
+                100.0,
+                4.0,
+                [0.0, 0.0, 600.0],
+                [60.0, 30.0, 0.0],
+            )],
+            RasterMethod::HalfSpace,
+            ShadingMode::Flat,
+            &material,
+            &default_lights(),
+        );
+        assert_golden(name, &buffer);
+    }
+}
+
+#[test]
+fn test_texture_wrap_modes() {
+    for (wrap, name) in [
+        (WrapMode::Repeat, "texture_repeat"),
+        (WrapMode::Clamp, "texture_clamp"),
+        (WrapMode::Mirror, "texture_mirror"),
+    ] {
+        let material = checkerboard(Filter::Nearest, wrap);
+        let buffer = render_lit(
+            &[textured_cube(
+                100.0,
+                1.5,
+                [0.0, 0.0, 300.0],
+                [0.0, 0.0, 0.0],
+            )],
+            RasterMethod::EdgeTable,
+            ShadingMode::Flat,
+            &material,
+            &default_lights(),
+        );
+        assert_golden(name, &buffer);
+    }
+}
+
+#[test]
+fn test_point_and_spot_lights() {
+    let mut material = Material::new([1.0, 1.0, 1.0]);
+    material.model = ShadingModel::BlinnPhong;
+    material.specular_colour = [1.0, 1.0, 1.0];
+    material.shininess = 16.0;
+
+    let lights = [
+        Light::Ambient {
+            colour: [0.05, 0.05, 0.05],
+        },
+        Light::Point {
+            position: Point::new([-150, 100, 150]),
+            colour: [1.0, 0.3, 0.3],
+            attenuation: Attenuation::with_range(1500.0),
+        },
+        Light::Spot {
+            position: Point::new([0, 0, 0]),
+            direction: Vector::new([0.1, -0.1, 1.0]),
+            colour: [0.3, 0.3, 1.0],
+            attenuation: Attenuation::none(),
+            inner_angle: 3.0,
+            outer_angle: 6.0,
+        },
+    ];
+
+    let buffer = render_lit(
+        &[cube(150.0, [0.0, 0.0, 450.0], [30.0, 45.0, 0.0])],
+        RasterMethod::HalfSpace,
+        ShadingMode::Flat,
+        &material,
+        &lights,
+    );
+    assert_golden("point_and_spot_lights", &buffer);
+}
+
+#[test]
+fn test_shading_modes() {
+    let mut material = Material::new([0.8, 0.8, 0.8]);
+    material.model = ShadingModel::BlinnPhong;
+    material.specular_colour = [0.8, 0.8, 0.8];
+
+    let lights = [
+        Light::Ambient {
+            colour: [0.1, 0.1, 0.1],
+        },
+        Light::Point {
+            position: Point::new([-200, 200, 100]),
+            colour: [1.0, 1.0, 1.0],
+            attenuation: Attenuation::none(),
+        },
+    ];
+
+    for (shading, name) in [
+        (ShadingMode::Flat, "shading_flat"),
+        (ShadingMode::Gouraud, "shading_gouraud"),
+        (ShadingMode::Phong, "shading_phong"),
+    ] {
+        let buffer = render_lit(
+            &[
+                sphere(80.0, [-60.0, 0.0, 450.0], NormalWeighting::Angle),
+                sphere(60.0, [90.0, 0.0, 450.0], NormalWeighting::Area),
+            ],
+            RasterMethod::HalfSpace,
+            shading,
+            &material,
+            &lights,
+        );
+        assert_golden(name, &buffer);
+    }
+}
+
+#[test]
+fn test_crease_angle() {
+    // Below the crease angle the cube keeps its hard edges. Above it the cube is shaded like a sphere.
+    let mut meshes = [
+        cube(100.0, [-70.0, 0.0, 400.0], [30.0, 45.0, 0.0]),
+        cube(100.0, [70.0, 0.0, 400.0], [30.0, 45.0, 0.0]),
+    ];
+    meshes[0].compute_vertex_normals(80.0, NormalWeighting::Angle);
+    meshes[1].compute_vertex_normals(100.0, NormalWeighting::Angle);
+
+    let buffer = render_lit(
+        &meshes,
+        RasterMethod::EdgeTable,
+        ShadingMode::Phong,
+        &Material::new([0.0, 1.0, 0.0]),
+        &default_lights(),
+    );
+    assert_golden("crease_angle", &buffer);
+}
+
+#[test]
+fn test_effect_shaders() {
+    let meshes = [
+        sphere(70.0, [-70.0, 0.0, 450.0], NormalWeighting::Angle),
+        cube(90.0, [80.0, 0.0, 450.0], [30.0, 45.0, 0.0]),
+    ];
+    let vertex_shader = ProjectionShader::with_projection(&projection());
+
+    let toon = ToonShader::new([1.0, 0.6, 0.2], Vector::new([0.5, -0.5, 1.0]), 3);
+    let buffer = render_shaded(&meshes, RasterMethod::HalfSpace, &vertex_shader, &toon);
+    assert_golden("shader_toon", &buffer);
+
+    let buffer = render_shaded(
+        &meshes,
+        RasterMethod::HalfSpace,
+        &vertex_shader,
+        &NormalShader,
+    );
+    assert_golden("shader_normals", &buffer);
+}
+
+/// Vertex shader that passes each vertex's height above the origin to the fragment shader in a custom varying.
+///
+struct HeightShader(Matrix4X4);
+
+impl VertexShader for HeightShader {
+    fn varyings(&self, attributes: &AttributeLayout) -> AttributeLayout {
+        let mut layout = *attributes;
+        layout.push(Attribute::Custom(0));
+        layout
+    }
+
+    fn shade(&self, input: &VertexInput, varyings: &mut [f64]) -> Vertex {
+        let layout = self.varyings(input.layout);
+        varyings[..input.attributes.len()].copy_from_slice(input.attributes);
+        let [_, height, _] = input.attribute(Attribute::Position).unwrap_or_default();
+        layout.write(varyings, Attribute::Custom(0), &[height]);
+        *input.vertex * self.0
+    }
+}
+
+/// Fragment shader that cuts a cube into horizontal slats by discarding every other band of height.
+///
+struct SlatShader;
+
+impl FragmentShader for SlatShader {
+    type PolygonData = ();
+
+    fn prepare(&self, _polygon: &RefPoly) {}
+
+    fn shade(&self, _data: &(), input: &FragmentInput) -> Option<[f64; 4]> {
+        let [height] = input.varying(Attribute::Custom(0))?;
+        if (height / 10.0).floor().rem_euclid(2.0) == 1.0 {
+            return None;
+        }
+        let [x, y, z] = input.normal().0.map(|component| (component + 1.0) / 2.0);
+        Some([x, y, z, 1.0])
+    }
+}
+
+#[test]
+fn test_custom_shaders() {
+    // The back faces of the cube show through the discarded slats.
+    let buffer = render_shaded(
+        &[double_sided(cube(
+            100.0,
+            [0.0, 0.0, 400.0],
+            [30.0, 45.0, 0.0],
+        ))],
+        RasterMethod::EdgeTable,
+        &HeightShader(*projection().matrix()),
+        &SlatShader,
+    );
+    assert_golden("shader_custom", &buffer);
+}
+
+#[test]
+fn test_line_styles() {
+    let meshes = [
+        double_sided(cube(100.0, [-30.0, 0.0, 450.0], [20.0, 30.0, 0.0])),
+        cube(80.0, [30.0, 10.0, 380.0], [45.0, 10.0, 30.0]),
+    ];
+    let vertex_shader = ProjectionShader::with_projection(&projection());
+    let material = Material::new([0.0, 0.6, 0.0]);
+    let lights = default_lights();
+    let fragment_shader = LitShader::new(&material, &lights, ShadingMode::Flat);
+
+    let mut line_style = LineStyle::new([255, 255, 255, 255]);
+    for (draw_type, anti_aliased, name) in [
+        (DrawType::Wireframe, false, "wireframe_aliased"),
+        (DrawType::Wireframe, true, "wireframe"),
+        (DrawType::Both, true, "wireframe_filled"),
+        (DrawType::HiddenLine, true, "hidden_line"),
+    ] {
+        line_style.anti_aliased = anti_aliased;
+        let buffer = render_styled(
+            &meshes,
+            RasterMethod::HalfSpace,
+            &vertex_shader,
+            &fragment_shader,
+            draw_type,
+            &line_style,
+        );
+        assert_golden(name, &buffer);
+    }
+}
+
+#[test]
+fn test_anti_aliasing() {
+    let meshes = [
+        textured_cube(100.0, 2.0, [-30.0, 0.0, 450.0], [20.0, 30.0, 0.0]),
+        cube(80.0, [30.0, 10.0, 380.0], [45.0, 10.0, 30.0]),
+    ];
+    let material = checkerboard(Filter::Nearest, WrapMode::Repeat);
+    let lights = default_lights();
+    let shader = LitShader::new(&material, &lights, ShadingMode::Flat);
+    let line_style = LineStyle::new([255, 255, 255, 255]);
+
+    // Supersampling also smooths the texture, while multisampling only smooths the edges of polygons.
+    for (anti_aliasing, name) in [
+        (
+            AntiAliasing::Supersample(SampleCount::X4, SamplePattern::Grid),
+            "ssaa_4x_grid",
+        ),
+        (
+            AntiAliasing::Supersample(SampleCount::X8, SamplePattern::RotatedGrid),
+            "ssaa_8x_rotated",
+        ),
+        (
+            AntiAliasing::Multisample(SampleCount::X2, SamplePattern::Grid),
+            "msaa_2x_grid",
+        ),
+        (
+            AntiAliasing::Multisample(SampleCount::X4, SamplePattern::RotatedGrid),
+            "msaa_4x_rotated",
+        ),
+    ] {
+        let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+        buffer.set_anti_aliasing(anti_aliasing);
+        for mesh in &meshes {
+            let mesh = mesh.run_pipeline(
+                &Camera::default(),
+                &projection(),
+                [WIDTH as f64, HEIGHT as f64],
+            );
+            buffer.draw_mesh(
+                &mesh,
+                DrawType::Fill,
+                RasterMethod::EdgeTable,
+                &shader,
+                &line_style,
+            );
+        }
+        assert_golden(name, &buffer);
+    }
+}
+
+#[test]
+fn test_transparency() {
+    let meshes = [
+        cube(80.0, [30.0, 10.0, 480.0], [45.0, 10.0, 30.0]),
+        double_sided(cube(100.0, [-30.0, 0.0, 400.0], [20.0, 30.0, 0.0])),
+    ];
+    let opaque = Material::new([0.0, 1.0, 0.0]);
+    let mut glass = Material::new([1.0, 0.3, 0.3]);
+    glass.opacity = 0.5;
+    let lights = default_lights();
+    let line_style = LineStyle::new([255, 255, 255, 255]);
+
+    // The transparent cube is in front of the opaque one, and its back faces show through its front faces.
+    for (blend, name) in [
+        (BlendMode::Alpha, "blend_alpha"),
+        (BlendMode::Additive, "blend_additive"),
+        (BlendMode::Multiply, "blend_multiply"),
+        (BlendMode::WeightedBlended, "blend_weighted"),
+    ] {
+        glass.blend = blend;
+
+        let mut pipes: Vec<_> = meshes
+            .iter()
+            .zip([&opaque, &glass])
+            .map(|(mesh, material)| {
+                let mut mesh = mesh.run_pipeline(
+                    &Camera::default(),
+                    &projection(),
+                    [WIDTH as f64, HEIGHT as f64],
+                );
+                if material.blend == BlendMode::Alpha {
+                    mesh.sort_back_to_front();
+                }
+                (mesh, material)
+            })
+            .collect();
+
+        // Start with the transparent cube first to check the meshes are put in draw order.
+        pipes.reverse();
+        pipes.sort_by(|a, b| compare_draw_order((&a.0, a.1.blend), (&b.0, b.1.blend)));
+
+        let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+        for (mesh, material) in &pipes {
+            let shader = LitShader::new(material, &lights, ShadingMode::Flat);
+            buffer.draw_mesh(
+                mesh,
+                DrawType::Fill,
+                RasterMethod::HalfSpace,
+                &shader,
+                &line_style,
+            );
+        }
+        buffer.composite_transparency();
+        assert_golden(name, &buffer);
+    }
+}
+
+#[test]
+fn test_camera_look_at() {
+    let mut floor = Mesh::default();
+    floor.load_plane(600.0, 600.0);
+    floor.physics.position = Point::new([0, -60, 450]);
+    let meshes = [
+        floor,
+        cube(100.0, [-60.0, -10.0, 420.0], [0.0, 30.0, 0.0]),
+        sphere(50.0, [90.0, -10.0, 480.0], NormalWeighting::Angle),
+    ];
+
+    let mut material = Material::new([0.8, 0.8, 0.8]);
+    material.model = ShadingModel::BlinnPhong;
+    material.specular_colour = [0.8, 0.8, 0.8];
+    let lights = default_lights();
+
+    // Look down on the scene from above and to the right, with the highlights seen from the camera.
+    let camera = Camera::look_at(
+        &Point::new([250, 250, 150]),
+        &Point::new([0, -40, 450]),
+        &Vector::new([0, 1, 0]),
+    );
+    let mut shader = LitShader::new(&material, &lights, ShadingMode::Phong);
+    shader.eye = camera.position();
+    let line_style = LineStyle::new([255, 255, 255, 255]);
+
+    let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+    for mesh in &meshes {
+        let mesh = mesh.run_pipeline(&camera, &projection(), [WIDTH as f64, HEIGHT as f64]);
+        buffer.draw_mesh(
+            &mesh,
+            DrawType::Fill,
+            RasterMethod::HalfSpace,
+            &shader,
+            &line_style,
+        );
+    }
+    assert_golden("camera_look_at", &buffer);
+}
@@ -0,0 +1,77 @@
+//! Implementation of a writer for uncompressed 24 bit BMP files.
+//!
+
+use super::{ColourType, Image};
+use std::io::{Result, Write};
+
+const FILE_HEADER_SIZE: u32 = 14;
+const INFO_HEADER_SIZE: u32 = 40;
+
+/// Write an image as an uncompressed 24 bit BMP. The alpha channel is discarded and greyscale images are expanded
+/// to BGR.
+///
+pub fn write<W: Write>(image: &Image, writer: &mut W) -> Result<()> {
+    // Each row is padded to a multiple of 4 bytes.
+    let row_size = (image.width() * 3 + 3) & !3;
+    let data_size = row_size * image.height();
+    let data_offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+
+    // BITMAPFILEHEADER
+    writer.write_all(b"BM")?;
+    writer.write_all(&(data_offset + data_size).to_le_bytes())?;
+    writer.write_all(&[0; 4])?;
+    writer.write_all(&data_offset.to_le_bytes())?;
+
+    // BITMAPINFOHEADER
+    writer.write_all(&INFO_HEADER_SIZE.to_le_bytes())?;
+    writer.write_all(&(image.width() as i32).to_le_bytes())?;
+    writer.write_all(&(image.height() as i32).to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?; // Colour planes
+    writer.write_all(&24u16.to_le_bytes())?; // Bits per pixel
+    writer.write_all(&0u32.to_le_bytes())?; // No compression
+    writer.write_all(&data_size.to_le_bytes())?;
+    writer.write_all(&2835i32.to_le_bytes())?; // 72 DPI horizontally
+    writer.write_all(&2835i32.to_le_bytes())?; // 72 DPI vertically
+    writer.write_all(&0u32.to_le_bytes())?; // Palette size
+    writer.write_all(&0u32.to_le_bytes())?; // Important colours
+
+    // Pixel data is stored as BGR, starting with the bottom row.
+    let mut row_data = Vec::with_capacity(row_size as usize);
+    for row in image.rows().rev() {
+        row_data.clear();
+        match image.colour_type() {
+            ColourType::Grey => row.iter().for_each(|&grey| row_data.extend([grey; 3])),
+            ColourType::Rgba => row
+                .chunks(4)
+                .for_each(|pixel| row_data.extend([pixel[2], pixel[1], pixel[0]])),
+        }
+        row_data.resize(row_size as usize, 0);
+        writer.write_all(&row_data)?;
+    }
+
+    Ok(())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write() {
+        let image = Image::new(1, 2, ColourType::Rgba, vec![1, 2, 3, 255, 4, 5, 6, 255]);
+
+        let mut output = Vec::new();
+        write(&image, &mut output).unwrap();
+
+        assert_eq!(output.len(), 54 + 8);
+        assert_eq!(output[0..2], *b"BM");
+        assert_eq!(output[2..6], 62u32.to_le_bytes());
+
+        // Rows are bottom up and padded to 4 bytes.
+        assert_eq!(output[54..], [6, 5, 4, 0, 3, 2, 1, 0]);
+    }
+}
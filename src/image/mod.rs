@@ -0,0 +1,295 @@
+//! Implementation of an image type that can be built from a render target's colour or depth buffer and written to
+//! disk in a number of file formats.
+//!
+
+mod bmp;
+mod png;
+mod ppm;
+
+use crate::framebuffer::RenderTarget;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Error handling
+///
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    UnknownFormat,
+    #[allow(dead_code)]
+    Malformed,
+    Io(std::io::Error),
+}
+
+/// File formats an image can be written as.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ImageFormat {
+    Ppm,
+    Bmp,
+    Png,
+}
+
+/// The layout of each pixel within an image.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ColourType {
+    Grey,
+    Rgba,
+}
+
+/// An image stored as rows of pixels, starting with the top row.
+///
+pub struct Image {
+    width: u32,
+    height: u32,
+    colour_type: ColourType,
+    data: Vec<u8>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl ImageFormat {
+    /// Return the format matching a path's file extension.
+    ///
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<ImageFormat> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "ppm" | "pgm" => Some(ImageFormat::Ppm),
+            "bmp" => Some(ImageFormat::Bmp),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+impl Image {
+    /// Return a new image from raw pixel data.
+    ///
+    /// # Panics
+    /// The length of data doesn't match the width, height and colour type.
+    ///
+    pub fn new(width: u32, height: u32, colour_type: ColourType, data: Vec<u8>) -> Image {
+        assert_eq!(
+            data.len(),
+            (width * height) as usize * colour_type.channels(),
+            "Error: image data doesn't match its dimensions"
+        );
+
+        Image {
+            width,
+            height,
+            colour_type,
+            data,
+        }
+    }
+
+    /// Return a copy of a render target's colour buffer.
+    ///
+    pub fn from_colour<T: RenderTarget>(target: &T) -> Image {
+        let (width, height) = (target.width(), target.height());
+
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in (0..height).rev() {
+            for x in 0..width {
+                data.extend_from_slice(&target.get_pixel(x, y));
+            }
+        }
+
+        Image::new(width, height, ColourType::Rgba, data)
+    }
+
+    /// Return a greyscale image of a render target's depth buffer.
+    /// Closer pixels are brighter. Pixels that were never drawn to are black.
+    ///
+    pub fn from_depth<T: RenderTarget>(target: &T) -> Image {
+        let (width, height) = (target.width(), target.height());
+
+        let mut depths = Vec::with_capacity((width * height) as usize);
+        for y in (0..height).rev() {
+            for x in 0..width {
+                depths.push(target.get_depth(x, y));
+            }
+        }
+
+        // Scale the drawn depths to fill the range 1 to 255.
+        let (min, max) = depths
+            .iter()
+            .filter(|&&depth| depth > 0.0)
+            .fold((f64::MAX, f64::MIN), |(min, max), &depth| {
+                (min.min(depth), max.max(depth))
+            });
+        let range = (max - min).max(f64::EPSILON);
+
+        let data = depths
+            .iter()
+            .map(|&depth| {
+                if depth > 0.0 {
+                    1 + (((depth - min) / range) * 254.0) as u8
+                } else {
+                    0
+                }
+            })
+            .collect();
+
+        Image::new(width, height, ColourType::Grey, data)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl ColourType {
+    /// Return the number of bytes used by each pixel.
+    ///
+    pub fn channels(&self) -> usize {
+        match self {
+            ColourType::Grey => 1,
+            ColourType::Rgba => 4,
+        }
+    }
+}
+
+impl Image {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn colour_type(&self) -> ColourType {
+        self.colour_type
+    }
+
+    /// Return the pixel data as rows of pixels, starting with the top row.
+    ///
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Return an iterator over the rows of the image, starting with the top row.
+    ///
+    pub fn rows(&self) -> std::slice::Chunks<'_, u8> {
+        self.data
+            .chunks(self.width as usize * self.colour_type.channels())
+    }
+
+    /// Save the image to a file. The format is chosen from the file's extension.
+    ///
+    /// # Errors
+    /// UnknownFormat: The file extension doesn't match any supported format.
+    /// Io: The file couldn't be written.
+    ///
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let format = ImageFormat::from_path(&path).ok_or(Error::UnknownFormat)?;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer, format)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Load an image from a file. Only PPM and PGM files can be read.
+    ///
+    /// # Errors
+    /// UnknownFormat: The file isn't a PPM or PGM.
+    /// Malformed: The file's contents couldn't be decoded.
+    /// Io: The file couldn't be read.
+    ///
+    #[allow(dead_code)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Image> {
+        if ImageFormat::from_path(&path) != Some(ImageFormat::Ppm) {
+            return Err(Error::UnknownFormat);
+        }
+
+        ppm::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Write the image in the given format.
+    ///
+    pub fn write<W: Write>(&self, writer: &mut W, format: ImageFormat) -> std::io::Result<()> {
+        match format {
+            ImageFormat::Ppm => ppm::write(self, writer),
+            ImageFormat::Bmp => bmp::write(self, writer),
+            ImageFormat::Png => png::write(self, writer),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownFormat => write!(f, "unknown image format"),
+            Error::Malformed => write!(f, "malformed image data"),
+            Error::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::FrameBuffer;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(ImageFormat::from_path("frame.PNG"), Some(ImageFormat::Png));
+        assert_eq!(
+            ImageFormat::from_path("a/b/frame.bmp"),
+            Some(ImageFormat::Bmp)
+        );
+        assert_eq!(ImageFormat::from_path("frame.ppm"), Some(ImageFormat::Ppm));
+        assert_eq!(ImageFormat::from_path("frame.jpg"), None);
+        assert_eq!(ImageFormat::from_path("frame"), None);
+    }
+
+    #[test]
+    fn test_from_colour() {
+        let mut buffer = FrameBuffer::new(2, 2);
+        buffer.draw_pixel(0, 1, [1, 2, 3, 4]);
+        buffer.draw_pixel(1, 0, [5, 6, 7, 8]);
+
+        let image = Image::from_colour(&buffer);
+        assert_eq!(image.data()[0..4], [1, 2, 3, 4]);
+        assert_eq!(image.data()[12..16], [5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_from_depth() {
+        let mut buffer = FrameBuffer::new(3, 1);
+        buffer.set_depth(0, 0, 10.0);
+        buffer.set_depth(1, 0, 20.0);
+
+        let image = Image::from_depth(&buffer);
+        assert_eq!(image.colour_type(), ColourType::Grey);
+        assert_eq!(image.data(), [1, 255, 0]);
+    }
+}
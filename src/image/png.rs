@@ -0,0 +1,151 @@
+//! Implementation of a writer for PNG files.
+//! Image data is wrapped in a zlib stream made of uncompressed deflate blocks, so no compression library is needed.
+//!
+
+use super::{ColourType, Image};
+use std::io::{Result, Write};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// The largest amount of data a single stored deflate block can hold.
+const MAX_STORED_BLOCK: usize = 0xFFFF;
+
+/// Write an image as a PNG.
+///
+pub fn write<W: Write>(image: &Image, writer: &mut W) -> Result<()> {
+    writer.write_all(&SIGNATURE)?;
+
+    // IHDR
+    let colour_type = match image.colour_type() {
+        ColourType::Grey => 0,
+        ColourType::Rgba => 6,
+    };
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&image.width().to_be_bytes());
+    header.extend_from_slice(&image.height().to_be_bytes());
+    header.extend_from_slice(&[8, colour_type, 0, 0, 0]);
+    write_chunk(writer, b"IHDR", &header)?;
+
+    // IDAT. Each row is prefixed with filter type 0 (none).
+    let mut filtered = Vec::with_capacity(image.data().len() + image.height() as usize);
+    for row in image.rows() {
+        filtered.push(0);
+        filtered.extend_from_slice(row);
+    }
+    write_chunk(writer, b"IDAT", &zlib_store(&filtered))?;
+
+    // IEND
+    write_chunk(writer, b"IEND", &[])
+}
+
+/// Write a PNG chunk consisting of its length, type, data and CRC.
+///
+fn write_chunk<W: Write>(writer: &mut W, chunk_type: &[u8; 4], data: &[u8]) -> Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(chunk_type)?;
+    writer.write_all(data)?;
+
+    let crc = crc32_update(crc32_update(0xFFFF_FFFF, chunk_type), data) ^ 0xFFFF_FFFF;
+    writer.write_all(&crc.to_be_bytes())
+}
+
+/// Return a zlib stream containing the data in uncompressed deflate blocks.
+///
+fn zlib_store(data: &[u8]) -> Vec<u8> {
+    let blocks = (data.len() / MAX_STORED_BLOCK) + 1;
+    let mut stream = Vec::with_capacity(data.len() + (blocks * 5) + 6);
+
+    // CMF: deflate with a 32K window. FLG: no dictionary, fastest compression, with a valid check value.
+    stream.extend_from_slice(&[0x78, 0x01]);
+
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+
+        stream.push(last as u8);
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(chunk);
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+/// Update a running CRC-32 (as used by PNG) with more data.
+///
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// Return the Adler-32 checksum of the data.
+///
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % MOD_ADLER;
+        (a, (b + a) % MOD_ADLER)
+    });
+    (b << 16) | a
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(
+            crc32_update(0xFFFF_FFFF, b"123456789") ^ 0xFFFF_FFFF,
+            0xCBF4_3926
+        );
+    }
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn test_zlib_store_blocks() {
+        let data = vec![7; MAX_STORED_BLOCK + 10];
+        let stream = zlib_store(&data);
+
+        // Header, 2 block headers, data and checksum.
+        assert_eq!(stream.len(), 2 + 5 + 5 + data.len() + 4);
+        assert_eq!(stream[2], 0);
+        assert_eq!(stream[2 + 5 + MAX_STORED_BLOCK], 1);
+    }
+
+    #[test]
+    fn test_write() {
+        let image = Image::new(1, 1, ColourType::Rgba, vec![1, 2, 3, 4]);
+
+        let mut output = Vec::new();
+        write(&image, &mut output).unwrap();
+
+        assert_eq!(output[0..8], SIGNATURE);
+        assert_eq!(output[12..16], *b"IHDR");
+        assert_eq!(output[output.len() - 8..output.len() - 4], *b"IEND");
+        assert_eq!(output[output.len() - 4..], [0xAE, 0x42, 0x60, 0x82]);
+    }
+}
@@ -0,0 +1,149 @@
+//! Implementation of a writer for the binary netpbm formats. RGBA images are written as PPM and greyscale images
+//! as PGM.
+//!
+
+use super::{ColourType, Error, Image};
+use std::io::{BufRead, Result, Write};
+
+/// Write an image as a binary PPM or PGM. The alpha channel is discarded.
+///
+pub fn write<W: Write>(image: &Image, writer: &mut W) -> Result<()> {
+    let magic = match image.colour_type() {
+        ColourType::Grey => "P5",
+        ColourType::Rgba => "P6",
+    };
+    write!(
+        writer,
+        "{}\n{} {}\n255\n",
+        magic,
+        image.width(),
+        image.height()
+    )?;
+
+    match image.colour_type() {
+        ColourType::Grey => writer.write_all(image.data()),
+        ColourType::Rgba => {
+            let rgb: Vec<u8> = image
+                .data()
+                .chunks(4)
+                .flat_map(|pixel| pixel[0..3].iter().copied())
+                .collect();
+            writer.write_all(&rgb)
+        }
+    }
+}
+
+/// Read a binary PPM or PGM with a maximum value of 255. PPM images are given an opaque alpha channel.
+///
+/// # Errors
+/// Malformed: The data isn't a binary PPM or PGM, or uses more than 8 bits per sample.
+/// Io: The data couldn't be read.
+///
+#[allow(dead_code)]
+pub fn read<R: BufRead>(reader: &mut R) -> super::Result<Image> {
+    let colour_type = match read_token(reader)?.as_str() {
+        "P5" => ColourType::Grey,
+        "P6" => ColourType::Rgba,
+        _ => return Err(Error::Malformed),
+    };
+    let width = read_number(reader)?;
+    let height = read_number(reader)?;
+    if read_number(reader)? != 255 {
+        return Err(Error::Malformed);
+    }
+
+    let samples = match colour_type {
+        ColourType::Grey => 1,
+        ColourType::Rgba => 3,
+    };
+    let mut raw = vec![0; (width * height) as usize * samples];
+    reader.read_exact(&mut raw)?;
+
+    let data = match colour_type {
+        ColourType::Grey => raw,
+        ColourType::Rgba => raw
+            .chunks(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+    };
+
+    Ok(Image::new(width, height, colour_type, data))
+}
+
+/// Read a header token, skipping whitespace and comments.
+/// A single whitespace character following the token is consumed.
+///
+fn read_token<R: BufRead>(reader: &mut R) -> super::Result<String> {
+    let mut token = String::new();
+    let mut comment = false;
+    let mut byte = [0];
+
+    loop {
+        reader.read_exact(&mut byte)?;
+        match byte[0] {
+            b'\n' if comment => comment = false,
+            _ if comment => {}
+            b'#' if token.is_empty() => comment = true,
+            byte if byte.is_ascii_whitespace() => {
+                if !token.is_empty() {
+                    return Ok(token);
+                }
+            }
+            byte => token.push(byte as char),
+        }
+    }
+}
+
+/// Read a header token as a number.
+///
+fn read_number<R: BufRead>(reader: &mut R) -> super::Result<u32> {
+    read_token(reader)?.parse().map_err(|_| Error::Malformed)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_rgba() {
+        let image = Image::new(2, 1, ColourType::Rgba, vec![1, 2, 3, 255, 4, 5, 6, 255]);
+
+        let mut output = Vec::new();
+        write(&image, &mut output).unwrap();
+        assert_eq!(output, b"P6\n2 1\n255\n\x01\x02\x03\x04\x05\x06");
+    }
+
+    #[test]
+    fn test_write_grey() {
+        let image = Image::new(1, 2, ColourType::Grey, vec![7, 8]);
+
+        let mut output = Vec::new();
+        write(&image, &mut output).unwrap();
+        assert_eq!(output, b"P5\n1 2\n255\n\x07\x08");
+    }
+
+    #[test]
+    fn test_read() {
+        let data = b"P6\n# A comment\n2 1\n255\n\x01\x02\x03\x04\x05\x06";
+        let image = read(&mut &data[..]).ok().unwrap();
+
+        assert_eq!((image.width(), image.height()), (2, 1));
+        assert_eq!(image.data(), [1, 2, 3, 255, 4, 5, 6, 255]);
+    }
+
+    #[test]
+    fn test_read_malformed() {
+        assert!(matches!(
+            read(&mut &b"P3\n1 1\n255\n"[..]),
+            Err(Error::Malformed)
+        ));
+        assert!(matches!(
+            read(&mut &b"P5\n1 1\n65535\n"[..]),
+            Err(Error::Malformed)
+        ));
+    }
+}
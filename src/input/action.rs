@@ -0,0 +1,182 @@
+//! Implementation of the named actions that keys and mouse buttons are bound to.
+//!
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Something the user can do by pressing or holding a button. Toggles happen once when a button is pressed, while
+/// camera movements last as long as a button is held.
+///
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Action {
+    TogglePause,
+    AdvanceFrame,
+    CycleRasterMethod,
+    CycleShading,
+    CycleCulling,
+    FlipFrontFace,
+    CycleDrawType,
+    CycleAntiAliasing,
+    ToggleTiledRendering,
+    CycleBlendMode,
+    ToggleDepthPrepass,
+    ToggleOcclusionCulling,
+    ToggleFrontToBack,
+    ToggleShadows,
+    CycleFog,
+    CycleFrustum,
+    ToggleInfiniteFarPlane,
+    CycleEffect,
+    CycleTextureFilter,
+    CycleTextureWrap,
+    CycleCameraController,
+    SaveFrame,
+    SaveDepth,
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    MoveFast,
+    /// Turns the orbit camera and slides the pan camera while the mouse is dragged.
+    Drag,
+    /// Moves the orbit camera's target while the mouse is dragged.
+    Pan,
+    /// Turns the fly camera with the mouse, and lets it move.
+    Look,
+    NudgeLeft,
+    NudgeRight,
+    NudgeUp,
+    NudgeDown,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Action {
+    /// Return the action with a name, as used in binding files.
+    ///
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.name().eq_ignore_ascii_case(name))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Action {
+    /// Every action, in the order they're listed in binding files.
+    ///
+    pub const ALL: [Action; 37] = [
+        Action::TogglePause,
+        Action::AdvanceFrame,
+        Action::CycleRasterMethod,
+        Action::CycleShading,
+        Action::CycleCulling,
+        Action::FlipFrontFace,
+        Action::CycleDrawType,
+        Action::CycleAntiAliasing,
+        Action::ToggleTiledRendering,
+        Action::CycleBlendMode,
+        Action::ToggleDepthPrepass,
+        Action::ToggleOcclusionCulling,
+        Action::ToggleFrontToBack,
+        Action::ToggleShadows,
+        Action::CycleFog,
+        Action::CycleFrustum,
+        Action::ToggleInfiniteFarPlane,
+        Action::CycleEffect,
+        Action::CycleTextureFilter,
+        Action::CycleTextureWrap,
+        Action::CycleCameraController,
+        Action::SaveFrame,
+        Action::SaveDepth,
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveFast,
+        Action::Drag,
+        Action::Pan,
+        Action::Look,
+        Action::NudgeLeft,
+        Action::NudgeRight,
+        Action::NudgeUp,
+        Action::NudgeDown,
+    ];
+
+    /// Return the action's name, as used in binding files.
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::TogglePause => "toggle_pause",
+            Action::AdvanceFrame => "advance_frame",
+            Action::CycleRasterMethod => "cycle_raster_method",
+            Action::CycleShading => "cycle_shading",
+            Action::CycleCulling => "cycle_culling",
+            Action::FlipFrontFace => "flip_front_face",
+            Action::CycleDrawType => "cycle_draw_type",
+            Action::CycleAntiAliasing => "cycle_anti_aliasing",
+            Action::ToggleTiledRendering => "toggle_tiled_rendering",
+            Action::CycleBlendMode => "cycle_blend_mode",
+            Action::ToggleDepthPrepass => "toggle_depth_prepass",
+            Action::ToggleOcclusionCulling => "toggle_occlusion_culling",
+            Action::ToggleFrontToBack => "toggle_front_to_back",
+            Action::ToggleShadows => "toggle_shadows",
+            Action::CycleFog => "cycle_fog",
+            Action::CycleFrustum => "cycle_frustum",
+            Action::ToggleInfiniteFarPlane => "toggle_infinite_far_plane",
+            Action::CycleEffect => "cycle_effect",
+            Action::CycleTextureFilter => "cycle_texture_filter",
+            Action::CycleTextureWrap => "cycle_texture_wrap",
+            Action::CycleCameraController => "cycle_camera_controller",
+            Action::SaveFrame => "save_frame",
+            Action::SaveDepth => "save_depth",
+            Action::MoveForward => "move_forward",
+            Action::MoveBack => "move_back",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::MoveFast => "move_fast",
+            Action::Drag => "drag",
+            Action::Pan => "pan",
+            Action::Look => "look",
+            Action::NudgeLeft => "nudge_left",
+            Action::NudgeRight => "nudge_right",
+            Action::NudgeUp => "nudge_up",
+            Action::NudgeDown => "nudge_down",
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_names() {
+        // Every action is listed once, with its own name.
+        let names: HashSet<_> = Action::ALL.iter().map(Action::name).collect();
+        assert_eq!(names.len(), Action::ALL.len());
+        for action in Action::ALL {
+            assert_eq!(Action::from_name(action.name()), Some(action));
+        }
+
+        assert_eq!(Action::from_name("Toggle_Pause"), Some(Action::TogglePause));
+        assert_eq!(Action::from_name("pause"), None);
+    }
+}
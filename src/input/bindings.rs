@@ -0,0 +1,235 @@
+//! Implementation of the bindings from keys and mouse buttons to actions, and the files they're read from.
+//!
+//! A binding file holds one action per line, followed by an equals sign and a comma separated list of the buttons
+//! bound to it. Blank lines and anything after a # are ignored. For example:
+//!
+//! ```text
+//! # Move with the arrow keys as well as WASD.
+//! move_forward = W, Up
+//! move_back = S, Down
+//! ```
+//!
+
+use super::{Action, Button, Error, Result};
+use std::{collections::HashMap, fs, path::Path};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// The bindings used when there's no binding file, or for any actions it doesn't list.
+///
+const DEFAULT_BINDINGS: &str = include_str!("default_bindings.cfg");
+
+/// The buttons bound to each action. An action can have any number of buttons, and a button can trigger any number
+/// of actions.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bindings {
+    buttons: HashMap<Action, Vec<Button>>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+impl Bindings {
+    /// Return a new set of bindings with nothing bound.
+    ///
+    pub fn new() -> Bindings {
+        Bindings {
+            buttons: HashMap::new(),
+        }
+    }
+
+    /// Return the default bindings, with the actions listed in a binding file rebound.
+    ///
+    /// # Errors
+    /// UnknownAction, UnknownButton or Malformed: The file isn't a valid binding file.
+    /// Io: The file couldn't be read.
+    ///
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Bindings> {
+        let mut bindings = Bindings::default();
+        bindings.apply(&fs::read_to_string(path)?)?;
+        Ok(bindings)
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let mut bindings = Bindings::new();
+        bindings
+            .apply(DEFAULT_BINDINGS)
+            .expect("the default bindings are valid");
+        bindings
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+impl Bindings {
+    /// Rebind the actions listed in the text of a binding file, leaving the others as they are. Nothing is rebound
+    /// if the text isn't valid.
+    ///
+    /// # Errors
+    /// UnknownAction: A line starts with something that isn't an action's name.
+    /// UnknownButton: A line lists something that isn't a key or mouse button's name.
+    /// Malformed: A line doesn't have an equals sign, or has an empty entry in its list of buttons.
+    ///
+    pub fn apply(&mut self, text: &str) -> Result<()> {
+        let mut parsed = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, list) = line
+                .split_once('=')
+                .ok_or(Error::Malformed { line: line_number })?;
+            let name = name.trim();
+            let action = Action::from_name(name).ok_or_else(|| Error::UnknownAction {
+                line: line_number,
+                name: name.to_string(),
+            })?;
+
+            let mut buttons = Vec::new();
+            let list = list.trim();
+            if !list.is_empty() {
+                for name in list.split(',').map(str::trim) {
+                    if name.is_empty() {
+                        return Err(Error::Malformed { line: line_number });
+                    }
+                    let button = Button::from_name(name).ok_or_else(|| Error::UnknownButton {
+                        line: line_number,
+                        name: name.to_string(),
+                    })?;
+                    if !buttons.contains(&button) {
+                        buttons.push(button);
+                    }
+                }
+            }
+            parsed.push((action, buttons));
+        }
+
+        self.buttons.extend(parsed);
+        Ok(())
+    }
+
+    /// Bind a button to an action, as well as any buttons already bound to it.
+    ///
+    pub fn bind(&mut self, action: Action, button: Button) {
+        let buttons = self.buttons.entry(action).or_default();
+        if !buttons.contains(&button) {
+            buttons.push(button);
+        }
+    }
+
+    /// Remove every button bound to an action.
+    ///
+    pub fn unbind(&mut self, action: Action) {
+        self.buttons.remove(&action);
+    }
+
+    /// Return the buttons bound to an action.
+    ///
+    pub fn buttons(&self, action: Action) -> &[Button] {
+        self.buttons.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Return the actions a button is bound to.
+    ///
+    pub fn actions(&self, button: Button) -> impl Iterator<Item = Action> + '_ {
+        Action::ALL
+            .into_iter()
+            .filter(move |action| self.buttons(*action).contains(&button))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::{MouseButton, VirtualKeyCode};
+
+    #[test]
+    fn test_default_bindings() {
+        // Every action has a button by default.
+        let bindings = Bindings::default();
+        for action in Action::ALL {
+            assert!(!bindings.buttons(action).is_empty(), "{:?}", action);
+        }
+
+        assert_eq!(
+            bindings.buttons(Action::MoveFast),
+            [
+                Button::Key(VirtualKeyCode::LShift),
+                Button::Key(VirtualKeyCode::RShift)
+            ]
+        );
+        let actions: Vec<_> = bindings.actions(Button::Key(VirtualKeyCode::W)).collect();
+        assert_eq!(actions, [Action::CycleTextureWrap, Action::MoveForward]);
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut bindings = Bindings::default();
+        let text = "
+            # Pause with escape, look with the left mouse button and stop dragging.
+            toggle_pause = Escape # Not space.
+            look=MouseLeft,  mouse4
+            drag =
+        ";
+        bindings.apply(text).unwrap();
+
+        assert_eq!(
+            bindings.buttons(Action::TogglePause),
+            [Button::Key(VirtualKeyCode::Escape)]
+        );
+        assert_eq!(
+            bindings.buttons(Action::Look),
+            [
+                Button::Mouse(MouseButton::Left),
+                Button::Mouse(MouseButton::Other(4))
+            ]
+        );
+        assert!(bindings.buttons(Action::Drag).is_empty());
+        assert_eq!(
+            bindings.buttons(Action::AdvanceFrame),
+            [Button::Key(VirtualKeyCode::N)]
+        );
+    }
+
+    #[test]
+    fn test_apply_errors() {
+        let cases = [
+            ("toggle_pause Space", "malformed binding on line 1"),
+            ("\nlook = MouseLeft,", "malformed binding on line 2"),
+            ("jump = Space", "unknown action 'jump' on line 1"),
+            (
+                "toggle_pause = Space\nlook = Wheel",
+                "unknown button 'Wheel' on line 2",
+            ),
+        ];
+        for (text, message) in cases {
+            // Nothing is rebound when any line is wrong.
+            let mut bindings = Bindings::new();
+            let error = bindings.apply(text).unwrap_err();
+            assert_eq!(error.to_string(), message);
+            assert_eq!(bindings, Bindings::new());
+        }
+
+        assert!(matches!(
+            Bindings::load("tests/no_such_bindings.cfg"),
+            Err(Error::Io(_))
+        ));
+    }
+}
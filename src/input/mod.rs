@@ -0,0 +1,430 @@
+//! Implementation of an input layer, which turns winit's key and mouse events into named actions.
+//!
+//! Window and device events are fed into an input state as they arrive. It keeps track of which keys and mouse
+//! buttons are held, which were pressed or released since the last frame, and how far the mouse moved and scrolled.
+//! Buttons are bound to actions, so the rest of the program asks whether an action was pressed or is held rather than
+//! checking for particular keys, and the bindings can be changed with a binding file.
+//!
+//! Synthetic input is fed in through the same methods the events use, so tests can press and release buttons without
+//! a window.
+//!
+
+mod action;
+mod bindings;
+mod names;
+
+pub use action::Action;
+pub use bindings::Bindings;
+
+use std::collections::HashSet;
+use winit::event::{
+    DeviceEvent, ElementState, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode,
+    WindowEvent,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Error handling
+///
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    UnknownAction { line: usize, name: String },
+    UnknownButton { line: usize, name: String },
+    Malformed { line: usize },
+    Io(std::io::Error),
+}
+
+/// The number of pixels scrolled by touchpads that counts as scrolling a single line.
+const PIXELS_PER_LINE: f64 = 20.0;
+
+/// A key or mouse button that can be bound to an action.
+///
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Button {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+}
+
+/// The buttons held down, pressed and released, and the mouse motion and scrolling since the last frame.
+///
+/// A button pressed and released within a single frame counts as both pressed and released, so quick taps aren't
+/// missed.
+///
+#[derive(Default)]
+pub struct InputState {
+    bindings: Bindings,
+    held: HashSet<Button>,
+    pressed: HashSet<Button>,
+    released: HashSet<Button>,
+    /// The raw mouse motion, with +x to the right and +y down.
+    mouse_delta: [f64; 2],
+    /// The lines scrolled, with positive values scrolling away from the user.
+    scroll: f64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl InputState {
+    /// Return a new input state with nothing held, which triggers actions using the given bindings.
+    ///
+    pub fn new(bindings: Bindings) -> InputState {
+        InputState {
+            bindings,
+            ..Default::default()
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+impl InputState {
+    /// Update the input from a window event, such as a key press or scrolling.
+    ///
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        match *event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => self.set_button(Button::Key(key), state),
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.set_button(Button::Mouse(button), state)
+            }
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(_, lines) => self.add_scroll(lines as f64),
+                MouseScrollDelta::PixelDelta(position) => {
+                    self.add_scroll(position.y / PIXELS_PER_LINE)
+                }
+            },
+            // Buttons released while another window has focus are never seen, so release everything held.
+            WindowEvent::Focused(false) => self.release_all(),
+            _ => {}
+        }
+    }
+
+    /// Update the input from a device event. Raw mouse motion is used to look around, as it keeps going when the
+    /// cursor reaches the edge of the screen.
+    ///
+    pub fn handle_device_event(&mut self, event: &DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta: (x, y) } = *event {
+            self.add_mouse_motion(x, y);
+        }
+    }
+
+    /// Record a button being pressed. Repeated presses while it's held, such as from key repeat, are ignored.
+    ///
+    pub fn press(&mut self, button: Button) {
+        if self.held.insert(button) {
+            self.pressed.insert(button);
+        }
+    }
+
+    /// Record a button being released.
+    ///
+    pub fn release(&mut self, button: Button) {
+        if self.held.remove(&button) {
+            self.released.insert(button);
+        }
+    }
+
+    /// Release every button held.
+    ///
+    pub fn release_all(&mut self) {
+        self.released.extend(self.held.drain());
+    }
+
+    /// Add to the mouse motion since the last frame, with +y moving down.
+    ///
+    pub fn add_mouse_motion(&mut self, x: f64, y: f64) {
+        self.mouse_delta[0] += x;
+        self.mouse_delta[1] += y;
+    }
+
+    /// Add to the lines scrolled since the last frame, with positive values scrolling away from the user.
+    ///
+    pub fn add_scroll(&mut self, lines: f64) {
+        self.scroll += lines;
+    }
+
+    /// Return true if a button is held down.
+    ///
+    pub fn is_held(&self, button: Button) -> bool {
+        self.held.contains(&button)
+    }
+
+    /// Return true if a button was pressed since the last frame.
+    ///
+    pub fn was_pressed(&self, button: Button) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    /// Return true if a button was released since the last frame.
+    ///
+    pub fn was_released(&self, button: Button) -> bool {
+        self.released.contains(&button)
+    }
+
+    /// Return true if any button bound to an action is held down.
+    ///
+    pub fn is_action_held(&self, action: Action) -> bool {
+        self.any_bound(action, &self.held)
+    }
+
+    /// Return true if any button bound to an action was pressed since the last frame.
+    ///
+    pub fn was_action_pressed(&self, action: Action) -> bool {
+        self.any_bound(action, &self.pressed)
+    }
+
+    /// Return true if any button bound to an action was released since the last frame.
+    ///
+    pub fn was_action_released(&self, action: Action) -> bool {
+        self.any_bound(action, &self.released)
+    }
+
+    /// Return the actions with a button pressed since the last frame, in the order they're listed in binding files.
+    ///
+    pub fn pressed_actions(&self) -> impl Iterator<Item = Action> + '_ {
+        Action::ALL
+            .into_iter()
+            .filter(|action| self.was_action_pressed(*action))
+    }
+
+    /// Return the actions with a button pressed since the last frame, leaving out the presses of any buttons bound to
+    /// the used actions. This stops the buttons a camera controller is moving with from triggering the other actions
+    /// they're bound to, while the rest of the buttons still work.
+    ///
+    pub fn pressed_actions_except<'a>(
+        &'a self,
+        used: &'a [Action],
+    ) -> impl Iterator<Item = Action> + 'a {
+        let is_used = move |button: &Button| {
+            used.iter()
+                .any(|action| self.bindings.buttons(*action).contains(button))
+        };
+        Action::ALL.into_iter().filter(move |action| {
+            self.bindings
+                .buttons(*action)
+                .iter()
+                .any(|button| self.pressed.contains(button) && !is_used(button))
+        })
+    }
+
+    /// Return 1 if the positive action is held, -1 if the negative action is held and 0 if both or neither are.
+    ///
+    pub fn axis(&self, positive: Action, negative: Action) -> f64 {
+        (self.is_action_held(positive) as i32 - self.is_action_held(negative) as i32) as f64
+    }
+
+    /// Return the mouse motion since the last frame, with +y moving down.
+    ///
+    pub fn mouse_delta(&self) -> [f64; 2] {
+        self.mouse_delta
+    }
+
+    /// Return the lines scrolled since the last frame, with positive values scrolling away from the user.
+    ///
+    pub fn scroll(&self) -> f64 {
+        self.scroll
+    }
+
+    /// Return the bindings used to trigger actions.
+    ///
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    /// Change the bindings used to trigger actions. Buttons stay held.
+    ///
+    pub fn set_bindings(&mut self, bindings: Bindings) {
+        self.bindings = bindings;
+    }
+
+    /// Forget the buttons pressed and released, the mouse motion and the scrolling once a frame has used them.
+    /// Buttons stay held.
+    ///
+    pub fn end_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.mouse_delta = [0.0; 2];
+        self.scroll = 0.0;
+    }
+
+    /// Record a button changing state.
+    ///
+    fn set_button(&mut self, button: Button, state: ElementState) {
+        match state {
+            ElementState::Pressed => self.press(button),
+            ElementState::Released => self.release(button),
+        }
+    }
+
+    /// Return true if any button bound to an action is in a set of buttons.
+    ///
+    fn any_bound(&self, action: Action, buttons: &HashSet<Button>) -> bool {
+        self.bindings
+            .buttons(action)
+            .iter()
+            .any(|button| buttons.contains(button))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownAction { line, name } => {
+                write!(f, "unknown action '{}' on line {}", name, line)
+            }
+            Error::UnknownButton { line, name } => {
+                write!(f, "unknown button '{}' on line {}", name, line)
+            }
+            Error::Malformed { line } => write!(f, "malformed binding on line {}", line),
+            Error::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPACE: Button = Button::Key(VirtualKeyCode::Space);
+
+    #[allow(deprecated)]
+    fn key_event(key: VirtualKeyCode, state: ElementState) -> WindowEvent<'static> {
+        WindowEvent::KeyboardInput {
+            device_id: unsafe { winit::event::DeviceId::dummy() },
+            input: KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(key),
+                modifiers: Default::default(),
+            },
+            is_synthetic: false,
+        }
+    }
+
+    #[test]
+    fn test_press_and_release() {
+        let mut input = InputState::default();
+
+        // Key repeat doesn't press the key again.
+        input.handle_window_event(&key_event(VirtualKeyCode::Space, ElementState::Pressed));
+        input.handle_window_event(&key_event(VirtualKeyCode::Space, ElementState::Pressed));
+        assert!(input.was_pressed(SPACE) && input.is_held(SPACE) && !input.was_released(SPACE));
+        assert!(input.was_action_pressed(Action::TogglePause));
+        assert_eq!(
+            input.pressed_actions().collect::<Vec<_>>(),
+            [Action::TogglePause]
+        );
+
+        // It stays held over following frames, but is only pressed in the first.
+        input.end_frame();
+        assert!(!input.was_pressed(SPACE) && input.is_held(SPACE));
+        input.handle_window_event(&key_event(VirtualKeyCode::Space, ElementState::Pressed));
+        assert!(!input.was_action_pressed(Action::TogglePause));
+
+        input.handle_window_event(&key_event(VirtualKeyCode::Space, ElementState::Released));
+        assert!(input.was_released(SPACE) && !input.is_held(SPACE));
+        assert!(input.was_action_released(Action::TogglePause));
+
+        // A quick tap within a single frame is both pressed and released.
+        input.end_frame();
+        input.press(SPACE);
+        input.release(SPACE);
+        assert!(input.was_pressed(SPACE) && input.was_released(SPACE) && !input.is_held(SPACE));
+    }
+
+    #[test]
+    fn test_actions() {
+        let mut bindings = Bindings::new();
+        bindings.bind(Action::MoveFast, Button::Key(VirtualKeyCode::LShift));
+        bindings.bind(Action::MoveFast, Button::Key(VirtualKeyCode::RShift));
+        bindings.bind(Action::MoveForward, Button::Mouse(MouseButton::Left));
+        let mut input = InputState::new(bindings);
+
+        // Any of an action's buttons trigger it.
+        input.press(Button::Key(VirtualKeyCode::RShift));
+        assert!(input.is_action_held(Action::MoveFast));
+        input.press(Button::Mouse(MouseButton::Left));
+        assert_eq!(input.axis(Action::MoveForward, Action::MoveBack), 1.0);
+
+        // Losing focus releases everything.
+        input.end_frame();
+        input.handle_window_event(&WindowEvent::Focused(false));
+        assert!(!input.is_action_held(Action::MoveFast));
+        assert!(input.was_action_released(Action::MoveForward));
+        assert_eq!(input.axis(Action::MoveForward, Action::MoveBack), 0.0);
+
+        // Buttons bound to used actions don't trigger the other actions they're bound to, but other buttons do.
+        input.end_frame();
+        input.set_bindings(Bindings::default());
+        input.press(Button::Key(VirtualKeyCode::W));
+        input.press(Button::Key(VirtualKeyCode::P));
+        let used = [Action::MoveForward];
+        assert_eq!(
+            input.pressed_actions_except(&used).collect::<Vec<_>>(),
+            [Action::SaveFrame]
+        );
+        assert_eq!(
+            input.pressed_actions_except(&[]).collect::<Vec<_>>(),
+            [
+                Action::CycleTextureWrap,
+                Action::SaveFrame,
+                Action::MoveForward
+            ]
+        );
+
+        // Rebinding changes the actions without changing the buttons held.
+        input.press(SPACE);
+        input.set_bindings(Bindings::default());
+        assert!(input.is_action_held(Action::TogglePause));
+    }
+
+    #[test]
+    fn test_mouse_motion_and_scroll() {
+        let mut input = InputState::default();
+        input.handle_device_event(&DeviceEvent::MouseMotion { delta: (3.0, -4.0) });
+        input.add_mouse_motion(1.0, 1.0);
+        input.add_scroll(1.0);
+        input.handle_window_event(&WindowEvent::MouseWheel {
+            device_id: unsafe { winit::event::DeviceId::dummy() },
+            delta: MouseScrollDelta::PixelDelta((0.0, PIXELS_PER_LINE * 2.0).into()),
+            phase: winit::event::TouchPhase::Moved,
+            modifiers: Default::default(),
+        });
+        assert_eq!(input.mouse_delta(), [4.0, -3.0]);
+        assert_eq!(input.scroll(), 3.0);
+
+        input.end_frame();
+        assert_eq!(input.mouse_delta(), [0.0; 2]);
+        assert_eq!(input.scroll(), 0.0);
+    }
+}
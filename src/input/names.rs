@@ -0,0 +1,198 @@
+//! Implementation of the names keys and mouse buttons are given in binding files.
+//!
+//! Keys are named after winit's virtual key codes, except for the number keys which are named after their digit.
+//! Mouse buttons are named MouseLeft, MouseRight and MouseMiddle, or MouseN for any other button. Names aren't case
+//! sensitive.
+//!
+
+use super::Button;
+use winit::event::{MouseButton, VirtualKeyCode};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// The keys that can be bound, and their names.
+///
+const KEYS: [(&str, VirtualKeyCode); 91] = [
+    ("1", VirtualKeyCode::Key1),
+    ("2", VirtualKeyCode::Key2),
+    ("3", VirtualKeyCode::Key3),
+    ("4", VirtualKeyCode::Key4),
+    ("5", VirtualKeyCode::Key5),
+    ("6", VirtualKeyCode::Key6),
+    ("7", VirtualKeyCode::Key7),
+    ("8", VirtualKeyCode::Key8),
+    ("9", VirtualKeyCode::Key9),
+    ("0", VirtualKeyCode::Key0),
+    ("A", VirtualKeyCode::A),
+    ("B", VirtualKeyCode::B),
+    ("C", VirtualKeyCode::C),
+    ("D", VirtualKeyCode::D),
+    ("E", VirtualKeyCode::E),
+    ("F", VirtualKeyCode::F),
+    ("G", VirtualKeyCode::G),
+    ("H", VirtualKeyCode::H),
+    ("I", VirtualKeyCode::I),
+    ("J", VirtualKeyCode::J),
+    ("K", VirtualKeyCode::K),
+    ("L", VirtualKeyCode::L),
+    ("M", VirtualKeyCode::M),
+    ("N", VirtualKeyCode::N),
+    ("O", VirtualKeyCode::O),
+    ("P", VirtualKeyCode::P),
+    ("Q", VirtualKeyCode::Q),
+    ("R", VirtualKeyCode::R),
+    ("S", VirtualKeyCode::S),
+    ("T", VirtualKeyCode::T),
+    ("U", VirtualKeyCode::U),
+    ("V", VirtualKeyCode::V),
+    ("W", VirtualKeyCode::W),
+    ("X", VirtualKeyCode::X),
+    ("Y", VirtualKeyCode::Y),
+    ("Z", VirtualKeyCode::Z),
+    ("Escape", VirtualKeyCode::Escape),
+    ("F1", VirtualKeyCode::F1),
+    ("F2", VirtualKeyCode::F2),
+    ("F3", VirtualKeyCode::F3),
+    ("F4", VirtualKeyCode::F4),
+    ("F5", VirtualKeyCode::F5),
+    ("F6", VirtualKeyCode::F6),
+    ("F7", VirtualKeyCode::F7),
+    ("F8", VirtualKeyCode::F8),
+    ("F9", VirtualKeyCode::F9),
+    ("F10", VirtualKeyCode::F10),
+    ("F11", VirtualKeyCode::F11),
+    ("F12", VirtualKeyCode::F12),
+    ("Insert", VirtualKeyCode::Insert),
+    ("Home", VirtualKeyCode::Home),
+    ("Delete", VirtualKeyCode::Delete),
+    ("End", VirtualKeyCode::End),
+    ("PageDown", VirtualKeyCode::PageDown),
+    ("PageUp", VirtualKeyCode::PageUp),
+    ("Left", VirtualKeyCode::Left),
+    ("Up", VirtualKeyCode::Up),
+    ("Right", VirtualKeyCode::Right),
+    ("Down", VirtualKeyCode::Down),
+    ("Back", VirtualKeyCode::Back),
+    ("Return", VirtualKeyCode::Return),
+    ("Space", VirtualKeyCode::Space),
+    ("Tab", VirtualKeyCode::Tab),
+    ("Numpad0", VirtualKeyCode::Numpad0),
+    ("Numpad1", VirtualKeyCode::Numpad1),
+    ("Numpad2", VirtualKeyCode::Numpad2),
+    ("Numpad3", VirtualKeyCode::Numpad3),
+    ("Numpad4", VirtualKeyCode::Numpad4),
+    ("Numpad5", VirtualKeyCode::Numpad5),
+    ("Numpad6", VirtualKeyCode::Numpad6),
+    ("Numpad7", VirtualKeyCode::Numpad7),
+    ("Numpad8", VirtualKeyCode::Numpad8),
+    ("Numpad9", VirtualKeyCode::Numpad9),
+    ("LShift", VirtualKeyCode::LShift),
+    ("RShift", VirtualKeyCode::RShift),
+    ("LControl", VirtualKeyCode::LControl),
+    ("RControl", VirtualKeyCode::RControl),
+    ("LAlt", VirtualKeyCode::LAlt),
+    ("RAlt", VirtualKeyCode::RAlt),
+    ("Minus", VirtualKeyCode::Minus),
+    ("Equals", VirtualKeyCode::Equals),
+    ("LBracket", VirtualKeyCode::LBracket),
+    ("RBracket", VirtualKeyCode::RBracket),
+    ("Semicolon", VirtualKeyCode::Semicolon),
+    ("Apostrophe", VirtualKeyCode::Apostrophe),
+    ("Backslash", VirtualKeyCode::Backslash),
+    ("Grave", VirtualKeyCode::Grave),
+    ("Comma", VirtualKeyCode::Comma),
+    ("Period", VirtualKeyCode::Period),
+    ("Slash", VirtualKeyCode::Slash),
+    ("Capital", VirtualKeyCode::Capital),
+];
+
+/// The prefix given to mouse button names.
+///
+const MOUSE_PREFIX: &str = "Mouse";
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Button {
+    /// Return the key or mouse button with a name, as used in binding files.
+    ///
+    pub fn from_name(name: &str) -> Option<Button> {
+        if let Some((_, key)) = KEYS
+            .iter()
+            .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        {
+            return Some(Button::Key(*key));
+        }
+
+        let prefix = name.get(..MOUSE_PREFIX.len())?;
+        if !prefix.eq_ignore_ascii_case(MOUSE_PREFIX) {
+            return None;
+        }
+        let button = &name[MOUSE_PREFIX.len()..];
+        let button = match button.to_ascii_lowercase().as_str() {
+            "left" => MouseButton::Left,
+            "right" => MouseButton::Right,
+            "middle" => MouseButton::Middle,
+            _ => MouseButton::Other(button.parse().ok()?),
+        };
+        Some(Button::Mouse(button))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for Button {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Button::Key(key) => match KEYS.iter().find(|(_, named)| named == key) {
+                Some((name, _)) => write!(f, "{}", name),
+                None => write!(f, "{:?}", key),
+            },
+            Button::Mouse(MouseButton::Other(button)) => write!(f, "{}{}", MOUSE_PREFIX, button),
+            Button::Mouse(button) => write!(f, "{}{:?}", MOUSE_PREFIX, button),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        let buttons = [
+            ("W", Button::Key(VirtualKeyCode::W)),
+            ("7", Button::Key(VirtualKeyCode::Key7)),
+            ("LShift", Button::Key(VirtualKeyCode::LShift)),
+            ("MouseLeft", Button::Mouse(MouseButton::Left)),
+            ("MouseMiddle", Button::Mouse(MouseButton::Middle)),
+            ("Mouse4", Button::Mouse(MouseButton::Other(4))),
+        ];
+        for (name, button) in buttons {
+            assert_eq!(Button::from_name(name), Some(button));
+            assert_eq!(button.to_string(), name);
+        }
+
+        // Names aren't case sensitive, and unknown names aren't bound to anything.
+        assert_eq!(
+            Button::from_name("space"),
+            Some(Button::Key(VirtualKeyCode::Space))
+        );
+        assert_eq!(
+            Button::from_name("mouseright"),
+            Some(Button::Mouse(MouseButton::Right))
+        );
+        for name in ["", "Key1", "Mouse", "MouseWheel", "Ω"] {
+            assert_eq!(Button::from_name(name), None, "{}", name);
+        }
+    }
+}
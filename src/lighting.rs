@@ -0,0 +1,419 @@
+//! Implementation of light sources and the materials that describe how surfaces respond to them.
+//!
+//! Lights are placed in world space and surfaces are lit with either the Lambert or Blinn-Phong shading model. Colours
+//! are stored as RGB with each component between 0 and 1, although lights can be brighter than 1.
+//!
+
+use crate::{
+    framebuffer::BlendMode,
+    mesh::geometry::{Point, Vector},
+    texture::Texture,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// How a light's intensity falls off with distance: 1 / (constant + linear * d + quadratic * d^2).
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Attenuation {
+    pub constant: f64,
+    pub linear: f64,
+    pub quadratic: f64,
+}
+
+/// A source of light in the world.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Light {
+    /// Light reaching every surface equally from all directions.
+    Ambient { colour: [f64; 3] },
+    /// Light travelling in a single direction from infinitely far away.
+    Directional {
+        direction: Vector<3>,
+        colour: [f64; 3],
+    },
+    /// Light shining in all directions from a point.
+    Point {
+        position: Point<3>,
+        colour: [f64; 3],
+        attenuation: Attenuation,
+    },
+    /// Light shining from a point in a cone around a direction. Surfaces within the inner angle are fully lit, and
+    /// the light fades out between the inner and outer angles, which are given in degrees.
+    Spot {
+        position: Point<3>,
+        direction: Vector<3>,
+        colour: [f64; 3],
+        attenuation: Attenuation,
+        inner_angle: f64,
+        outer_angle: f64,
+    },
+}
+
+/// The model used to find how much light a surface reflects towards the camera.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ShadingModel {
+    /// Diffuse reflection only.
+    Lambert,
+    /// Diffuse reflection with specular highlights.
+    BlinnPhong,
+}
+
+/// The properties of a surface used to light it.
+/// The base colour is multiplied by the surface's texture and vertex colours, if it has them. The opacity is
+/// multiplied into the surface's alpha, which only has an effect when the material isn't blended as opaque.
+///
+#[derive(Clone)]
+pub struct Material {
+    pub base_colour: [f64; 3],
+    pub specular_colour: [f64; 3],
+    pub shininess: f64,
+    pub model: ShadingModel,
+    pub opacity: f64,
+    pub blend: BlendMode,
+
+    pub texture: Option<Texture>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Attenuation {
+    /// Return an attenuation where the intensity doesn't fall off with distance.
+    ///
+    pub const fn none() -> Attenuation {
+        Attenuation {
+            constant: 1.0,
+            linear: 0.0,
+            quadratic: 0.0,
+        }
+    }
+
+    /// Return an attenuation where the intensity falls to roughly 1% at the given range.
+    ///
+    pub fn with_range(range: f64) -> Attenuation {
+        Attenuation {
+            constant: 1.0,
+            linear: 4.5 / range,
+            quadratic: 75.0 / range.powi(2),
+        }
+    }
+}
+
+impl Material {
+    /// Return a new untextured opaque Lambert material with the given base colour.
+    ///
+    pub fn new(base_colour: [f64; 3]) -> Material {
+        Material {
+            base_colour,
+            specular_colour: [0.0; 3],
+            shininess: 32.0,
+            model: ShadingModel::Lambert,
+            opacity: 1.0,
+            blend: BlendMode::Opaque,
+            texture: None,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Attenuation {
+    /// Return the fraction of a light's intensity that remains at a distance.
+    ///
+    pub fn factor(&self, distance: f64) -> f64 {
+        1.0 / (self.constant + (self.linear * distance) + (self.quadratic * distance.powi(2)))
+    }
+}
+
+impl Light {
+    /// Return the unit vector from a surface point towards the light, along with the colour of the light reaching it,
+    /// or None for ambient lights and points the light doesn't reach.
+    ///
+    fn incidence(&self, position: &Point<3>) -> Option<(Vector<3>, [f64; 3])> {
+        match *self {
+            Light::Ambient { .. } => None,
+            Light::Directional { direction, colour } => Some((-direction.normalise(), colour)),
+            Light::Point {
+                position: light_position,
+                colour,
+                attenuation,
+            } => {
+                let to_light = position.vector_to(&light_position);
+                let factor = attenuation.factor(to_light.magnitude());
+                Some((to_light.normalise(), colour.map(|channel| channel * factor)))
+            }
+            Light::Spot {
+                position: light_position,
+                direction,
+                colour,
+                attenuation,
+                inner_angle,
+                outer_angle,
+            } => {
+                let to_light = position.vector_to(&light_position);
+                let to_light_unit = to_light.normalise();
+
+                // Fade smoothly between the cosines of the inner and outer angles.
+                let cos_angle = -to_light_unit.dot(&direction.normalise());
+                let (cos_inner, cos_outer) = (
+                    inner_angle.to_radians().cos(),
+                    outer_angle.to_radians().cos(),
+                );
+                let cone = if cos_inner <= cos_outer {
+                    if cos_angle >= cos_outer {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                } else {
+                    let t = ((cos_angle - cos_outer) / (cos_inner - cos_outer)).clamp(0.0, 1.0);
+                    t * t * (3.0 - (2.0 * t))
+                };
+                if cone == 0.0 {
+                    return None;
+                }
+
+                let factor = attenuation.factor(to_light.magnitude()) * cone;
+                Some((to_light_unit, colour.map(|channel| channel * factor)))
+            }
+        }
+    }
+}
+
+impl Material {
+    /// Return the RGBA colour of a surface point lit by a list of lights and seen from the eye position.
+    /// The surface colour is the texture and vertex colour at the point, which is multiplied by the base colour.
+    /// Each channel of the result is between 0 and 1.
+    ///
+    #[allow(dead_code)]
+    pub fn shade(
+        &self,
+        lights: &[Light],
+        position: &Point<3>,
+        normal: &Vector<3>,
+        eye: &Point<3>,
+        surface: [f64; 4],
+    ) -> [f64; 4] {
+        self.shade_shadowed(lights, |_| 1.0, position, normal, eye, surface)
+    }
+
+    /// Return the colour of a surface point in the same way as shade, with the light from each light scaled by the
+    /// fraction of it that reaches the point. The visibility closure is given the index of the light and returns the
+    /// fraction, from 0 in full shadow to 1 fully lit. Ambient light is never shadowed.
+    ///
+    pub fn shade_shadowed<F>(
+        &self,
+        lights: &[Light],
+        visibility: F,
+        position: &Point<3>,
+        normal: &Vector<3>,
+        eye: &Point<3>,
+        surface: [f64; 4],
+    ) -> [f64; 4]
+    where
+        F: Fn(usize) -> f64,
+    {
+        let mut diffuse = self.base_colour;
+        diffuse
+            .iter_mut()
+            .zip(surface.iter())
+            .for_each(|(channel, surface)| *channel *= surface);
+
+        let normal = normal.normalise();
+        let to_eye = position.vector_to(eye).normalise();
+
+        let mut colour = [0.0; 3];
+        for (index, light) in lights.iter().enumerate() {
+            if let Light::Ambient { colour: ambient } = light {
+                for i in 0..3 {
+                    colour[i] += diffuse[i] * ambient[i];
+                }
+                continue;
+            }
+
+            let (to_light, radiance) = match light.incidence(position) {
+                Some(incidence) => incidence,
+                None => continue,
+            };
+            let lambert = normal.dot(&to_light);
+            if lambert <= 0.0 {
+                continue;
+            }
+            let visible = visibility(index);
+            if visible <= 0.0 {
+                continue;
+            }
+
+            let specular = match self.model {
+                ShadingModel::Lambert => 0.0,
+                ShadingModel::BlinnPhong => {
+                    let halfway = (to_light + &to_eye).normalise();
+                    normal.dot(&halfway).max(0.0).powf(self.shininess)
+                }
+            };
+
+            for i in 0..3 {
+                colour[i] += radiance[i]
+                    * visible
+                    * ((diffuse[i] * lambert) + (self.specular_colour[i] * specular));
+            }
+        }
+
+        [
+            colour[0].min(1.0),
+            colour[1].min(1.0),
+            colour[2].min(1.0),
+            surface[3] * self.opacity,
+        ]
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: [f64; 4] = [1.0; 4];
+
+    fn assert_colour(colour: [f64; 4], expected: [f64; 4]) {
+        for (channel, expected) in colour.iter().zip(expected.iter()) {
+            assert!(
+                (channel - expected).abs() < 1e-9,
+                "{:?} != {:?}",
+                colour,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_lambert() {
+        let material = Material::new([1.0, 0.5, 0.0]);
+        let lights = [
+            Light::Ambient {
+                colour: [0.1, 0.1, 0.1],
+            },
+            Light::Directional {
+                direction: Vector::new([0.0, -1.0, -1.0]),
+                colour: [1.0, 1.0, 1.0],
+            },
+        ];
+        let (position, eye) = (Point::new([0, 0, 0]), Point::new([0, 0, -10]));
+
+        // Lit at 45 degrees.
+        let normal = Vector::new([0, 0, 1]);
+        let colour = material.shade(&lights, &position, &normal, &eye, WHITE);
+        let lit = 0.1 + f64::sqrt(0.5);
+        assert_colour(colour, [lit, lit * 0.5, 0.0, 1.0]);
+
+        // Facing away from the light only receives ambient light.
+        let normal = Vector::new([0, -1, 0]);
+        let colour = material.shade(&lights, &position, &normal, &eye, [0.5, 0.5, 0.5, 0.5]);
+        assert_colour(colour, [0.05, 0.025, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn test_blinn_phong_highlight() {
+        let mut material = Material::new([0.0, 0.0, 0.0]);
+        material.model = ShadingModel::BlinnPhong;
+        material.specular_colour = [1.0, 1.0, 1.0];
+        let lights = [Light::Point {
+            position: Point::new([0, 0, -10]),
+            colour: [1.0, 1.0, 1.0],
+            attenuation: Attenuation::none(),
+        }];
+        let normal = Vector::new([0, 0, -1]);
+
+        // The light is reflected straight back to the eye.
+        let eye = Point::new([0, 0, -10]);
+        let colour = material.shade(&lights, &Point::new([0, 0, 0]), &normal, &eye, WHITE);
+        assert_colour(colour, [1.0, 1.0, 1.0, 1.0]);
+
+        // The highlight falls off quickly away from the reflection.
+        let eye = Point::new([10, 0, -10]);
+        let colour = material.shade(&lights, &Point::new([0, 0, 0]), &normal, &eye, WHITE);
+        assert!(colour[0] < 0.2);
+    }
+
+    #[test]
+    fn test_shadowed() {
+        let material = Material::new([1.0, 1.0, 1.0]);
+        let lights = [
+            Light::Ambient {
+                colour: [0.1, 0.1, 0.1],
+            },
+            Light::Directional {
+                direction: Vector::new([0, 0, 1]),
+                colour: [0.5, 0.5, 0.5],
+            },
+            Light::Directional {
+                direction: Vector::new([0, 0, 1]),
+                colour: [0.25, 0.25, 0.25],
+            },
+        ];
+        let (position, normal, eye) = (
+            Point::new([0, 0, 0]),
+            Vector::new([0, 0, -1]),
+            Point::new([0, 0, -10]),
+        );
+
+        // The first directional light is completely shadowed and the second is half shadowed, but the ambient light
+        // isn't affected.
+        let visibility = |index| match index {
+            0 | 1 => 0.0,
+            _ => 0.5,
+        };
+        let colour = material.shade_shadowed(&lights, visibility, &position, &normal, &eye, WHITE);
+        assert_colour(colour, [0.225, 0.225, 0.225, 1.0]);
+
+        let unshadowed = material.shade_shadowed(&lights, |_| 1.0, &position, &normal, &eye, WHITE);
+        assert_colour(
+            unshadowed,
+            material.shade(&lights, &position, &normal, &eye, WHITE),
+        );
+    }
+
+    #[test]
+    fn test_point_attenuation() {
+        let attenuation = Attenuation {
+            constant: 1.0,
+            linear: 0.5,
+            quadratic: 0.25,
+        };
+        assert_eq!(attenuation.factor(2.0), 1.0 / 3.0);
+        assert_eq!(Attenuation::none().factor(100.0), 1.0);
+        assert!(Attenuation::with_range(100.0).factor(100.0) < 0.02);
+    }
+
+    #[test]
+    fn test_spot_cone() {
+        let light = Light::Spot {
+            position: Point::new([0, 0, 0]),
+            direction: Vector::new([0, 0, 1]),
+            colour: [1.0, 1.0, 1.0],
+            attenuation: Attenuation::none(),
+            inner_angle: 20.0,
+            outer_angle: 30.0,
+        };
+
+        let inside = light.incidence(&Point::new([0, 0, 10])).unwrap();
+        assert_eq!(inside.1, [1.0, 1.0, 1.0]);
+
+        // 25 degrees is half way through the fade.
+        let edge = light.incidence(&Point::new([25f64.to_radians().tan(), 0.0, 1.0]));
+        let colour = edge.unwrap().1[0];
+        assert!(colour > 0.3 && colour < 0.7);
+
+        assert!(light.incidence(&Point::new([1, 0, 1])).is_none());
+    }
+}
@@ -1,14 +1,43 @@
+#[cfg(test)]
+mod allocation;
+mod camera;
+mod controller;
+mod fog;
+mod framebuffer;
+#[cfg(test)]
+mod golden;
+mod image;
+mod input;
+mod lighting;
 mod mesh;
+mod occlusion;
 mod physics;
+mod projection;
 mod rasterizer;
+mod shader;
+mod shadow;
+mod texture;
+mod tiled;
 mod window;
 //mod world_object;
 
 use crate::{
-    mesh::geometry::{Dim, OrientationVector3D, Point, Vector},
-    mesh::Mesh,
-    rasterizer::EdgeTable,
-    window::{DrawType, GraphicsWindow},
+    camera::Camera,
+    controller::{CameraController, FlyController, OrbitController, PanController},
+    fog::{Fog, FogFalloff},
+    framebuffer::{compare_draw_order, BlendMode, DepthTest, DrawType, LineStyle, RenderTarget},
+    input::{Action, Bindings, InputState},
+    lighting::{Attenuation, Light, Material, ShadingModel},
+    mesh::geometry::{Dim, Orientation3D, Point, Quaternion, Vector},
+    mesh::{CullMode, Mesh, NormalWeighting, PipelineStats, Winding},
+    occlusion::OcclusionCuller,
+    projection::{FieldOfView, Frustum},
+    rasterizer::{AntiAliasing, RasterMethod, SampleCount, SamplePattern},
+    shader::{FragmentShader, LitShader, NormalShader, ShadingMode, ToonShader},
+    shadow::{ShadowMap, ShadowSettings},
+    texture::{Filter, Texture, WrapMode},
+    tiled::TiledRenderer,
+    window::GraphicsWindow,
 };
 use std::time::{Duration, Instant};
 use winit::{
@@ -16,23 +45,178 @@ use winit::{
     event_loop::{ControlFlow, EventLoop},
 };
 
+/// The fragment shaders the scene can be drawn with.
+///
+#[derive(Debug, Clone, Copy)]
+enum Effect {
+    Lit,
+    Toon,
+    Normals,
+}
+
 fn main() -> ! {
     // Create the window
     let event_loop = EventLoop::new();
     let mut window = GraphicsWindow::new(960, 720, &event_loop);
     window.clear();
 
-    // Build a mesh in the form of a cube.
+    // View the scene from above and in front, looking down at the middle of the floor.
+    let scene_centre = Point::new([0, -50, 450]);
+    window.camera = Camera::look_at(
+        &Point::new([0, 150, -100]),
+        &scene_centre,
+        &Vector::new([0, 1, 0]),
+    );
+
+    // Read the key and mouse button bindings, falling back to the defaults for anything not rebound.
+    let bindings = match Bindings::load("bindings.cfg") {
+        Ok(bindings) => bindings,
+        Err(input::Error::Io(error)) if error.kind() == std::io::ErrorKind::NotFound => {
+            Bindings::default()
+        }
+        Err(error) => {
+            println!("Failed to load bindings.cfg, using the defaults: {}", error);
+            Bindings::default()
+        }
+    };
+    let mut input = InputState::new(bindings);
+
+    // Cycle through the ways the camera can be moved: orbiting around the middle of the scene, flying around it while
+    // the look action is held, and panning across it.
+    let mut controllers: [Box<dyn CameraController>; 3] = [
+        Box::new(OrbitController::new(scene_centre)),
+        Box::new(FlyController::new(300.0)),
+        Box::new(PanController::new(300.0)),
+    ];
+    let controller_names = ["orbit", "fly", "pan"];
+    let mut controller = 0;
+    let mut time_of_last_update = Instant::now();
+
+    // Build a mesh in the form of a textured cube.
     // Set it's initial position and velocities so that it moves around the screen.
     let mut cube = Mesh::default();
-    cube.load_cube(100.0);
+    cube.load_textured_cube(100.0, 1.5);
     cube.physics.position = Point::new([0, 0, 400]);
     let mut cube_velocity = Vector::new([1, 1, 1]);
 
+    // Build a smooth sphere that sits to one side of the screen. It's half transparent once it's blended.
+    let mut sphere = Mesh::default();
+    sphere.load_sphere(60.0, 12, 18);
+    sphere.compute_vertex_normals(60.0, NormalWeighting::Angle);
+    sphere.physics.position = Point::new([-150, -50, 450]);
+    let mut sphere_material = Material::new([0.9, 0.9, 0.9]);
+    sphere_material.opacity = 0.5;
+
+    // Lay a floor below the meshes for their shadows to fall on.
+    let mut floor = Mesh::default();
+    floor.load_plane(1200.0, 1200.0);
+    floor.physics.position = Point::new([0, -250, 500]);
+    let floor_material = Material::new([0.6, 0.6, 0.6]);
+
+    // Keep the meshes run through the pipeline between frames, so that their buffers are reused.
+    let mut cube_pipe = Mesh::default();
+    let mut sphere_pipe = Mesh::default();
+    let mut floor_pipe = Mesh::default();
+
     // Set controls for pausing and manually advancing each frame.
     let mut pause = false;
     let mut advance_frame = false;
 
+    // Set the algorithm used to fill polygons and how they're lit.
+    let mut raster_method = RasterMethod::EdgeTable;
+    let mut shading = ShadingMode::Gouraud;
+    let mut effect = Effect::Lit;
+
+    // Cycle through the anti-aliasing modes to compare their quality and frame time.
+    let anti_aliasing_modes = [
+        AntiAliasing::Off,
+        AntiAliasing::Supersample(SampleCount::X2, SamplePattern::Grid),
+        AntiAliasing::Supersample(SampleCount::X4, SamplePattern::Grid),
+        AntiAliasing::Supersample(SampleCount::X4, SamplePattern::RotatedGrid),
+        AntiAliasing::Supersample(SampleCount::X8, SamplePattern::RotatedGrid),
+        AntiAliasing::Multisample(SampleCount::X4, SamplePattern::RotatedGrid),
+        AntiAliasing::Multisample(SampleCount::X8, SamplePattern::RotatedGrid),
+    ];
+    let mut anti_aliasing = 0;
+
+    // Optionally split the screen into tiles drawn on every available thread.
+    let mut tiled_renderer: Option<TiledRenderer> = None;
+
+    // Optionally skip hidden work: fill the depth buffer before shading, test meshes and triangles against a depth
+    // pyramid before drawing them, and sort the triangles of opaque meshes from front to back.
+    let mut depth_prepass = false;
+    let mut occlusion_culling = false;
+    let mut front_to_back = false;
+    let mut occlusion_culler = OcclusionCuller::default();
+
+    // Set how meshes are drawn, and draw any edges as white anti-aliased lines.
+    let mut draw_type = DrawType::Fill;
+    let line_style = LineStyle::new([255, 255, 255, 255]);
+
+    // Give the cube a shiny checkerboard textured material.
+    let mut material = Material::new([1.0, 1.0, 1.0]);
+    material.model = ShadingModel::BlinnPhong;
+    material.specular_colour = [0.6, 0.6, 0.6];
+    material.texture = Some(Texture::checkerboard(
+        64,
+        8,
+        [[255, 255, 255, 255], [40, 40, 160, 255]],
+    ));
+
+    // Light the scene with a dim ambient light, a key light from the top left, a warm point light to the right and a
+    // spot light shining from the camera into the centre of the screen.
+    let mut lights = [
+        Light::Ambient {
+            colour: [0.15, 0.15, 0.15],
+        },
+        Light::Directional {
+            direction: Vector::new([1, -1, 1]),
+            colour: [0.7, 0.7, 0.7],
+        },
+        Light::Point {
+            position: Point::new([300, 0, 300]),
+            colour: [0.8, 0.6, 0.3],
+            attenuation: Attenuation::with_range(2000.0),
+        },
+        Light::Spot {
+            position: window.camera.position(),
+            direction: window.camera.forward(),
+            colour: [0.4, 0.4, 0.4],
+            attenuation: Attenuation::none(),
+            inner_angle: 8.0,
+            outer_angle: 12.0,
+        },
+    ];
+
+    // Let the directional light cast shadows, split into cascades across the view.
+    let mut shadows = false;
+    let mut shadow_settings = ShadowSettings::new(512, 1000.0);
+    shadow_settings.cascades = 3;
+    let mut shadow_maps = [None, Some(ShadowMap::new(shadow_settings)), None, None];
+
+    // Optionally fade distant objects into fog that matches the background, so they don't pop in and out at the far
+    // plane.
+    let mut fog_falloff: Option<FogFalloff> = None;
+    let fog_colour = [0.55, 0.6, 0.7];
+
+    // Cycle through the shapes of the view, and optionally reverse depths and push the far plane out to infinity.
+    let frustums = [
+        Frustum::Perspective(FieldOfView::Vertical(45.0)),
+        Frustum::Perspective(FieldOfView::Horizontal(90.0)),
+        Frustum::OffCentre {
+            left: -20.0,
+            right: 60.0,
+            bottom: -30.0,
+            top: 30.0,
+        },
+        Frustum::Orthographic { height: 800.0 },
+    ];
+    let mut frustum = 0;
+    let far_plane = window.projection.far_plane();
+
+    // Count saved frames so each one gets a new file name.
+    let mut saved_frames = 0;
+
     // Set up a timers to limit and measure frame rate.
     // Aim for 15ms minimum between frames. Equivilent to 66.6FPS.
     let mut time_of_current_frame = Instant::now();
@@ -45,6 +229,11 @@ fn main() -> ! {
         // This controls how the thread runs the code. In poll mode, it will loop through the code.
         *control_flow = ControlFlow::Poll;
 
+        // Keep track of the keys and mouse buttons pressed, held and released.
+        if let Event::WindowEvent { event, .. } = &event {
+            input.handle_window_event(event);
+        }
+
         match event {
             // Handle any event triggered by the user.
             // E.g resizing the window, key presses, etc.
@@ -58,20 +247,213 @@ fn main() -> ! {
                 // User has resized the window.
                 WindowEvent::Resized(size) => window.resize(size.width, size.height),
 
-                // User has pressed a key.
-                WindowEvent::ReceivedCharacter(char) => match char {
-                    ' ' => pause = !pause,
-                    'n' => advance_frame = true,
-                    _ => {}
-                },
-
                 // Anything else.
                 _ => {}
             },
 
+            // Raw mouse motion is used to turn the camera.
+            Event::DeviceEvent { event, .. } => input.handle_device_event(&event),
+
             // This event is triggered when all user events have been handled.
-            // Decide whether to redraw the window at this time.
+            // Handle the actions triggered by them, move the camera and decide whether to redraw the window at this
+            // time.
             Event::MainEventsCleared => {
+                // Buttons being used to move the camera don't trigger anything else.
+                let used_actions = controllers[controller].used_actions(&input);
+                let pressed_actions: Vec<_> = input.pressed_actions_except(used_actions).collect();
+                for action in pressed_actions {
+                    match action {
+                        Action::TogglePause => pause = !pause,
+                        Action::AdvanceFrame => advance_frame = true,
+                        Action::CycleRasterMethod => {
+                            raster_method = match raster_method {
+                                RasterMethod::EdgeTable => RasterMethod::HalfSpace,
+                                RasterMethod::HalfSpace => RasterMethod::EdgeTable,
+                            };
+                            println!("Rasterizing with {:?}", raster_method);
+                        }
+                        Action::CycleShading => {
+                            shading = match shading {
+                                ShadingMode::Flat => ShadingMode::Gouraud,
+                                ShadingMode::Gouraud => ShadingMode::Phong,
+                                ShadingMode::Phong => ShadingMode::Flat,
+                            };
+                            println!("Shading with {:?}", shading);
+                        }
+                        Action::CycleCulling => {
+                            let mode = match cube.culling.mode {
+                                CullMode::Back => CullMode::Front,
+                                CullMode::Front => CullMode::None,
+                                CullMode::None => CullMode::Back,
+                            };
+                            cube.culling.mode = mode;
+                            sphere.culling.mode = mode;
+                            println!("Culling with {:?}", mode);
+                        }
+                        Action::FlipFrontFace => {
+                            let front_face = match cube.culling.front_face {
+                                Winding::Clockwise => Winding::CounterClockwise,
+                                Winding::CounterClockwise => Winding::Clockwise,
+                            };
+                            cube.culling.front_face = front_face;
+                            sphere.culling.front_face = front_face;
+                            println!("Front faces are wound {:?}", front_face);
+                        }
+                        Action::CycleDrawType => {
+                            draw_type = match draw_type {
+                                DrawType::Fill => DrawType::Both,
+                                DrawType::Both => DrawType::Wireframe,
+                                DrawType::Wireframe => DrawType::HiddenLine,
+                                DrawType::HiddenLine => DrawType::Fill,
+                            };
+                            println!("Drawing meshes with {:?}", draw_type);
+                        }
+                        Action::CycleAntiAliasing => {
+                            anti_aliasing = (anti_aliasing + 1) % anti_aliasing_modes.len();
+                            window.set_anti_aliasing(anti_aliasing_modes[anti_aliasing]);
+                            println!(
+                                "Anti-aliasing with {:?}",
+                                anti_aliasing_modes[anti_aliasing]
+                            );
+                        }
+                        Action::ToggleTiledRendering => {
+                            tiled_renderer = match tiled_renderer {
+                                Some(_) => None,
+                                None => Some(TiledRenderer::with_available_parallelism(64)),
+                            };
+                            println!("Tiled rendering: {}", tiled_renderer.is_some());
+                        }
+                        Action::CycleBlendMode => {
+                            sphere_material.blend = match sphere_material.blend {
+                                BlendMode::Opaque => BlendMode::Alpha,
+                                BlendMode::Alpha => BlendMode::Additive,
+                                BlendMode::Additive => BlendMode::Multiply,
+                                BlendMode::Multiply => BlendMode::WeightedBlended,
+                                BlendMode::WeightedBlended | BlendMode::DepthOnly => {
+                                    BlendMode::Opaque
+                                }
+                            };
+                            println!("Blending the sphere with {:?}", sphere_material.blend);
+                        }
+                        Action::ToggleDepthPrepass => {
+                            depth_prepass = !depth_prepass;
+                            println!("Depth prepass: {}", depth_prepass);
+                        }
+                        Action::ToggleOcclusionCulling => {
+                            occlusion_culling = !occlusion_culling;
+                            println!("Occlusion culling: {}", occlusion_culling);
+                        }
+                        Action::ToggleFrontToBack => {
+                            front_to_back = !front_to_back;
+                            println!("Sorting opaque triangles front to back: {}", front_to_back);
+                        }
+                        Action::ToggleShadows => {
+                            shadows = !shadows;
+                            println!("Shadows: {}", shadows);
+                        }
+                        Action::CycleFog => {
+                            fog_falloff = match fog_falloff {
+                                None => Some(FogFalloff::Linear),
+                                Some(FogFalloff::Linear) => Some(FogFalloff::Exponential),
+                                Some(FogFalloff::Exponential) => {
+                                    Some(FogFalloff::ExponentialSquared)
+                                }
+                                Some(FogFalloff::ExponentialSquared) => None,
+                            };
+                            let fog = fog_falloff.map(|falloff| {
+                                Fog::new(fog_colour, falloff, 200.0, far_plane.unwrap_or(1000.0))
+                            });
+                            window.set_fog(fog);
+                            window.set_clear_colour(
+                                fog.map_or([0, 0, 0, 0], |fog| fog.clear_colour()),
+                            );
+                            println!("Fog: {:?}", fog_falloff);
+                        }
+                        Action::CycleFrustum => {
+                            frustum = (frustum + 1) % frustums.len();
+                            window.projection.set_frustum(frustums[frustum]);
+                            println!("Viewing through {:?}", frustums[frustum]);
+                        }
+                        Action::ToggleInfiniteFarPlane => {
+                            let infinite = window.projection.far_plane().is_none();
+                            if !matches!(frustums[frustum], Frustum::Orthographic { .. }) {
+                                let far = if infinite { far_plane } else { None };
+                                window.projection.set_far_plane(far);
+                                window.projection.set_reversed_z(!infinite);
+                            }
+                            println!(
+                                "Reversed-Z with an infinite far plane: {}",
+                                window.projection.reversed_z()
+                            );
+                        }
+                        Action::CycleEffect => {
+                            effect = match effect {
+                                Effect::Lit => Effect::Toon,
+                                Effect::Toon => Effect::Normals,
+                                Effect::Normals => Effect::Lit,
+                            };
+                            println!("Drawing with the {:?} effect", effect);
+                        }
+                        Action::CycleTextureFilter => {
+                            let texture = material.texture.as_mut().unwrap();
+                            texture.filter = match texture.filter {
+                                Filter::Nearest => Filter::Bilinear,
+                                Filter::Bilinear => Filter::Trilinear,
+                                Filter::Trilinear => Filter::Nearest,
+                            };
+                            println!("Filtering textures with {:?}", texture.filter);
+                        }
+                        Action::CycleTextureWrap => {
+                            let texture = material.texture.as_mut().unwrap();
+                            texture.wrap = match texture.wrap {
+                                WrapMode::Repeat => WrapMode::Clamp,
+                                WrapMode::Clamp => WrapMode::Mirror,
+                                WrapMode::Mirror => WrapMode::Repeat,
+                            };
+                            println!("Wrapping textures with {:?}", texture.wrap);
+                        }
+                        Action::CycleCameraController => {
+                            controller = (controller + 1) % controllers.len();
+                            println!(
+                                "Moving the camera with the {} controller",
+                                controller_names[controller]
+                            );
+                        }
+                        Action::SaveFrame | Action::SaveDepth => {
+                            saved_frames += 1;
+                            let result = if action == Action::SaveFrame {
+                                window.save_frame(format!("frame_{:03}.png", saved_frames))
+                            } else {
+                                window.save_depth(format!("depth_{:03}.png", saved_frames))
+                            };
+
+                            match result {
+                                Ok(_) => println!("Saved frame {}", saved_frames),
+                                Err(error) => println!("Failed to save frame: {}", error),
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                // Move the camera by the input since the last update. Long gaps, such as while the window is being
+                // dragged, are shortened so that held keys don't send the camera flying.
+                let elapsed = time_of_last_update
+                    .elapsed()
+                    .min(Duration::from_millis(100));
+                time_of_last_update = Instant::now();
+                controllers[controller].update(&mut window.camera, &input, elapsed);
+                input.end_frame();
+                if let Light::Spot {
+                    position,
+                    direction,
+                    ..
+                } = &mut lights[3]
+                {
+                    *position = window.camera.position();
+                    *direction = window.camera.forward();
+                }
+
                 // Redraw if either:
                 // Window is running and a new frame is due according to the framerate timer.
                 // User has manualy requested a new frame.
@@ -108,19 +490,136 @@ fn main() -> ! {
 
                 // Move and rotate the mesh.
                 cube.physics.position.translate(&cube_velocity);
-                cube.physics.orientation += OrientationVector3D::new(1, 0.6, 3);
-
-                // Get a copy of the cube that's been run through the pipeline.
-                // This copy will be in NDC space.
-                let cube_pipe = cube.run_pipeline(
-                    &window.projection_matrix,
-                    [window.width as f64, window.height as f64],
-                );
-
-                // Generate an edge table for every polygon in the mesh and draw it to the screen buffer.
-                for polygon in cube_pipe.iter_visible_polygons() {
-                    window.draw_polygon(&EdgeTable::new(polygon), DrawType::Fill);
+                cube.physics
+                    .rotate(&Quaternion::from(Orientation3D::new(1, 0.6, 3)));
+
+                // Get copies of the meshes that have been run through the pipeline.
+                // These copies will be in screen space.
+                let window_size = [window.width as f64, window.height as f64];
+                let (camera, projection) = (&window.camera, &window.projection);
+                cube.run_pipeline_into(camera, projection, window_size, &mut cube_pipe);
+                sphere.run_pipeline_into(camera, projection, window_size, &mut sphere_pipe);
+                floor.run_pipeline_into(camera, projection, window_size, &mut floor_pipe);
+
+                let mut stats = PipelineStats::default();
+                stats += cube_pipe.stats();
+                stats += sphere_pipe.stats();
+                stats += floor_pipe.stats();
+
+                // Render the shadow maps from each light's point of view.
+                if shadows {
+                    let casters = [&cube, &sphere];
+                    for (light, shadow_map) in lights.iter().zip(shadow_maps.iter_mut()) {
+                        if let Some(shadow_map) = shadow_map {
+                            shadow_map.render(light, &casters, &window.camera, &window.projection);
+                        }
+                    }
+                }
+
+                // Draw opaque meshes first, then transparent meshes from back to front.
+                let mut meshes = [
+                    (&cube, &mut cube_pipe, &material),
+                    (&sphere, &mut sphere_pipe, &sphere_material),
+                    (&floor, &mut floor_pipe, &floor_material),
+                ];
+                for (_, mesh, material) in meshes.iter_mut() {
+                    if material.blend == BlendMode::Alpha {
+                        mesh.sort_back_to_front();
+                    } else if front_to_back && !material.blend.is_transparent() {
+                        mesh.sort_front_to_back();
+                    }
                 }
+                meshes.sort_by(|a, b| compare_draw_order((a.1, a.2.blend), (b.1, b.2.blend)));
+
+                // Wireframes aren't depth tested, so nothing can be skipped based on depth.
+                let depth_tested = draw_type != DrawType::Wireframe;
+                let occlusion_culling = occlusion_culling && depth_tested;
+                let depth_prepass = depth_prepass && depth_tested;
+
+                // Fill the depth buffer with the opaque meshes, so that only their visible fragments are shaded.
+                if depth_prepass {
+                    for (_, mesh, material) in &meshes {
+                        if !material.blend.is_transparent() {
+                            window.draw_depth(mesh, raster_method);
+                        }
+                    }
+                    window.set_depth_test(DepthTest::GreaterEqual);
+                }
+                occlusion_culler.reset_stats();
+                if occlusion_culling {
+                    window.build_depth_pyramid();
+                }
+
+                // Rasterize every polygon in the meshes into the screen buffer.
+                for (source, mesh, material) in meshes.iter_mut() {
+                    if occlusion_culling {
+                        let pyramid = window.depth_pyramid();
+                        let (camera, projection) = (&window.camera, &window.projection);
+                        if occlusion_culler.is_occluded(
+                            pyramid,
+                            source,
+                            camera,
+                            projection,
+                            window_size,
+                        ) {
+                            continue;
+                        }
+                        occlusion_culler.cull_triangles(pyramid, mesh);
+                    }
+
+                    let renderer = tiled_renderer.as_mut();
+                    match effect {
+                        Effect::Lit => {
+                            let mut shader = LitShader::new(material, &lights, shading);
+                            shader.eye = window.camera.position();
+                            if shadows {
+                                shader.shadows = &shadow_maps;
+                            }
+                            draw_mesh(
+                                &mut window,
+                                renderer,
+                                mesh,
+                                draw_type,
+                                raster_method,
+                                &shader,
+                                &line_style,
+                            );
+                        }
+                        Effect::Toon => {
+                            let mut shader =
+                                ToonShader::new(material.base_colour, Vector::new([1, -1, 1]), 4);
+                            shader.eye = window.camera.position();
+                            draw_mesh(
+                                &mut window,
+                                renderer,
+                                mesh,
+                                draw_type,
+                                raster_method,
+                                &shader,
+                                &line_style,
+                            );
+                        }
+                        Effect::Normals => {
+                            let shader = NormalShader;
+                            draw_mesh(
+                                &mut window,
+                                renderer,
+                                mesh,
+                                draw_type,
+                                raster_method,
+                                &shader,
+                                &line_style,
+                            );
+                        }
+                    }
+
+                    // Without a prepass the pyramid only holds the meshes drawn so far, so add each opaque mesh to it.
+                    if occlusion_culling && !depth_prepass && !material.blend.is_transparent() {
+                        window.build_depth_pyramid();
+                    }
+                }
+                window.set_depth_test(DepthTest::Greater);
+                window.composite_transparency();
 
                 // Render the screen buffer.
                 window.render();
@@ -137,8 +636,45 @@ fn main() -> ! {
                 average /= 100;
 
                 println!("average: {}, last: {}", average, last_time);
+                println!("triangles: {}, culled: {}", stats.triangles, stats.culled);
+                if occlusion_culling {
+                    let occlusion = occlusion_culler.stats();
+                    println!(
+                        "occluded objects: {}/{}, occluded triangles: {}/{}",
+                        occlusion.objects_occluded,
+                        occlusion.objects_tested,
+                        occlusion.triangles_occluded,
+                        occlusion.triangles_tested
+                    );
+                }
             }
             _ => (),
         }
     });
 }
+
+/// Draw a mesh into the window, using the tiled renderer if there is one.
+///
+fn draw_mesh<S>(
+    window: &mut GraphicsWindow,
+    tiled_renderer: Option<&mut TiledRenderer>,
+    mesh: &Mesh,
+    draw_type: DrawType,
+    method: RasterMethod,
+    shader: &S,
+    line_style: &LineStyle,
+) where
+    S: FragmentShader + Sync,
+{
+    match tiled_renderer {
+        Some(renderer) => renderer.draw_mesh(
+            window.frame_buffer(),
+            mesh,
+            draw_type,
+            method,
+            shader,
+            line_style,
+        ),
+        None => window.draw_mesh(mesh, draw_type, method, shader, line_style),
+    }
+}
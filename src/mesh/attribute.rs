@@ -0,0 +1,412 @@
+//! Implementation of per vertex attributes such as colours, texture coordinates and normals.
+//!
+
+use super::geometry::Point;
+use std::{fmt, mem::swap};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// The largest number of values a single vertex's attributes can take up.
+/// Each attribute can only appear once in a layout.
+///
+pub const MAX_STRIDE: usize = 16;
+
+/// Kinds of data that can be stored for each vertex.
+///
+#[allow(dead_code)]
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Attribute {
+    /// RGBA colour with each component between 0 and 1.
+    Colour,
+    /// U and V texture coordinates.
+    TexCoord,
+    /// X, Y and Z components of a normal vector.
+    Normal,
+    /// X, Y and Z world space coordinates. These are added by the pipeline so that fragments can be lit.
+    Position,
+    /// X, Y and Z view space coordinates. These are added by the pipeline so that fragments can be fogged by their
+    /// distance from the camera.
+    ViewPosition,
+    /// A single value written by a vertex shader for its fragment shader, identified by number.
+    Custom(u8),
+}
+
+/// The attributes stored for each vertex, in the order they're stored in.
+/// Layouts are stored inline so that they can be copied without allocating. Every attribute takes up at least 1
+/// value, so a layout never holds more than MAX_STRIDE of them.
+///
+#[derive(Clone, Copy)]
+pub struct AttributeLayout {
+    attributes: [Attribute; MAX_STRIDE],
+    len: usize,
+}
+
+/// Attribute values for a list of verticies. Each vertex's values are stored one after the other, in the order
+/// given by the layout.
+///
+#[derive(Debug, Default)]
+pub struct VertexAttributes {
+    layout: AttributeLayout,
+    data: Vec<f64>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl AttributeLayout {
+    /// Return a new layout containing the given attributes.
+    ///
+    /// # Panics
+    /// An attribute appears more than once.
+    ///
+    pub fn new(attributes: &[Attribute]) -> AttributeLayout {
+        let mut layout = AttributeLayout::empty();
+        for &attribute in attributes {
+            layout.push(attribute);
+        }
+        layout
+    }
+
+    /// Return a layout without any attributes.
+    ///
+    pub const fn empty() -> AttributeLayout {
+        AttributeLayout {
+            attributes: [Attribute::Custom(0); MAX_STRIDE],
+            len: 0,
+        }
+    }
+}
+
+impl VertexAttributes {
+    /// Return a new list of vertex attributes.
+    ///
+    /// # Panics
+    /// The length of data isn't a multiple of the layout's stride.
+    ///
+    pub fn new(layout: AttributeLayout, data: Vec<f64>) -> VertexAttributes {
+        let stride = layout.stride();
+        assert!(
+            data.len().is_multiple_of(stride),
+            "Error: vertex attribute data doesn't match its layout"
+        );
+
+        VertexAttributes { layout, data }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Attribute {
+    /// Return the number of values the attribute takes up.
+    ///
+    pub fn size(&self) -> usize {
+        match self {
+            Attribute::Colour => 4,
+            Attribute::TexCoord => 2,
+            Attribute::Normal => 3,
+            Attribute::Position => 3,
+            Attribute::ViewPosition => 3,
+            Attribute::Custom(_) => 1,
+        }
+    }
+}
+
+impl AttributeLayout {
+    /// Return the number of values each vertex's attributes take up.
+    ///
+    pub fn stride(&self) -> usize {
+        self.attributes().iter().map(Attribute::size).sum()
+    }
+
+    /// Return the offset of an attribute within a vertex's values, if it's part of the layout.
+    ///
+    pub fn offset(&self, attribute: Attribute) -> Option<usize> {
+        let index = self.attributes().iter().position(|&a| a == attribute)?;
+        Some(self.attributes()[..index].iter().map(Attribute::size).sum())
+    }
+
+    /// Return the values of an attribute from a vertex's or fragment's values, if it's part of the layout.
+    ///
+    pub fn read<const N: usize>(&self, values: &[f64], attribute: Attribute) -> Option<[f64; N]> {
+        let offset = self.offset(attribute)?;
+        let mut result = [0.0; N];
+        result.copy_from_slice(&values[offset..(offset + N)]);
+        Some(result)
+    }
+
+    /// Write the values of an attribute into a vertex's values.
+    ///
+    /// # Panics
+    /// The attribute isn't part of the layout.
+    ///
+    #[allow(dead_code)]
+    pub fn write(&self, values: &mut [f64], attribute: Attribute, value: &[f64]) {
+        let offset = self
+            .offset(attribute)
+            .unwrap_or_else(|| panic!("Error: {:?} isn't part of the attribute layout", attribute));
+        values[offset..(offset + value.len())].copy_from_slice(value);
+    }
+
+    /// Add an attribute to the end of the layout.
+    ///
+    /// # Panics
+    /// The attribute is already part of the layout, or its values would take the stride past MAX_STRIDE.
+    ///
+    pub fn push(&mut self, attribute: Attribute) {
+        assert!(
+            !self.attributes().contains(&attribute),
+            "Error: {:?} appears more than once in the attribute layout",
+            attribute
+        );
+        assert!(
+            self.stride() + attribute.size() <= MAX_STRIDE,
+            "Error: the attribute layout can't hold more than {} values",
+            MAX_STRIDE
+        );
+        self.attributes[self.len] = attribute;
+        self.len += 1;
+    }
+
+    /// Return the attributes in the layout.
+    ///
+    fn attributes(&self) -> &[Attribute] {
+        &self.attributes[..self.len]
+    }
+}
+
+impl VertexAttributes {
+    /// Return the layout of each vertex's attributes.
+    ///
+    pub fn layout(&self) -> &AttributeLayout {
+        &self.layout
+    }
+
+    /// Return the number of verticies that have attributes.
+    ///
+    pub fn len(&self) -> usize {
+        match self.layout.stride() {
+            0 => 0,
+            stride => self.data.len() / stride,
+        }
+    }
+
+    /// Return true if no verticies have attributes.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Return the attribute values of a vertex.
+    ///
+    pub fn get(&self, index: usize) -> &[f64] {
+        let stride = self.layout.stride();
+        &self.data[(index * stride)..((index + 1) * stride)]
+    }
+
+    /// Return the attribute values of a vertex mutably.
+    ///
+    pub fn get_mut(&mut self, index: usize) -> &mut [f64] {
+        let stride = self.layout.stride();
+        &mut self.data[(index * stride)..((index + 1) * stride)]
+    }
+
+    /// Set the values of an attribute for every vertex, adding the attribute to the layout if it isn't already part
+    /// of it.
+    ///
+    /// # Panics
+    /// The values aren't given for every vertex that already has attributes.
+    ///
+    pub fn set_attribute(&mut self, attribute: Attribute, values: &[f64]) {
+        let size = attribute.size();
+        let count = values.len() / size;
+        assert!(
+            self.is_empty() || self.len() == count,
+            "Error: {:?} values must be given for every vertex",
+            attribute
+        );
+
+        self.add_attribute(attribute, count);
+        let offset = self.layout.offset(attribute).unwrap();
+        for (index, values) in values.chunks(size).enumerate() {
+            self.get_mut(index)[offset..(offset + size)].copy_from_slice(values);
+        }
+    }
+
+    /// Add an attribute to the end of the layout with its values set to 0, if it isn't already part of it. If there
+    /// are no attribute values yet, the given number of verticies are given them. The values are moved within the
+    /// existing buffer, so nothing is allocated if it has the capacity.
+    ///
+    pub fn add_attribute(&mut self, attribute: Attribute, count: usize) {
+        if self.layout.offset(attribute).is_some() {
+            return;
+        }
+
+        let (stride, size) = (self.layout.stride(), attribute.size());
+        let count = if self.is_empty() { count } else { self.len() };
+
+        // Spread the existing values out from the back, leaving room for the new values after each vertex's.
+        self.data.resize(count * (stride + size), 0.0);
+        for index in (0..count).rev() {
+            let start = index * (stride + size);
+            self.data
+                .copy_within((index * stride)..((index + 1) * stride), start);
+            self.data[(start + stride)..(start + stride + size)].fill(0.0);
+        }
+
+        self.layout.push(attribute);
+    }
+
+    /// Replace the layout and the values of every vertex. The old values are swapped into data, so that its
+    /// allocation can be reused.
+    ///
+    /// # Panics
+    /// The length of data isn't a multiple of the layout's stride.
+    ///
+    pub fn replace(&mut self, layout: AttributeLayout, data: &mut Vec<f64>) {
+        assert!(
+            data.len().is_multiple_of(layout.stride()),
+            "Error: vertex attribute data doesn't match its layout"
+        );
+
+        self.layout = layout;
+        swap(&mut self.data, data);
+    }
+
+    /// Add the attributes of a new vertex linearly interpolated between 2 existing verticies.
+    ///
+    pub fn push_interpolated(&mut self, from: usize, to: usize, t: f64) {
+        let stride = self.layout.stride();
+        for i in 0..stride {
+            let (a, b) = (self.data[(from * stride) + i], self.data[(to * stride) + i]);
+            self.data.push(a + ((b - a) * t));
+        }
+    }
+
+    /// Apply a transformation to the normal of each vertex, if the layout contains normals.
+    ///
+    pub fn transform_normals<F>(&mut self, f: F)
+    where
+        F: Fn(Point<3>) -> Point<3>,
+    {
+        if let Some(offset) = self.layout.offset(Attribute::Normal) {
+            for index in 0..self.len() {
+                let values = &mut self.get_mut(index)[offset..(offset + 3)];
+                let normal = f(Point::new([values[0], values[1], values[2]]));
+                values.copy_from_slice(&normal.0);
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Layouts are equal if they hold the same attributes in the same order.
+///
+impl PartialEq for AttributeLayout {
+    fn eq(&self, other: &Self) -> bool {
+        self.attributes() == other.attributes()
+    }
+}
+
+impl fmt::Debug for AttributeLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.attributes()).finish()
+    }
+}
+
+impl Default for AttributeLayout {
+    fn default() -> Self {
+        AttributeLayout::empty()
+    }
+}
+
+/// Cloning into existing attributes reuses the allocation of their values.
+///
+impl Clone for VertexAttributes {
+    fn clone(&self) -> Self {
+        VertexAttributes {
+            layout: self.layout,
+            data: self.data.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.layout = source.layout;
+        self.data.clone_from(&source.data);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let layout = AttributeLayout::new(&[Attribute::TexCoord, Attribute::Colour]);
+
+        assert_eq!(layout.stride(), 6);
+        assert_eq!(layout.offset(Attribute::TexCoord), Some(0));
+        assert_eq!(layout.offset(Attribute::Colour), Some(2));
+        assert_eq!(layout.offset(Attribute::Normal), None);
+    }
+
+    #[test]
+    fn test_layout_read_write() {
+        let mut layout = AttributeLayout::new(&[Attribute::TexCoord]);
+        layout.push(Attribute::Custom(1));
+        layout.push(Attribute::Custom(0));
+        assert_eq!(layout.stride(), 4);
+
+        let mut values = [0.0; 4];
+        layout.write(&mut values, Attribute::Custom(0), &[5.0]);
+        layout.write(&mut values, Attribute::TexCoord, &[1.0, 2.0]);
+        assert_eq!(values, [1.0, 2.0, 0.0, 5.0]);
+        assert_eq!(layout.read(&values, Attribute::TexCoord), Some([1.0, 2.0]));
+        assert_eq!(layout.read::<1>(&values, Attribute::Custom(0)), Some([5.0]));
+        assert_eq!(layout.read::<3>(&values, Attribute::Normal), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_layout_duplicate() {
+        AttributeLayout::new(&[Attribute::Colour, Attribute::Colour]);
+    }
+
+    #[test]
+    fn test_set_attribute() {
+        let layout = AttributeLayout::new(&[Attribute::TexCoord]);
+        let mut attributes = VertexAttributes::new(layout, vec![0.0, 1.0, 2.0, 3.0]);
+
+        attributes.set_attribute(Attribute::Position, &[4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+        assert_eq!(attributes.layout().stride(), 5);
+        assert_eq!(attributes.get(1), [2.0, 3.0, 7.0, 8.0, 9.0]);
+
+        attributes.set_attribute(Attribute::TexCoord, &[0.5, 0.5, 0.5, 0.5]);
+        assert_eq!(attributes.get(0), [0.5, 0.5, 4.0, 5.0, 6.0]);
+
+        let mut attributes = VertexAttributes::default();
+        attributes.set_attribute(Attribute::Position, &[1.0, 2.0, 3.0]);
+        assert_eq!(attributes.len(), 1);
+    }
+
+    #[test]
+    fn test_push_interpolated() {
+        let layout = AttributeLayout::new(&[Attribute::TexCoord]);
+        let mut attributes = VertexAttributes::new(layout, vec![0.0, 1.0, 1.0, 0.0]);
+
+        attributes.push_interpolated(0, 1, 0.25);
+        assert_eq!(attributes.len(), 3);
+        assert_eq!(attributes.get(2), [0.25, 0.75]);
+    }
+}
@@ -0,0 +1,143 @@
+//! Implementation of the back-face culling stage of the pipeline, which removes polygons based on the order their
+//! verticies appear in on screen.
+//!
+
+use super::{geometry::Dim, Vertex};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Which polygons are removed by the culling stage.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum CullMode {
+    /// Remove polygons facing away from the camera.
+    Back,
+    /// Remove polygons facing towards the camera.
+    Front,
+    /// Keep every polygon.
+    None,
+}
+
+/// The order in which a polygon's verticies appear on screen, with Y increasing up the screen.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// How a mesh's polygons are culled. The front face winding is the order in which the verticies of polygons facing
+/// the camera appear on screen.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Culling {
+    pub mode: CullMode,
+    pub front_face: Winding,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Winding {
+    /// Return the winding of a screen space polygon, or None if it has no area.
+    ///
+    pub fn of(verticies: [&Vertex; 3]) -> Option<Winding> {
+        let [p0, p1, p2] = verticies;
+        let area = ((p1[Dim::X] - p0[Dim::X]) * (p2[Dim::Y] - p0[Dim::Y]))
+            - ((p2[Dim::X] - p0[Dim::X]) * (p1[Dim::Y] - p0[Dim::Y]));
+
+        if area > 0.0 {
+            Some(Winding::CounterClockwise)
+        } else if area < 0.0 {
+            Some(Winding::Clockwise)
+        } else {
+            None
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Culling {
+    /// Return true if a screen space polygon should be removed. Polygons with no area are always removed unless
+    /// culling is disabled.
+    ///
+    pub fn is_culled(&self, verticies: [&Vertex; 3]) -> bool {
+        if self.mode == CullMode::None {
+            return false;
+        }
+
+        match Winding::of(verticies) {
+            Some(winding) => (winding == self.front_face) == (self.mode == CullMode::Front),
+            None => true,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Cull back faces, with front faces wound clockwise. This matches the winding of the mesh loaders.
+///
+impl Default for Culling {
+    fn default() -> Self {
+        Culling {
+            mode: CullMode::Back,
+            front_face: Winding::Clockwise,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_winding() {
+        let (a, b, c) = (
+            Vertex::new([0.0, 0.0, 0.0, 1.0]),
+            Vertex::new([0.0, 1.0, 0.0, 1.0]),
+            Vertex::new([1.0, 0.0, 0.0, 1.0]),
+        );
+
+        assert_eq!(Winding::of([&a, &b, &c]), Some(Winding::Clockwise));
+        assert_eq!(Winding::of([&a, &c, &b]), Some(Winding::CounterClockwise));
+        assert_eq!(Winding::of([&a, &a, &b]), None);
+    }
+
+    #[test]
+    fn test_is_culled() {
+        let (a, b, c) = (
+            Vertex::new([0.0, 0.0, 0.0, 1.0]),
+            Vertex::new([0.0, 1.0, 0.0, 1.0]),
+            Vertex::new([1.0, 0.0, 0.0, 1.0]),
+        );
+        let (clockwise, counter_clockwise) = ([&a, &b, &c], [&a, &c, &b]);
+
+        let mut culling = Culling::default();
+        assert!(!culling.is_culled(clockwise));
+        assert!(culling.is_culled(counter_clockwise));
+
+        culling.mode = CullMode::Front;
+        assert!(culling.is_culled(clockwise));
+        assert!(!culling.is_culled(counter_clockwise));
+
+        culling.front_face = Winding::CounterClockwise;
+        assert!(!culling.is_culled(clockwise));
+        assert!(culling.is_culled(counter_clockwise));
+
+        culling.mode = CullMode::None;
+        assert!(!culling.is_culled(counter_clockwise));
+        assert!(!culling.is_culled([&a, &a, &b]));
+    }
+}
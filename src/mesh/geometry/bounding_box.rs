@@ -4,12 +4,12 @@
 use super::Point;
 
 ////////////////////////////////////////////////////////////////////////////////
-///Types & Traits //////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Type represneting a N dimensional bounding box.
 ///
-#[derive(Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub struct BBox<const D: usize>([(f64, f64); D]);
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -30,7 +30,7 @@ impl<const D: usize> BBox<D> {
 
         bbox.0
             .iter_mut()
-            .zip(p1.into_iter().zip(p2.into_iter()))
+            .zip(p1.into_iter().zip(&p2))
             .for_each(|((min, max), (c1, c2))| {
                 if c1 <= c2 {
                     (*min, *max) = (c1, c2);
@@ -40,6 +40,22 @@ impl<const D: usize> BBox<D> {
             });
         bbox
     }
+
+    /// Return the smallest bounding box containing every one of a list of points, or None if the list is empty.
+    ///
+    pub fn from_points<I>(points: I) -> Option<BBox<D>>
+    where
+        I: IntoIterator<Item = Point<D>>,
+    {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bbox = BBox::new(first, first);
+
+        for point in points {
+            bbox.extend(&point);
+        }
+        Some(bbox)
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -49,12 +65,55 @@ impl<const D: usize> BBox<D> {
 impl<const D: usize> BBox<D> {
     /// Return true if a point lies within a bounding box. Return else otherwise
     ///
+    #[allow(dead_code)]
     pub fn bounds(&self, point: &Point<D>) -> bool {
         point
             .into_iter()
             .zip(self.0.iter())
             .all(|(coord, &(min, max))| (min..=max).contains(&coord))
     }
+
+    /// Return the corner of the box with the smallest coordinates.
+    ///
+    pub fn min(&self) -> Point<D> {
+        Point::new(self.0.map(|(min, _)| min))
+    }
+
+    /// Return the corner of the box with the largest coordinates.
+    ///
+    pub fn max(&self) -> Point<D> {
+        Point::new(self.0.map(|(_, max)| max))
+    }
+
+    /// Grow the box just enough to contain a point.
+    ///
+    pub fn extend(&mut self, point: &Point<D>) {
+        self.0
+            .iter_mut()
+            .zip(point)
+            .for_each(|((min, max), coord)| {
+                *min = min.min(coord);
+                *max = max.max(coord);
+            });
+    }
+}
+
+impl BBox<3> {
+    /// Return the 8 corners of the box.
+    ///
+    pub fn corners(&self) -> [Point<3>; 8] {
+        let [(x0, x1), (y0, y1), (z0, z1)] = self.0;
+        [
+            Point::new([x0, y0, z0]),
+            Point::new([x1, y0, z0]),
+            Point::new([x0, y1, z0]),
+            Point::new([x1, y1, z0]),
+            Point::new([x0, y0, z1]),
+            Point::new([x1, y0, z1]),
+            Point::new([x0, y1, z1]),
+            Point::new([x1, y1, z1]),
+        ]
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -78,4 +137,20 @@ mod tests {
         assert!(bbox.bounds(&point_bound));
         assert!(!bbox.bounds(&point_not_bound));
     }
+
+    #[test]
+    fn test_from_points() {
+        let points = [
+            Point::new([1.0, -2.0, 3.0]),
+            Point::new([-4.0, 5.0, 0.5]),
+            Point::new([2.0, 0.0, -1.0]),
+        ];
+        let bbox = BBox::from_points(points).unwrap();
+
+        assert_eq!(bbox.min(), Point::new([-4.0, -2.0, -1.0]));
+        assert_eq!(bbox.max(), Point::new([2.0, 5.0, 3.0]));
+        assert!(points.iter().all(|point| bbox.bounds(point)));
+        assert!(bbox.corners().iter().all(|corner| bbox.bounds(corner)));
+        assert_eq!(BBox::<3>::from_points([]), None);
+    }
 }
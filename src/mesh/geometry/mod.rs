@@ -8,9 +8,10 @@ mod matrix;
 mod orientation;
 mod orientation_vector;
 mod point;
+mod quaternion;
 mod vector;
 
 pub use self::{
     bounding_box::BBox, dimension::Dim, orientation::Orientation3D,
-    orientation_vector::OrientationVector3D, point::Point, vector::Vector,
+    orientation_vector::OrientationVector3D, point::Point, quaternion::Quaternion, vector::Vector,
 };
@@ -5,12 +5,12 @@ use super::orientation_vector::OrientationVector3D;
 use std::ops::{Add, AddAssign};
 
 ////////////////////////////////////////////////////////////////////////////////
-///Types & Traits //////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Type representing a 3D Orientation.
 ///
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default)]
 pub struct Orientation3D {
     pub x: f64,
     pub y: f64,
@@ -18,18 +18,9 @@ pub struct Orientation3D {
 }
 
 ////////////////////////////////////////////////////////////////////////////////
-/// Implementations ////////////////////////////////////////////////////////////
+// Implementations /////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
-impl Default for Orientation3D {
-    fn default() -> Self {
-        Self {
-            x: Default::default(),
-            y: Default::default(),
-            z: Default::default(),
-        }
-    }
-}
 impl Orientation3D {
     /// Return a new Vector3D object, given it's x, y and z components.
     ///
@@ -47,6 +38,7 @@ impl Orientation3D {
     }
 
     /// Returns a vector from the origin to this point.
+    #[allow(dead_code)]
     pub fn vector(&self) -> OrientationVector3D {
         OrientationVector3D::new(self.x, self.y, self.z)
     }
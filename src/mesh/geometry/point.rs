@@ -110,7 +110,7 @@ impl<const D: usize> Point<D> {
 // Trait Implementations ///////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
-impl<'a, const D: usize> IntoIterator for &'a Point<D> {
+impl<const D: usize> IntoIterator for &Point<D> {
     type Item = f64;
     type IntoIter = std::array::IntoIter<Self::Item, D>;
 
@@ -159,7 +159,7 @@ impl<const D: usize> Add<Vector<D>> for Point<D> {
         let mut pt = self;
 
         pt.iter_mut()
-            .zip(rhs.into_iter())
+            .zip(&rhs)
             .for_each(|(lhs, rhs)| lhs.add_assign(rhs));
         pt
     }
@@ -170,7 +170,7 @@ impl<const D: usize> Add<&Vector<D>> for Point<D> {
     fn add(self, rhs: &Vector<D>) -> Self::Output {
         let mut pt = self;
         pt.iter_mut()
-            .zip(rhs.into_iter())
+            .zip(rhs)
             .for_each(|(lhs, rhs)| lhs.add_assign(rhs));
         pt
     }
@@ -181,7 +181,7 @@ impl<const D: usize> Add<Vector<D>> for &Point<D> {
     fn add(self, rhs: Vector<D>) -> Self::Output {
         let mut pt = *self;
         pt.iter_mut()
-            .zip(rhs.into_iter())
+            .zip(&rhs)
             .for_each(|(lhs, rhs)| lhs.add_assign(rhs));
         pt
     }
@@ -192,7 +192,7 @@ impl<const D: usize> Add<&Vector<D>> for &Point<D> {
     fn add(self, rhs: &Vector<D>) -> Self::Output {
         let mut pt = *self;
         pt.iter_mut()
-            .zip(rhs.into_iter())
+            .zip(rhs)
             .for_each(|(lhs, rhs)| lhs.add_assign(rhs));
         pt
     }
@@ -203,28 +203,28 @@ impl<const D: usize> Add<&Vector<D>> for &Point<D> {
 impl<const D: usize> AddAssign<Vector<D>> for Point<D> {
     fn add_assign(&mut self, rhs: Vector<D>) {
         self.iter_mut()
-            .zip(rhs.into_iter())
+            .zip(&rhs)
             .for_each(|(lhs, rhs)| lhs.add_assign(rhs));
     }
 }
 impl<const D: usize> AddAssign<&Vector<D>> for Point<D> {
     fn add_assign(&mut self, rhs: &Vector<D>) {
         self.iter_mut()
-            .zip(rhs.into_iter())
+            .zip(rhs)
             .for_each(|(lhs, rhs)| lhs.add_assign(rhs));
     }
 }
 impl<const D: usize> AddAssign<Vector<D>> for &mut Point<D> {
     fn add_assign(&mut self, rhs: Vector<D>) {
         self.iter_mut()
-            .zip(rhs.into_iter())
+            .zip(&rhs)
             .for_each(|(lhs, rhs)| lhs.add_assign(rhs));
     }
 }
 impl<const D: usize> AddAssign<&Vector<D>> for &mut Point<D> {
     fn add_assign(&mut self, rhs: &Vector<D>) {
         self.iter_mut()
-            .zip(rhs.into_iter())
+            .zip(rhs)
             .for_each(|(lhs, rhs)| lhs.add_assign(rhs));
     }
 }
@@ -238,7 +238,7 @@ impl<const D: usize> Sub<Point<D>> for Point<D> {
         let mut vector = Vector::new(self.0);
         vector
             .iter_mut()
-            .zip(rhs.into_iter())
+            .zip(&rhs)
             .for_each(|(lhs, rhs)| lhs.sub_assign(rhs));
         vector
     }
@@ -250,7 +250,7 @@ impl<const D: usize> Sub<&Point<D>> for Point<D> {
         let mut vector = Vector::new(self.0);
         vector
             .iter_mut()
-            .zip(rhs.into_iter())
+            .zip(rhs)
             .for_each(|(lhs, rhs)| lhs.sub_assign(rhs));
         vector
     }
@@ -262,7 +262,7 @@ impl<const D: usize> Sub<Point<D>> for &Point<D> {
         let mut vector = Vector::new(self.0);
         vector
             .iter_mut()
-            .zip(rhs.into_iter())
+            .zip(&rhs)
             .for_each(|(lhs, rhs)| lhs.sub_assign(rhs));
         vector
     }
@@ -274,7 +274,7 @@ impl<const D: usize> Sub<&Point<D>> for &Point<D> {
         let mut vector = Vector::new(self.0);
         vector
             .iter_mut()
-            .zip(rhs.into_iter())
+            .zip(rhs)
             .for_each(|(lhs, rhs)| lhs.sub_assign(rhs));
         vector
     }
@@ -0,0 +1,540 @@
+//! Implementation of a quaternion type, used to represent rotations without gimbal lock.
+//!
+//! Rotations follow the same conventions as rotation matrices: vertices are row vectors multiplied on the left, and a
+//! quaternion built from an orientation's angles gives the same matrix as `Matrix4X4::new_rotation`. Multiplying two
+//! quaternions applies the left one first, in the same way as multiplying their matrices.
+//!
+
+use super::{
+    dimension::Dim::{X, Y, Z},
+    orientation::Orientation3D,
+    vector::Vector,
+};
+use crate::mesh::Matrix4X4;
+use std::ops::{Mul, MulAssign};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// The order rotations about each axis are applied in when converting to and from Euler angles. XYZ rotates about x
+/// first and z last, which is the order used by `Matrix4X4::new_rotation`.
+///
+#[allow(dead_code, clippy::upper_case_acronyms)]
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum EulerOrder {
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+/// Type representing a rotation as a quaternion, with a scalar part w and a vector part x, y and z. Only unit
+/// quaternions represent rotations.
+///
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+impl Quaternion {
+    /// Return a new quaternion, given its w, x, y and z components.
+    ///
+    pub fn new<T, U, V, W>(w: T, x: U, y: V, z: W) -> Quaternion
+    where
+        T: Into<f64>,
+        U: Into<f64>,
+        V: Into<f64>,
+        W: Into<f64>,
+    {
+        Quaternion {
+            w: w.into(),
+            x: x.into(),
+            y: y.into(),
+            z: z.into(),
+        }
+    }
+
+    /// Return a quaternion that doesn't rotate anything.
+    ///
+    pub fn identity() -> Quaternion {
+        Quaternion::new(1, 0, 0, 0)
+    }
+
+    /// Return a quaternion rotating by an angle in degrees about an axis. A zero length axis gives no rotation.
+    ///
+    pub fn from_axis_angle(axis: &Vector<3>, angle: f64) -> Quaternion {
+        let axis = axis.normalise();
+        let (sin, cos) = f64::sin_cos(angle.to_radians() / 2.0);
+        Quaternion::new(cos, axis[X] * sin, axis[Y] * sin, axis[Z] * sin)
+    }
+
+    /// Return a quaternion rotating by an orientation's angles in degrees, about each axis in the given order.
+    ///
+    pub fn from_euler(orientation: &Orientation3D, order: EulerOrder) -> Quaternion {
+        let [first, second, third] = order.axes();
+        let angles = [orientation.x, orientation.y, orientation.z];
+        let about = |axis: usize| {
+            let mut direction = Vector::default();
+            direction.0[axis] = 1.0;
+            Quaternion::from_axis_angle(&direction, angles[axis])
+        };
+        about(first) * about(second) * about(third)
+    }
+
+    /// Return a quaternion with the same rotation as the top left 3x3 of a matrix. The matrix must be a rotation,
+    /// without any scaling.
+    ///
+    pub fn from_matrix(matrix: &Matrix4X4) -> Quaternion {
+        let m = &matrix.0;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+
+        // Divide by the largest of the components, to keep the result accurate.
+        let quaternion = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quaternion::new(
+                s / 4.0,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Quaternion::new(
+                (m[2][1] - m[1][2]) / s,
+                s / 4.0,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Quaternion::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / 4.0,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Quaternion::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.0,
+            )
+        };
+        quaternion.normalise()
+    }
+}
+
+impl Default for Quaternion {
+    /// Return a quaternion that doesn't rotate anything.
+    ///
+    fn default() -> Self {
+        Quaternion::identity()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl EulerOrder {
+    /// Return the indices of the axes in the order they're rotated about.
+    ///
+    fn axes(&self) -> [usize; 3] {
+        match self {
+            EulerOrder::XYZ => [0, 1, 2],
+            EulerOrder::XZY => [0, 2, 1],
+            EulerOrder::YXZ => [1, 0, 2],
+            EulerOrder::YZX => [1, 2, 0],
+            EulerOrder::ZXY => [2, 0, 1],
+            EulerOrder::ZYX => [2, 1, 0],
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl Quaternion {
+    /// Return the axis and angle in degrees of the rotation. The angle is between 0 and 360, and the axis is +X when
+    /// there's no rotation.
+    ///
+    pub fn to_axis_angle(self) -> (Vector<3>, f64) {
+        let quaternion = self.normalise();
+        let axis = Vector::new([quaternion.x, quaternion.y, quaternion.z]);
+        let sin = axis.magnitude();
+        if sin < 1e-12 {
+            return (Vector::new([1, 0, 0]), 0.0);
+        }
+        let angle = 2.0 * f64::atan2(sin, quaternion.w);
+        (axis / sin, angle.to_degrees())
+    }
+
+    /// Return the angles in degrees that rotate about each axis in the given order to give the same rotation. The
+    /// middle rotation is between -90 and 90 degrees and the others are between -180 and 180 degrees. When the middle
+    /// rotation is a quarter turn the first and last rotations are about the same axis, so all of it is given to the
+    /// first.
+    ///
+    pub fn to_euler(self, order: EulerOrder) -> Orientation3D {
+        let m = self.matrix().0;
+        let [i, j, k] = order.axes();
+
+        // Orders that don't cycle through the axes, such as XZY, give the same entries with the opposite signs. The
+        // middle angle is found from both its sine and cosine, as the sine alone is inaccurate near a quarter turn.
+        let sign = if (j + 3 - i) % 3 == 1 { 1.0 } else { -1.0 };
+        let cos_j = f64::hypot(m[i][i], m[i][j]);
+        let mut angles = [0.0; 3];
+        angles[j] = f64::atan2(sign * m[i][k], cos_j);
+        if cos_j > 1e-9 {
+            angles[i] = f64::atan2(-sign * m[j][k], m[k][k]);
+            angles[k] = f64::atan2(-sign * m[i][j], m[i][i]);
+        } else {
+            angles[i] = f64::atan2(sign * m[k][j], m[j][j]);
+        }
+
+        Orientation3D::new(
+            angles[0].to_degrees(),
+            angles[1].to_degrees(),
+            angles[2].to_degrees(),
+        )
+    }
+
+    /// Return the rotation matrix for the quaternion, which rotates vertices multiplied on its left.
+    ///
+    pub fn matrix(&self) -> Matrix4X4 {
+        let Quaternion { w, x, y, z } = self.normalise();
+
+        Matrix4X4([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+                0.0,
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+                0.0,
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Return a vector rotated by the quaternion, in the same way as multiplying it by the quaternion's matrix.
+    ///
+    pub fn rotate(&self, vector: &Vector<3>) -> Vector<3> {
+        let quaternion = self.normalise();
+        let axis = Vector::new([quaternion.x, quaternion.y, quaternion.z]);
+        let cross = axis.cross(vector);
+        *vector + &(cross * (-2.0 * quaternion.w)) + &(axis.cross(&cross) * 2.0)
+    }
+
+    /// Return the quaternion with its vector part negated. For unit quaternions this is the opposite rotation.
+    ///
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Return the quaternion that undoes this one's rotation.
+    ///
+    pub fn inverse(&self) -> Quaternion {
+        let squared = self.dot(self);
+        let conjugate = self.conjugate();
+        Quaternion::new(
+            conjugate.w / squared,
+            conjugate.x / squared,
+            conjugate.y / squared,
+            conjugate.z / squared,
+        )
+    }
+
+    /// Return the dot product of two quaternions, which is the cosine of half the angle between their rotations.
+    ///
+    pub fn dot(&self, rhs: &Quaternion) -> f64 {
+        (self.w * rhs.w) + (self.x * rhs.x) + (self.y * rhs.y) + (self.z * rhs.z)
+    }
+
+    /// Return the magnitude of the quaternion.
+    ///
+    pub fn magnitude(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Return the quaternion scaled to a magnitude of one. A zero quaternion gives no rotation.
+    ///
+    pub fn normalise(&self) -> Quaternion {
+        let magnitude = self.magnitude();
+        if magnitude == 0.0 {
+            return Quaternion::identity();
+        }
+        Quaternion::new(
+            self.w / magnitude,
+            self.x / magnitude,
+            self.y / magnitude,
+            self.z / magnitude,
+        )
+    }
+
+    /// Return a rotation part of the way to another, by linearly interpolating the components and normalising the
+    /// result. It's cheaper than slerp, but doesn't turn at a constant rate. It takes the shortest way round.
+    ///
+    pub fn nlerp(&self, other: &Quaternion, t: f64) -> Quaternion {
+        let other = self.nearest(other);
+        Quaternion::new(
+            self.w + (other.w - self.w) * t,
+            self.x + (other.x - self.x) * t,
+            self.y + (other.y - self.y) * t,
+            self.z + (other.z - self.z) * t,
+        )
+        .normalise()
+    }
+
+    /// Return a rotation part of the way to another, turning at a constant rate about a single axis. It takes the
+    /// shortest way round.
+    ///
+    pub fn slerp(&self, other: &Quaternion, t: f64) -> Quaternion {
+        let (from, to) = (self.normalise(), self.nearest(&other.normalise()));
+
+        // Nearly identical rotations would divide by almost zero, and are close enough to interpolate linearly.
+        let cos = from.dot(&to).min(1.0);
+        if cos > 1.0 - 1e-9 {
+            return from.nlerp(&to, t);
+        }
+
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let from_weight = ((1.0 - t) * angle).sin() / sin;
+        let to_weight = (t * angle).sin() / sin;
+        Quaternion::new(
+            from.w * from_weight + to.w * to_weight,
+            from.x * from_weight + to.x * to_weight,
+            from.y * from_weight + to.y * to_weight,
+            from.z * from_weight + to.z * to_weight,
+        )
+        .normalise()
+    }
+
+    /// Return whichever of a quaternion and its negation is closest to this one. Both give the same rotation, but
+    /// interpolating towards the closest one takes the shortest way round.
+    ///
+    fn nearest(&self, other: &Quaternion) -> Quaternion {
+        if self.dot(other) < 0.0 {
+            Quaternion::new(-other.w, -other.x, -other.y, -other.z)
+        } else {
+            *other
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Mul<Quaternion> for Quaternion {
+    type Output = Quaternion;
+
+    /// Return the rotation applying this quaternion's rotation and then the other's.
+    ///
+    fn mul(self, rhs: Quaternion) -> Self::Output {
+        Quaternion {
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        }
+    }
+}
+
+impl MulAssign<Quaternion> for Quaternion {
+    fn mul_assign(&mut self, rhs: Quaternion) {
+        *self = *self * rhs;
+    }
+}
+
+impl From<Orientation3D> for Quaternion {
+    /// Return the quaternion for an orientation, which is rotated about x, then y, then z.
+    ///
+    fn from(orientation: Orientation3D) -> Self {
+        Quaternion::from_euler(&orientation, EulerOrder::XYZ)
+    }
+}
+
+impl From<Quaternion> for Orientation3D {
+    /// Return the orientation for a quaternion, which is rotated about x, then y, then z.
+    ///
+    fn from(quaternion: Quaternion) -> Self {
+        quaternion.to_euler(EulerOrder::XYZ)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::Vertex;
+
+    const ORDERS: [EulerOrder; 6] = [
+        EulerOrder::XYZ,
+        EulerOrder::XZY,
+        EulerOrder::YXZ,
+        EulerOrder::YZX,
+        EulerOrder::ZXY,
+        EulerOrder::ZYX,
+    ];
+
+    fn assert_matrix_near(actual: &Matrix4X4, expected: &Matrix4X4) {
+        for (actual, expected) in actual.0.iter().flatten().zip(expected.0.iter().flatten()) {
+            assert!((actual - expected).abs() < 1e-9, "{:?}", (actual, expected));
+        }
+    }
+
+    fn assert_vector_near(actual: &Vector<3>, expected: [f64; 3]) {
+        for (actual, expected) in actual.into_iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-9, "{:?}", (actual, expected));
+        }
+    }
+
+    /// Return the matrix rotating about each axis in turn, using the same matrices as `Matrix4X4::new_rotation`.
+    ///
+    fn euler_matrix(angles: [f64; 3], order: EulerOrder) -> Matrix4X4 {
+        let about = |axis: usize| {
+            let mut rotation = [0.0; 3];
+            rotation[axis] = angles[axis];
+            Matrix4X4::new_rotation(
+                Orientation3D::new(rotation[0], rotation[1], rotation[2]).vector(),
+            )
+        };
+        let [first, second, third] = order.axes();
+        about(first) * about(second) * about(third)
+    }
+
+    #[test]
+    fn test_matches_rotation_matrix() {
+        let orientation = Orientation3D::new(30, 45, 60);
+        let quaternion = Quaternion::from(orientation);
+        let matrix = Matrix4X4::new_rotation(orientation.vector());
+        assert_matrix_near(&quaternion.matrix(), &matrix);
+        assert_matrix_near(&Quaternion::from_matrix(&matrix).matrix(), &matrix);
+
+        // Rotating a vector matches multiplying it by the matrix.
+        let rotated = (Vertex::new([1.0, 2.0, 3.0, 1.0]) * matrix).demote::<3>();
+        let vector = quaternion.rotate(&Vector::new([1, 2, 3]));
+        assert_vector_near(&vector, rotated.0);
+
+        // Composition applies the left rotation first, as with matrices.
+        let other = Quaternion::from_axis_angle(&Vector::new([1.0, -2.0, 0.5]), 75.0);
+        assert_matrix_near(
+            &(quaternion * other).matrix(),
+            &(quaternion.matrix() * other.matrix()),
+        );
+        assert_vector_near(
+            &(quaternion * other).rotate(&Vector::new([0, 1, 0])),
+            other.rotate(&quaternion.rotate(&Vector::new([0, 1, 0]))).0,
+        );
+        assert_matrix_near(
+            &(quaternion * quaternion.inverse()).matrix(),
+            &Quaternion::identity().matrix(),
+        );
+    }
+
+    #[test]
+    fn test_axis_angle() {
+        // Turning positively about x tilts +Z up, in the same way as an orientation.
+        let quaternion = Quaternion::from_axis_angle(&Vector::new([2, 0, 0]), 90.0);
+        assert_vector_near(&quaternion.rotate(&Vector::new([0, 0, 1])), [0.0, 1.0, 0.0]);
+
+        let (axis, angle) = quaternion.to_axis_angle();
+        assert_vector_near(&axis, [1.0, 0.0, 0.0]);
+        assert!((angle - 90.0).abs() < 1e-9);
+
+        let (axis, angle) = Quaternion::identity().to_axis_angle();
+        assert_vector_near(&axis, [1.0, 0.0, 0.0]);
+        assert_eq!(angle, 0.0);
+    }
+
+    #[test]
+    fn test_euler_orders() {
+        let angles = [
+            [30.0, 45.0, 60.0],
+            [-70.0, 20.0, 85.0],
+            [10.0, -80.0, -45.0],
+        ];
+        for order in ORDERS {
+            for angle in angles {
+                // Each order rotates about the axes in turn, and converting back gives the same angles.
+                let orientation = Orientation3D::new(angle[0], angle[1], angle[2]);
+                let quaternion = Quaternion::from_euler(&orientation, order);
+                assert_matrix_near(&quaternion.matrix(), &euler_matrix(angle, order));
+
+                let euler = quaternion.to_euler(order);
+                for (actual, expected) in [euler.x, euler.y, euler.z].into_iter().zip(angle) {
+                    assert!(
+                        (actual - expected).abs() < 1e-6,
+                        "{:?}",
+                        (order, actual, expected)
+                    );
+                }
+            }
+
+            // A quarter turn about the middle axis locks the other two together, but still gives the same rotation.
+            let mut locked = [25.0, 35.0, 45.0];
+            locked[order.axes()[1]] = -90.0;
+            let quaternion =
+                Quaternion::from_euler(&Orientation3D::new(locked[0], locked[1], locked[2]), order);
+            let euler = quaternion.to_euler(order);
+            assert_matrix_near(
+                &Quaternion::from_euler(&euler, order).matrix(),
+                &quaternion.matrix(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let from = Quaternion::identity();
+        let to = Quaternion::from_axis_angle(&Vector::new([0, 1, 0]), 90.0);
+
+        // Slerp turns at a constant rate about a single axis.
+        for t in [0.0, 0.25, 0.5, 1.0] {
+            let (axis, angle) = from.slerp(&to, t).to_axis_angle();
+            assert!((angle - 90.0 * t).abs() < 1e-9, "{:?}", (t, angle));
+            if t > 0.0 {
+                assert_vector_near(&axis, [0.0, 1.0, 0.0]);
+            }
+        }
+
+        // Nlerp only matches it at the ends and halfway, and both take the shortest way round.
+        let halfway = from.slerp(&to, 0.5);
+        assert!((from.nlerp(&to, 0.5).dot(&halfway) - 1.0).abs() < 1e-9);
+        let (_, angle) = from.nlerp(&to, 0.25).to_axis_angle();
+        assert!((angle - 22.5).abs() > 0.1);
+
+        let negated = Quaternion::new(-to.w, -to.x, -to.y, -to.z);
+        let (_, angle) = from.slerp(&negated, 0.5).to_axis_angle();
+        assert!((angle - 45.0).abs() < 1e-9);
+
+        // Nearly identical rotations don't divide by zero.
+        let close = Quaternion::from_axis_angle(&Vector::new([0, 1, 0]), 1e-7);
+        assert!(from.slerp(&close, 0.5).magnitude().is_finite());
+    }
+}
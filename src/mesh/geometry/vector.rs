@@ -6,7 +6,7 @@ use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign,
 use super::{dimension::Dim, point::Point};
 
 ////////////////////////////////////////////////////////////////////////////////
-///Types & Traits //////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Type representing a N dimensional vector.
@@ -82,12 +82,40 @@ impl<const D: usize> Vector<D> {
         normal_vector
     }
 
+    /// Return the cross product of the first 3 components of 2 vectors.
+    ///
+    pub fn cross(&self, rhs: &Vector<D>) -> Vector<D> {
+        let mut cross_product: Vector<D> = Vector::default();
+        cross_product.0[0] = (self.0[1] * rhs.0[2]) - (self.0[2] * rhs.0[1]);
+        cross_product.0[1] = (self.0[2] * rhs.0[0]) - (self.0[0] * rhs.0[2]);
+        cross_product.0[2] = (self.0[0] * rhs.0[1]) - (self.0[1] * rhs.0[0]);
+        cross_product
+    }
+
     /// Return the magnitude of the vector.
     ///
     pub fn magnitude(&self) -> f64 {
         f64::sqrt(self.into_iter().fold(0.0, |sum, coord| sum + coord.powi(2)))
     }
 
+    /// Return the dot product of this vector and another.
+    ///
+    pub fn dot(&self, rhs: &Vector<D>) -> f64 {
+        self.into_iter().zip(rhs).map(|(lhs, rhs)| lhs * rhs).sum()
+    }
+
+    /// Return a vector with the same direction and a magnitude of 1.
+    /// A zero length vector is returned unchanged.
+    ///
+    pub fn normalise(&self) -> Vector<D> {
+        let magnitude = self.magnitude();
+        if magnitude == 0.0 {
+            *self
+        } else {
+            self / magnitude
+        }
+    }
+
     /// Returns an iterator over a vector's coordinates that allows modifying each value.
     ///
     pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, f64> {
@@ -117,7 +145,7 @@ impl<const D: usize> Vector<D> {
 // Trait Implementations ///////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
-impl<'a, const D: usize> IntoIterator for &'a Vector<D> {
+impl<const D: usize> IntoIterator for &Vector<D> {
     type Item = f64;
     type IntoIter = std::array::IntoIter<Self::Item, D>;
 
@@ -166,7 +194,7 @@ impl<const D: usize> Add<&Vector<D>> for Vector<D> {
         let mut point = self;
         point
             .iter_mut()
-            .zip(rhs.into_iter())
+            .zip(rhs)
             .for_each(|(new_comp, rhs_comp)| *new_comp += rhs_comp);
         point
     }
@@ -177,7 +205,7 @@ impl<const D: usize> Add<&Vector<D>> for Vector<D> {
 impl<const D: usize> AddAssign for Vector<D> {
     fn add_assign(&mut self, rhs: Self) {
         self.iter_mut()
-            .zip(rhs.into_iter())
+            .zip(&rhs)
             .for_each(|(new_comp, rhs_comp)| *new_comp += rhs_comp);
     }
 }
@@ -293,4 +321,30 @@ mod tests {
         point_mul_assign /= scaler;
         assert_eq!(point_mul_assign, Vector::new(coords_scaled));
     }
+
+    #[test]
+    fn test_dot() {
+        let vector1 = Vector::new([1.0, 2.0, 3.0]);
+        let vector2 = Vector::new([4.0, -5.0, 6.0]);
+
+        assert_eq!(vector1.dot(&vector2), 12.0);
+    }
+
+    #[test]
+    fn test_cross() {
+        let vector1 = Vector::new([1, 0, 0]);
+        let vector2 = Vector::new([0, 1, 0]);
+
+        assert_eq!(vector1.cross(&vector2), Vector::new([0, 0, 1]));
+        assert_eq!(vector2.cross(&vector1), Vector::new([0, 0, -1]));
+    }
+
+    #[test]
+    fn test_normalise() {
+        assert_eq!(
+            Vector::new([0, 3, -4]).normalise(),
+            Vector::new([0.0, 0.6, -0.8])
+        );
+        assert_eq!(Vector::new([0, 0, 0]).normalise(), Vector::new([0, 0, 0]));
+    }
 }
@@ -1,19 +1,23 @@
 use std::ops::MulAssign;
 
-use crate::mesh::geometry::OrientationVector3D;
+use crate::mesh::geometry::{
+    Dim::{X, Y, Z},
+    OrientationVector3D, Point, Vector,
+};
 
 ////////////////////////////////////////////////////////////////////////////////
-///Types & Traits //////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Type representing a 4x4 matrix which can be used to represent vertex transformations.
 ///
 #[derive(Copy, Clone)]
 pub struct Matrix4X4(pub [[f64; 4]; 4]);
+#[allow(dead_code)]
 pub struct Transformation([[f64; 4]; 4]);
 
 ////////////////////////////////////////////////////////////////////////////////
-/// Implementations ////////////////////////////////////////////////////////////
+// Implementations /////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
 impl Default for Transformation {
@@ -30,6 +34,72 @@ impl Default for Transformation {
 }
 
 impl Matrix4X4 {
+    /// Construct and return a perspective projection matrix which projects camera space onto NDC space, given the
+    /// vertical field of view in degrees.
+    ///
+    pub fn new_projection(
+        aspect_ratio: f64,
+        near_plane: f64,
+        far_plane: f64,
+        fov: f64,
+    ) -> Matrix4X4 {
+        let y_mul = 1.0 / f64::tan(fov.to_radians() / 2.0);
+        let x_mul = y_mul / aspect_ratio;
+        let z1_mul = far_plane / (far_plane - near_plane);
+        let z2_mul = -(far_plane * near_plane) / (far_plane - near_plane);
+
+        Matrix4X4([
+            [x_mul, 0.0, 0.0, 0.0],
+            [0.0, y_mul, 0.0, 0.0],
+            [0.0, 0.0, z1_mul, 1.0],
+            [0.0, 0.0, z2_mul, 0.0],
+        ])
+    }
+
+    /// Construct and return an orthographic projection matrix which projects the box between the given planes onto
+    /// NDC space. Depths between the near and far planes are mapped from 0 to 1, as with a perspective projection.
+    ///
+    pub fn new_orthographic(
+        left: f64,
+        right: f64,
+        bottom: f64,
+        top: f64,
+        near_plane: f64,
+        far_plane: f64,
+    ) -> Matrix4X4 {
+        let (width, height, depth) = (right - left, top - bottom, far_plane - near_plane);
+
+        Matrix4X4([
+            [2.0 / width, 0.0, 0.0, 0.0],
+            [0.0, 2.0 / height, 0.0, 0.0],
+            [0.0, 0.0, 1.0 / depth, 0.0],
+            [
+                -(right + left) / width,
+                -(top + bottom) / height,
+                -near_plane / depth,
+                1.0,
+            ],
+        ])
+    }
+
+    /// Construct and return a view matrix which moves world space into the space of an eye at a position looking
+    /// towards a target, with the up vector pointing up the screen. The eye looks down its +Z axis with +X to the
+    /// right, in the same way as the camera at the origin of world space.
+    ///
+    pub fn new_look_at(eye: &Point<3>, target: &Point<3>, up: &Vector<3>) -> Matrix4X4 {
+        let forward = eye.vector_to(target).normalise();
+        let right = up.cross(&forward).normalise();
+        let up = forward.cross(&right);
+        let eye = eye.vector_from(&Point::default());
+
+        Matrix4X4([
+            [right[X], up[X], forward[X], 0.0],
+            [right[Y], up[Y], forward[Y], 0.0],
+            [right[Z], up[Z], forward[Z], 0.0],
+            [-right.dot(&eye), -up.dot(&eye), -forward.dot(&eye), 1.0],
+        ])
+    }
+
     /// Construct and return a rotation matrix
     ///
     pub fn new_rotation(rotation: OrientationVector3D) -> Matrix4X4 {
@@ -59,6 +129,7 @@ impl Matrix4X4 {
     }
 }
 
+#[allow(dead_code)]
 impl Transformation {
     /// Add a rotation to the transformation.
     /// 
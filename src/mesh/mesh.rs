@@ -1,7 +1,12 @@
 //! Implementations of a face-vertex mesh data structure and methods construction methods.
 //!
 
-use crate::physics::PhysicalState;
+use crate::{
+    camera::Camera,
+    physics::PhysicalState,
+    projection::Projection,
+    shader::{ProjectionShader, VertexInput, VertexShader},
+};
 
 use super::{
     geometry::{
@@ -9,51 +14,128 @@ use super::{
         Dim::{W, X, Y, Z},
         Point, Vector,
     },
-    {IndexPoly, Matrix4X4, RefPoly, Vertex},
+    {
+        Attribute, AttributeLayout, Culling, IndexPoly, Matrix4X4, RefEdge, RefPoly, Vertex,
+        VertexAttributes,
+    },
+};
+use std::{
+    collections::{HashMap, HashSet},
+    mem::swap,
+    ops::AddAssign,
 };
 
 ////////////////////////////////////////////////////////////////////////////////
-///Types & Traits //////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
+/// The screen depth of the near plane. Screen depths run from 0 at the far plane up to this at the near plane.
+pub const SCREEN_DEPTH: f64 = 1000.0;
+
 /// The mesh consists of a number of verticies and polygons.
 /// Each polygon's points are indexes into the verticies vector.
 /// Each polygon also contains an index into the normal vector to its normal.
-#[derive(Clone)]
+/// Each vertex can also have attributes such as a colour, which are kept in step with the verticies.
 pub struct Mesh {
     verticies: Vec<Vertex>,
+    attributes: VertexAttributes,
     normals: Vec<Vector<3>>,
     polygons: Vec<IndexPoly>,
     visible_polygons: Vec<IndexPoly>,
+    stats: PipelineStats,
+
+    /// Scratch space the vertex shader writes its varyings into. Kept between frames to reuse its allocation.
+    varyings: Vec<f64>,
 
     pub physics: PhysicalState,
+    pub culling: Culling,
+}
+
+/// Counts of the triangles that made it through each stage of the pipeline.
+///
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct PipelineStats {
+    /// Triangles left after clipping, before they're culled.
+    pub triangles: usize,
+    /// Triangles removed by culling.
+    pub culled: usize,
+}
+
+/// How the normals of the polygons around a vertex are weighted when they're averaged into a vertex normal.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum NormalWeighting {
+    /// Weight each polygon by its area, so larger polygons have more influence.
+    #[allow(dead_code)]
+    Area,
+    /// Weight each polygon by the angle of its corner at the vertex, so the result doesn't depend on how a surface is
+    /// split into polygons.
+    Angle,
+}
+
+/// The planes bounding the view frustum in homogeneous clip space.
+///
+#[derive(Clone, Copy)]
+enum ClipPlane {
+    Left,
+    Right,
+    Bottom,
+    Top,
+    Near,
+    Far,
+}
+
+/// The vertex indices of a polygon being clipped. Clipping a triangle against each of the 6 planes adds at most 1
+/// vertex per plane, so they're stored inline rather than allocated.
+///
+struct ClippedPolygon {
+    verticies: [usize; 9],
+    len: usize,
+}
+
+/// The edges of a mesh's visible polygons, as pairs of indices into its verticies. Kept between meshes to reuse its
+/// allocations.
+///
+#[derive(Default)]
+pub struct EdgeList {
+    edges: Vec<[usize; 2]>,
+    seen: HashSet<[usize; 2]>,
 }
 
 pub struct PolyIterator<'a> {
     vertex_list: &'a [Vertex],
+    attributes: &'a VertexAttributes,
     normal_list: &'a [Vector<3>],
     polygon_list: &'a [IndexPoly],
 }
 
 ////////////////////////////////////////////////////////////////////////////////
-/// Implementations ////////////////////////////////////////////////////////////
+// Implementations /////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
 impl Default for Mesh {
     fn default() -> Self {
         let verticies = Vec::new();
+        let attributes = VertexAttributes::default();
         let normals = Vec::new();
         let polygons = Vec::new();
         let visible_polygons = Vec::new();
+        let stats = PipelineStats::default();
+        let varyings = Vec::new();
 
         let physical_state = PhysicalState::new();
+        let culling = Culling::default();
 
         Self {
             verticies,
+            attributes,
             normals,
             polygons,
             visible_polygons,
+            stats,
+            varyings,
             physics: physical_state,
+            culling,
         }
     }
 }
@@ -61,6 +143,7 @@ impl Default for Mesh {
 impl Mesh {
     /// Load a cube into the mesh.
     ///
+    #[allow(dead_code)]
     pub fn load_cube(&mut self, edge_length: f64) {
         let pos = edge_length / 2.0;
         let neg = -edge_length / 2.0;
@@ -91,27 +174,331 @@ impl Mesh {
             self.normals.push(Vector::new([0, 0, 0]));
         }
     }
+
+    /// Load a cube with texture coordinates into an empty mesh.
+    /// Each face has its own verticies so that it can be textured separately. Texture coordinates run from 0 to
+    /// texture_scale across each face, so textures are tiled when it's greater than 1.
+    ///
+    pub fn load_textured_cube(&mut self, edge_length: f64, texture_scale: f64) {
+        let pos = edge_length / 2.0;
+        let neg = -edge_length / 2.0;
+
+        let corners = [
+            [neg, neg, pos],
+            [pos, neg, pos],
+            [neg, neg, neg],
+            [pos, neg, neg],
+            [neg, pos, pos],
+            [pos, pos, pos],
+            [neg, pos, neg],
+            [pos, pos, neg],
+        ];
+
+        // The corners of each face, wound in the same order as the polygons of load_cube.
+        let faces = [
+            [2, 6, 7, 3],
+            [3, 7, 5, 1],
+            [1, 5, 4, 0],
+            [0, 4, 6, 2],
+            [6, 4, 5, 7],
+            [0, 2, 3, 1],
+        ];
+        let tex_coords = [[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+
+        let mut data = Vec::with_capacity(faces.len() * 8);
+        for face in faces {
+            let first = self.verticies.len();
+            for (corner, uv) in face.iter().zip(tex_coords) {
+                let [x, y, z] = corners[*corner];
+                self.verticies.push(Vertex::new([x, y, z, 1.0]));
+                data.extend(uv.map(|coordinate| coordinate * texture_scale));
+            }
+
+            for (second, third) in [(1, 2), (2, 3)] {
+                let normal = self.normals.len();
+                self.polygons
+                    .push(IndexPoly::new(first, first + second, first + third, normal));
+                self.normals.push(Vector::new([0, 0, 0]));
+            }
+        }
+
+        self.set_attributes(VertexAttributes::new(
+            AttributeLayout::new(&[Attribute::TexCoord]),
+            data,
+        ));
+    }
+
+    /// Load a flat rectangle lying in the x-z plane and facing up the y axis into the mesh, such as to use as a floor.
+    ///
+    pub fn load_plane(&mut self, width: f64, depth: f64) {
+        let first = self.verticies.len();
+        let (x, z) = (width / 2.0, depth / 2.0);
+
+        for [x, z] in [[-x, -z], [-x, z], [x, z], [x, -z]] {
+            self.verticies.push(Vertex::new([x, 0.0, z, 1.0]));
+        }
+        for (second, third) in [(1, 2), (2, 3)] {
+            let normal = self.normals.len();
+            self.polygons
+                .push(IndexPoly::new(first, first + second, first + third, normal));
+            self.normals.push(Vector::new([0, 0, 0]));
+        }
+    }
+
+    /// Load a sphere made of rings of verticies into the mesh. Rings is the number of bands from pole to pole and
+    /// segments is the number of verticies around each ring.
+    ///
+    pub fn load_sphere(&mut self, radius: f64, rings: usize, segments: usize) {
+        let first = self.verticies.len();
+        let north = first;
+        let south = first + 1;
+        self.verticies.push(Vertex::new([0.0, radius, 0.0, 1.0]));
+        self.verticies.push(Vertex::new([0.0, -radius, 0.0, 1.0]));
+
+        // Each ring is below the last, starting from the north pole.
+        for ring in 1..rings {
+            let polar = std::f64::consts::PI * ring as f64 / rings as f64;
+            for segment in 0..segments {
+                let azimuth = 2.0 * std::f64::consts::PI * segment as f64 / segments as f64;
+                self.verticies.push(Vertex::new([
+                    radius * polar.sin() * azimuth.cos(),
+                    radius * polar.cos(),
+                    radius * polar.sin() * azimuth.sin(),
+                    1.0,
+                ]));
+            }
+        }
+
+        let index = |ring: usize, segment: usize| {
+            first + 2 + ((ring - 1) * segments) + (segment % segments)
+        };
+        let add_polygon = |mesh: &mut Mesh, p1, p2, p3| {
+            let normal = mesh.normals.len();
+            mesh.polygons.push(IndexPoly::new(p1, p2, p3, normal));
+            mesh.normals.push(Vector::new([0, 0, 0]));
+        };
+
+        for segment in 0..segments {
+            add_polygon(self, north, index(1, segment + 1), index(1, segment));
+            for ring in 1..(rings - 1) {
+                let (top, bottom) = ((ring, segment), (ring + 1, segment));
+                add_polygon(
+                    self,
+                    index(top.0, top.1),
+                    index(bottom.0, bottom.1 + 1),
+                    index(bottom.0, bottom.1),
+                );
+                add_polygon(
+                    self,
+                    index(top.0, top.1),
+                    index(top.0, top.1 + 1),
+                    index(bottom.0, bottom.1 + 1),
+                );
+            }
+            add_polygon(
+                self,
+                south,
+                index(rings - 1, segment),
+                index(rings - 1, segment + 1),
+            );
+        }
+    }
+
+    /// Compute a normal for each vertex by averaging the normals of the polygons around it, and store them as vertex
+    /// attributes. Polygons sharing a vertex position are only averaged together if their normals are within the
+    /// crease angle, given in degrees, of each other. Verticies on a crease are split so that each side of it can
+    /// have its own normal.
+    ///
+    pub fn compute_vertex_normals(&mut self, crease_angle: f64, weighting: NormalWeighting) {
+        // Find each polygon's normal and the weight of each of its corners.
+        let mut face_normals = Vec::with_capacity(self.polygons.len());
+        let mut corner_weights = Vec::with_capacity(self.polygons.len());
+        for indexpoly in self.polygons.iter() {
+            let points = indexpoly.verticies.map(|index| self.verticies[index]);
+            let edge = |from: usize, to: usize| -> Vector<3> {
+                points[to].vector_from(&points[from]).demote()
+            };
+
+            let cross_product = edge(0, 1).cross(&edge(0, 2));
+            face_normals.push(cross_product.normalise());
+            corner_weights.push(match weighting {
+                NormalWeighting::Area => [cross_product.magnitude() / 2.0; 3],
+                NormalWeighting::Angle => [(0, 1, 2), (1, 2, 0), (2, 0, 1)].map(|(at, a, b)| {
+                    let (a, b) = (edge(at, a).normalise(), edge(at, b).normalise());
+                    a.dot(&b).clamp(-1.0, 1.0).acos()
+                }),
+            });
+        }
+
+        // Group the polygon corners sharing each vertex position.
+        let mut corners: HashMap<[u64; 3], Vec<(usize, usize)>> = HashMap::new();
+        for (polygon, indexpoly) in self.polygons.iter().enumerate() {
+            for (corner, &index) in indexpoly.verticies.iter().enumerate() {
+                let vertex = &self.verticies[index];
+                let key = [vertex[X], vertex[Y], vertex[Z]].map(f64::to_bits);
+                corners.entry(key).or_default().push((polygon, corner));
+            }
+        }
+
+        // Average the normals of the polygons within the crease angle of each corner's polygon.
+        let min_cos = crease_angle.to_radians().cos();
+        let mut corner_normals = vec![[Vector::<3>::default(); 3]; self.polygons.len()];
+        for group in corners.values() {
+            for &(polygon, corner) in group {
+                let mut normal = Vector::default();
+                for &(other, other_corner) in group {
+                    if face_normals[polygon].dot(&face_normals[other]) >= min_cos - 1e-9 {
+                        normal += face_normals[other] * corner_weights[other][other_corner];
+                    }
+                }
+                corner_normals[polygon][corner] = normal.normalise();
+            }
+        }
+
+        // Give each vertex its corners' normal, splitting off a copy of the vertex for each different normal.
+        let mut vertex_normals: Vec<Option<Vector<3>>> = vec![None; self.verticies.len()];
+        let mut splits: Vec<(usize, usize)> = Vec::new();
+        for (polygon, normals) in corner_normals.iter().enumerate() {
+            for (corner, &normal) in normals.iter().enumerate() {
+                let index = self.polygons[polygon].verticies[corner];
+                let same = |other: &Option<Vector<3>>| {
+                    other.is_some_and(|other| (other.dot(&normal) - 1.0).abs() < 1e-9)
+                };
+
+                match vertex_normals[index] {
+                    None => vertex_normals[index] = Some(normal),
+                    ref existing if same(existing) => {}
+                    _ => {
+                        let split = splits
+                            .iter()
+                            .find(|&&(original, split)| {
+                                original == index && same(&vertex_normals[split])
+                            })
+                            .map(|&(_, split)| split);
+
+                        self.polygons[polygon].verticies[corner] = match split {
+                            Some(split) => split,
+                            None => {
+                                self.verticies.push(self.verticies[index]);
+                                if !self.attributes.is_empty() {
+                                    self.attributes.push_interpolated(index, index, 0.0);
+                                }
+                                vertex_normals.push(Some(normal));
+                                splits.push((index, self.verticies.len() - 1));
+                                self.verticies.len() - 1
+                            }
+                        };
+                    }
+                }
+            }
+        }
+
+        let normals: Vec<f64> = vertex_normals
+            .iter()
+            .flat_map(|normal| normal.unwrap_or_default().0)
+            .collect();
+        self.attributes.set_attribute(Attribute::Normal, &normals);
+    }
+
+    /// Set the attributes of each vertex in the mesh.
+    ///
+    /// # Panics
+    /// The attributes aren't given for every vertex.
+    ///
+    pub fn set_attributes(&mut self, attributes: VertexAttributes) {
+        assert_eq!(
+            attributes.len(),
+            self.verticies.len(),
+            "Error: vertex attributes must be given for every vertex"
+        );
+        self.attributes = attributes;
+    }
 }
 
 impl Mesh {
-    /// Create a new mesh that has been run through the pipeline and contains only the polygons that should be drawn.
+    /// Create a new mesh that has been run through the pipeline, viewed from a camera through a projection, and
+    /// contains only the polygons that should be drawn.
     ///
-    pub fn run_pipeline(&self, project_mat: &Matrix4X4, window_size: [f64; 2]) -> Mesh {
-        let mut processed_mesh = self.clone();
+    #[allow(dead_code)]
+    pub fn run_pipeline(
+        &self,
+        camera: &Camera,
+        projection: &Projection,
+        window_size: [f64; 2],
+    ) -> Mesh {
+        let shader = ProjectionShader::with_projection(projection);
+        self.run_pipeline_with_shader(&shader, camera, window_size)
+    }
+
+    /// Run the mesh through the pipeline, replacing the contents of another mesh with the result. The other mesh's
+    /// buffers are reused, so once it has been through the pipeline with this mesh a few times it won't allocate.
+    ///
+    pub fn run_pipeline_into(
+        &self,
+        camera: &Camera,
+        projection: &Projection,
+        window_size: [f64; 2],
+        processed_mesh: &mut Mesh,
+    ) {
+        let shader = ProjectionShader::with_projection(projection);
+        self.run_pipeline_with_shader_into(&shader, camera, window_size, processed_mesh);
+    }
+
+    /// Create a new mesh that has been run through the pipeline, viewed from a camera and using a vertex shader to
+    /// move it from view space to clip space, and contains only the polygons that should be drawn. The attributes of
+    /// the new mesh are the varyings written by the shader.
+    ///
+    #[allow(dead_code)]
+    pub fn run_pipeline_with_shader<S>(
+        &self,
+        shader: &S,
+        camera: &Camera,
+        window_size: [f64; 2],
+    ) -> Mesh
+    where
+        S: VertexShader,
+    {
+        let mut processed_mesh = Mesh::default();
+        self.run_pipeline_with_shader_into(shader, camera, window_size, &mut processed_mesh);
+        processed_mesh
+    }
+
+    /// Run the mesh through the pipeline using a vertex shader, replacing the contents of another mesh with the
+    /// result and reusing its buffers.
+    ///
+    pub fn run_pipeline_with_shader_into<S>(
+        &self,
+        shader: &S,
+        camera: &Camera,
+        window_size: [f64; 2],
+        processed_mesh: &mut Mesh,
+    ) where
+        S: VertexShader,
+    {
+        processed_mesh.clone_from(self);
         processed_mesh.apply_transformations();
+        processed_mesh.store_positions();
         processed_mesh.find_normals();
-        processed_mesh.project_to_ndc(project_mat);
-        processed_mesh.polygons_in_view();
-        processed_mesh.project_to_screen(window_size[0], window_size[1]);
+        processed_mesh.apply_view_transformation(&camera.view_matrix());
+        processed_mesh.store_view_positions();
+        processed_mesh.apply_vertex_shader(shader);
+        processed_mesh.clip_polygons();
+        processed_mesh.project_to_ndc();
+        processed_mesh.project_to_screen(window_size[0], window_size[1], shader.reversed_z());
+        processed_mesh.cull_polygons();
+    }
 
-        processed_mesh
+    /// Return the counts of triangles from the last time the mesh was run through the pipeline.
+    ///
+    pub fn stats(&self) -> PipelineStats {
+        self.stats
     }
 
     /// Apply position and rotation transformations.
     ///
     pub fn apply_transformations(&mut self) {
         // Find the rotation matrix
-        let rotation_matrix = Matrix4X4::new_rotation(self.physics.orientation.vector());
+        let rotation_matrix = self.physics.rotation_matrix();
 
         let position_vector = self.physics.position.vector_from(&Point::new([0, 0, 0]));
 
@@ -120,6 +507,45 @@ impl Mesh {
             *vertex = *vertex * rotation_matrix;
             vertex.translate(&position_vector.promote());
         }
+
+        // Normals are directions so are only rotated.
+        self.attributes.transform_normals(|normal| {
+            let rotated = Vertex::new([normal[X], normal[Y], normal[Z], 0.0]) * rotation_matrix;
+            Point::new([rotated[X], rotated[Y], rotated[Z]])
+        });
+    }
+
+    /// Return the bounding box of the mesh's verticies in world space: the box around its model space bounding box
+    /// once it has been rotated and positioned. Returns None if the mesh has no verticies.
+    ///
+    pub fn world_bounds(&self) -> Option<BBox<3>> {
+        let bounds = BBox::from_points(self.verticies.iter().map(|vertex| vertex.demote()))?;
+
+        let rotation_matrix = self.physics.rotation_matrix();
+        let position_vector = self.physics.position.vector_from(&Point::new([0, 0, 0]));
+
+        BBox::from_points(bounds.corners().map(|corner| {
+            let mut vertex = Vertex::new([corner[X], corner[Y], corner[Z], 1.0]) * rotation_matrix;
+            vertex.translate(&position_vector.promote());
+            vertex.demote()
+        }))
+    }
+
+    /// Store each vertex's world space position as an attribute, so it can be interpolated for lighting.
+    ///
+    pub fn store_positions(&mut self) {
+        self.attributes
+            .add_attribute(Attribute::Position, self.verticies.len());
+
+        let offset = self
+            .attributes
+            .layout()
+            .offset(Attribute::Position)
+            .unwrap();
+        for (index, vertex) in self.verticies.iter().enumerate() {
+            self.attributes.get_mut(index)[offset..(offset + 3)]
+                .copy_from_slice(&[vertex[X], vertex[Y], vertex[Z]]);
+        }
     }
 
     /// Find the normal unit vectors of each polygon in the mesh.
@@ -139,42 +565,263 @@ impl Mesh {
         }
     }
 
-    /// Project the mesh from camera space to NDC space by applying a projection matrix to each vertex
+    /// Move each vertex from world space into view space. Positions and normals stored as attributes stay in world
+    /// space, where the lights are.
     ///
-    pub fn project_to_ndc(&mut self, projection_matrix: &Matrix4X4) {
+    pub fn apply_view_transformation(&mut self, view_matrix: &Matrix4X4) {
         for vertex in self.verticies.iter_mut() {
-            *vertex = *vertex * (*projection_matrix);
-            *vertex /= vertex[W];
+            *vertex = *vertex * *view_matrix;
         }
     }
 
-    /// Copy any polygons that are at least partially within ndc space, into the visible polygon list.
+    /// Store each vertex's view space position as an attribute, so it can be interpolated for fog. The length of the
+    /// interpolated position is a fragment's distance from the camera, where the clip space w is only its depth in
+    /// front of the camera, and only for perspective projections.
     ///
-    pub fn polygons_in_view(&mut self) {
-        let ndc_space = BBox::new(Point::new([-1, -1, -1, -1]), Point::new([1, 1, 1, 1]));
+    pub fn store_view_positions(&mut self) {
+        self.attributes
+            .add_attribute(Attribute::ViewPosition, self.verticies.len());
 
-        for indexpoly in self.polygons.iter() {
-            let vert1_bound = ndc_space.bounds(&self.verticies[indexpoly.verticies[0]]);
-            let vert2_bound = ndc_space.bounds(&self.verticies[indexpoly.verticies[1]]);
-            let vert3_bound = ndc_space.bounds(&self.verticies[indexpoly.verticies[2]]);
+        let offset = self
+            .attributes
+            .layout()
+            .offset(Attribute::ViewPosition)
+            .unwrap();
+        for (index, vertex) in self.verticies.iter().enumerate() {
+            self.attributes.get_mut(index)[offset..(offset + 3)]
+                .copy_from_slice(&[vertex[X], vertex[Y], vertex[Z]]);
+        }
+    }
+
+    /// Run a vertex shader on each vertex, replacing the verticies with their clip space positions and the vertex
+    /// attributes with the shader's varyings.
+    ///
+    pub fn apply_vertex_shader<S>(&mut self, shader: &S)
+    where
+        S: VertexShader,
+    {
+        let layout = shader.varyings(self.attributes.layout());
+        let stride = layout.stride();
+        self.varyings.clear();
+        self.varyings.resize(self.verticies.len() * stride, 0.0);
+
+        for index in 0..self.verticies.len() {
+            let input = VertexInput {
+                vertex: &self.verticies[index],
+                attributes: self.attributes.get(index),
+                layout: self.attributes.layout(),
+            };
+            let output = &mut self.varyings[(index * stride)..((index + 1) * stride)];
+            self.verticies[index] = shader.shade(&input, output);
+        }
+
+        self.attributes.replace(layout, &mut self.varyings);
+    }
+
+    /// Clip each polygon against the view frustum and copy the results into the visible polygon list.
+    /// Polygons crossing the frustum are clipped with the Sutherland-Hodgman algorithm and split back into triangles.
+    /// Any verticies created by clipping are added to the end of the vertex list.
+    ///
+    pub fn clip_polygons(&mut self) {
+        let mut polygon = ClippedPolygon::new();
+        let mut clipped = ClippedPolygon::new();
+
+        for index in 0..self.polygons.len() {
+            let indexpoly = self.polygons[index];
+
+            polygon.clear();
+            indexpoly
+                .verticies
+                .iter()
+                .for_each(|&vertex| polygon.push(vertex));
+
+            for plane in ClipPlane::ALL {
+                clipped.clear();
 
-            if vert1_bound || vert2_bound || vert3_bound {
-                self.visible_polygons.push(indexpoly.to_owned());
+                for (i, &current) in polygon.as_slice().iter().enumerate() {
+                    let next = polygon.as_slice()[(i + 1) % polygon.len()];
+                    let current_dist = plane.distance(&self.verticies[current]);
+                    let next_dist = plane.distance(&self.verticies[next]);
+
+                    if current_dist >= 0.0 {
+                        clipped.push(current);
+                    }
+                    if (current_dist >= 0.0) != (next_dist >= 0.0) {
+                        let t = current_dist / (current_dist - next_dist);
+                        clipped.push(self.interpolate_vertex(current, next, t));
+                    }
+                }
+
+                swap(&mut polygon, &mut clipped);
+                if polygon.len() < 3 {
+                    break;
+                }
+            }
+
+            // Split the clipped polygon back into a fan of triangles.
+            let polygon = polygon.as_slice();
+            for i in 1..polygon.len().saturating_sub(1) {
+                self.visible_polygons.push(IndexPoly::new(
+                    polygon[0],
+                    polygon[i],
+                    polygon[i + 1],
+                    indexpoly.normal,
+                ));
             }
         }
     }
 
-    /// Project the mesh from NDC space to screen space
+    /// Add a new vertex linearly interpolated between 2 existing verticies and return its index.
+    /// The vertex's attributes are interpolated in the same way.
     ///
-    pub fn project_to_screen(&mut self, screen_width: f64, screen_height: f64) {
+    fn interpolate_vertex(&mut self, from: usize, to: usize, t: f64) -> usize {
+        let vector = self.verticies[to].vector_from(&self.verticies[from]) * t;
+        self.verticies.push(self.verticies[from] + vector);
+        self.attributes.push_interpolated(from, to, t);
+        self.verticies.len() - 1
+    }
+
+    /// Project the mesh from clip space to NDC space by performing the perspective divide on each vertex.
+    /// W is replaced with 1/w so that attributes can later be interpolated with perspective correction.
+    ///
+    pub fn project_to_ndc(&mut self) {
+        for vertex in self.verticies.iter_mut() {
+            let w = vertex[W];
+            *vertex /= w;
+            vertex[W] = 1.0 / w;
+        }
+    }
+
+    /// Project the mesh from NDC space to screen space. Reversed NDC depths have the near plane at 1 rather than 0.
+    ///
+    pub fn project_to_screen(&mut self, screen_width: f64, screen_height: f64, reversed_z: bool) {
         let screen_width_mul = screen_width / 2.0;
         let screen_height_mul = screen_height / 2.0;
-        let screen_depth_mul = 1000.0;
 
         for vertex in self.verticies.iter_mut() {
             vertex[X] = (vertex[X] + 1.0) * screen_width_mul;
             vertex[Y] = (vertex[Y] + 1.0) * screen_height_mul;
-            vertex[Z] = screen_depth_mul - (vertex[Z] * screen_depth_mul);
+            vertex[Z] = screen_depth(vertex[Z], reversed_z);
+        }
+    }
+
+    /// Sort the visible polygons from back to front by the average screen depth of their verticies, so that they can
+    /// be drawn with alpha blending.
+    ///
+    pub fn sort_back_to_front(&mut self) {
+        let verticies = &self.verticies;
+        let depth = |indexpoly: &IndexPoly| {
+            indexpoly
+                .verticies
+                .iter()
+                .map(|&index| verticies[index][Z])
+                .sum::<f64>()
+        };
+
+        // Larger screen depths are closer to the camera.
+        self.visible_polygons
+            .sort_unstable_by(|a, b| depth(a).total_cmp(&depth(b)));
+    }
+
+    /// Sort the visible polygons from front to back by the average screen depth of their verticies, so that hidden
+    /// fragments of opaque meshes fail the depth test before they're shaded.
+    ///
+    pub fn sort_front_to_back(&mut self) {
+        self.sort_back_to_front();
+        self.visible_polygons.reverse();
+    }
+
+    /// Keep only the visible polygons whose screen space verticies the closure returns true for, and return how many
+    /// were removed.
+    ///
+    pub fn retain_visible_polygons<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut([&Vertex; 3]) -> bool,
+    {
+        let (before, verticies) = (self.visible_polygons.len(), &self.verticies);
+        self.visible_polygons
+            .retain(|indexpoly| f(indexpoly.verticies.map(|index| &verticies[index])));
+        before - self.visible_polygons.len()
+    }
+}
+
+impl Mesh {
+    /// Remove the visible polygons that should be culled given their winding on screen, and record how many were
+    /// removed.
+    ///
+    pub fn cull_polygons(&mut self) {
+        let triangles = self.visible_polygons.len();
+        let (verticies, culling) = (&self.verticies, self.culling);
+
+        self.visible_polygons.retain(|indexpoly| {
+            let [v1, v2, v3] = indexpoly.verticies;
+            !culling.is_culled([&verticies[v1], &verticies[v2], &verticies[v3]])
+        });
+
+        self.stats = PipelineStats {
+            triangles,
+            culled: triangles - self.visible_polygons.len(),
+        };
+    }
+}
+
+impl ClippedPolygon {
+    /// Return a new polygon without any verticies.
+    ///
+    fn new() -> ClippedPolygon {
+        ClippedPolygon {
+            verticies: [0; 9],
+            len: 0,
+        }
+    }
+
+    /// Add a vertex index to the end of the polygon.
+    ///
+    fn push(&mut self, vertex: usize) {
+        self.verticies[self.len] = vertex;
+        self.len += 1;
+    }
+
+    /// Remove every vertex from the polygon.
+    ///
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Return the number of verticies in the polygon.
+    ///
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return the polygon's vertex indices.
+    ///
+    fn as_slice(&self) -> &[usize] {
+        &self.verticies[..self.len]
+    }
+}
+
+impl ClipPlane {
+    const ALL: [ClipPlane; 6] = [
+        ClipPlane::Left,
+        ClipPlane::Right,
+        ClipPlane::Bottom,
+        ClipPlane::Top,
+        ClipPlane::Near,
+        ClipPlane::Far,
+    ];
+
+    /// Return the signed distance of a clip space vertex from the plane. Verticies inside the frustum have a
+    /// positive distance.
+    ///
+    fn distance(&self, vertex: &Vertex) -> f64 {
+        match self {
+            ClipPlane::Left => vertex[W] + vertex[X],
+            ClipPlane::Right => vertex[W] - vertex[X],
+            ClipPlane::Bottom => vertex[W] + vertex[Y],
+            ClipPlane::Top => vertex[W] - vertex[Y],
+            ClipPlane::Near => vertex[Z],
+            ClipPlane::Far => vertex[W] - vertex[Z],
         }
     }
 }
@@ -183,13 +830,15 @@ impl Mesh {
 impl Mesh {
     /// Iterate over all polygons immutably.
     ///
-    pub fn iter_all_polygons(&self) -> PolyIterator {
+    pub fn iter_all_polygons(&self) -> PolyIterator<'_> {
         let vertex_list = self.verticies.as_slice();
+        let attributes = &self.attributes;
         let normal_list = self.normals.as_slice();
         let polygon_list = self.polygons.as_slice();
 
         PolyIterator {
             vertex_list,
+            attributes,
             normal_list,
             polygon_list,
         }
@@ -197,18 +846,137 @@ impl Mesh {
 
     /// Iterate over only visible polygons immutably.
     ///
-    pub fn iter_visible_polygons(&self) -> PolyIterator {
+    pub fn iter_visible_polygons(&self) -> PolyIterator<'_> {
         let vertex_list = self.verticies.as_slice();
+        let attributes = &self.attributes;
         let normal_list = self.normals.as_slice();
         let polygon_list = self.visible_polygons.as_slice();
 
         PolyIterator {
             vertex_list,
+            attributes,
             normal_list,
             polygon_list,
         }
     }
+
+    /// Return one of the visible polygons, given its position in the order they're iterated over.
+    ///
+    pub fn visible_polygon(&self, index: usize) -> RefPoly<'_> {
+        let mut iterator = PolyIterator {
+            vertex_list: self.verticies.as_slice(),
+            attributes: &self.attributes,
+            normal_list: self.normals.as_slice(),
+            polygon_list: &self.visible_polygons[index..=index],
+        };
+        iterator.next().unwrap()
+    }
+
+    /// Return the average screen depth of the visible polygons' verticies, or 0 if there are none.
+    ///
+    pub fn screen_depth(&self) -> f64 {
+        let sum: f64 = self
+            .visible_polygons
+            .iter()
+            .flat_map(|indexpoly| indexpoly.verticies.iter())
+            .map(|&index| self.verticies[index][Z])
+            .sum();
+        sum / (self.visible_polygons.len() * 3).max(1) as f64
+    }
+
+    /// Fill an edge list with the edges of the visible polygons, replacing anything already in it. Edges shared
+    /// between polygons are only listed once.
+    ///
+    pub fn find_visible_edges(&self, edges: &mut EdgeList) {
+        edges.clear();
+        for indexpoly in self.visible_polygons.iter() {
+            let [v1, v2, v3] = indexpoly.verticies;
+            for (from, to) in [(v1, v2), (v2, v3), (v3, v1)] {
+                if edges.seen.insert([from.min(to), from.max(to)]) {
+                    edges.edges.push([from, to]);
+                }
+            }
+        }
+    }
+
+    /// Iterate over the edges in a list filled from this mesh.
+    ///
+    pub fn iter_edges<'a>(
+        &'a self,
+        edges: &'a EdgeList,
+    ) -> impl Iterator<Item = RefEdge<'a>> + Clone {
+        edges.edges.iter().map(|&[from, to]| {
+            let edge = RefEdge::new(&self.verticies[from], &self.verticies[to]);
+            if !self.attributes.is_empty() {
+                edge.with_attributes(
+                    [self.attributes.get(from), self.attributes.get(to)],
+                    self.attributes.layout(),
+                )
+            } else {
+                edge
+            }
+        })
+    }
 }
+
+impl EdgeList {
+    /// Remove every edge from the list, keeping its allocations.
+    ///
+    pub fn clear(&mut self) {
+        self.edges.clear();
+        self.seen.clear();
+    }
+
+    /// Return the number of edges in the list.
+    ///
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Return whether the list has no edges.
+    ///
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+}
+/// Cloning into an existing mesh reuses its buffers. The vertex shader's scratch space isn't cloned.
+///
+impl Clone for Mesh {
+    fn clone(&self) -> Self {
+        Mesh {
+            verticies: self.verticies.clone(),
+            attributes: self.attributes.clone(),
+            normals: self.normals.clone(),
+            polygons: self.polygons.clone(),
+            visible_polygons: self.visible_polygons.clone(),
+            stats: self.stats,
+            varyings: Vec::new(),
+            physics: self.physics.clone(),
+            culling: self.culling,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.verticies.clone_from(&source.verticies);
+        self.attributes.clone_from(&source.attributes);
+        self.normals.clone_from(&source.normals);
+        self.polygons.clone_from(&source.polygons);
+        self.visible_polygons.clone_from(&source.visible_polygons);
+        self.stats = source.stats;
+        self.physics.clone_from(&source.physics);
+        self.culling = source.culling;
+    }
+}
+
+impl AddAssign for PipelineStats {
+    fn add_assign(&mut self, other: PipelineStats) {
+        self.triangles += other.triangles;
+        self.culled += other.culled;
+    }
+}
+
 impl<'a> Iterator for PolyIterator<'a> {
     type Item = RefPoly<'a>;
 
@@ -226,15 +994,342 @@ impl<'a> Iterator for PolyIterator<'a> {
                 self.polygon_list = remaining_list;
 
                 // Construct a polygon of references from the index polygon and vertex list.
-                RefPoly::new(
-                    &self.vertex_list[index_poly.verticies[0]],
-                    &self.vertex_list[index_poly.verticies[1]],
-                    &self.vertex_list[index_poly.verticies[2]],
+                let [v1, v2, v3] = index_poly.verticies;
+                let polygon = RefPoly::new(
+                    &self.vertex_list[v1],
+                    &self.vertex_list[v2],
+                    &self.vertex_list[v3],
                     &self.normal_list[index_poly.normal],
-                )
+                );
+
+                if !self.attributes.is_empty() {
+                    polygon.with_attributes(
+                        [
+                            self.attributes.get(v1),
+                            self.attributes.get(v2),
+                            self.attributes.get(v3),
+                        ],
+                        self.attributes.layout(),
+                    )
+                } else {
+                    polygon
+                }
             };
 
             Some(ref_polygon)
         }
     }
 }
+
+/// Return the screen depth of an NDC depth. Reversed NDC depths have the near plane at 1 rather than 0, and are
+/// scaled without being subtracted from the near plane so that they keep their precision far from the camera.
+///
+pub fn screen_depth(ndc_depth: f64, reversed_z: bool) -> f64 {
+    if reversed_z {
+        ndc_depth * SCREEN_DEPTH
+    } else {
+        SCREEN_DEPTH - (ndc_depth * SCREEN_DEPTH)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{geometry::Orientation3D, CullMode};
+
+    #[test]
+    fn test_clip_polygons() {
+        let mut mesh = Mesh::default();
+        mesh.verticies.push(Vertex::new([0.0, 0.0, 0.5, 1.0]));
+        mesh.verticies.push(Vertex::new([0.0, 4.0, 0.5, 1.0]));
+        mesh.verticies.push(Vertex::new([0.5, 0.0, -1.0, 1.0]));
+        mesh.normals.push(Vector::new([0, 0, 0]));
+        mesh.polygons.push(IndexPoly::new(0, 1, 2, 0));
+
+        mesh.clip_polygons();
+
+        // Clipped against the top and near planes, leaving a quad split into 2 triangles.
+        assert_eq!(mesh.visible_polygons.len(), 2);
+        for polygon in mesh.iter_visible_polygons() {
+            for vertex in polygon.verticies {
+                assert!(ClipPlane::ALL
+                    .iter()
+                    .all(|plane| plane.distance(vertex) >= -1e-9));
+            }
+        }
+    }
+
+    #[test]
+    fn test_clip_polygons_outside() {
+        let mut mesh = Mesh::default();
+        mesh.verticies.push(Vertex::new([2.0, 0.0, 0.5, 1.0]));
+        mesh.verticies.push(Vertex::new([3.0, 1.0, 0.5, 1.0]));
+        mesh.verticies.push(Vertex::new([3.0, 0.0, 0.5, 1.0]));
+        mesh.normals.push(Vector::new([0, 0, 0]));
+        mesh.polygons.push(IndexPoly::new(0, 1, 2, 0));
+
+        mesh.clip_polygons();
+        assert!(mesh.visible_polygons.is_empty());
+    }
+
+    #[test]
+    fn test_clip_polygons_attributes() {
+        let mut mesh = Mesh::default();
+        mesh.verticies.push(Vertex::new([0.0, 0.0, 0.5, 1.0]));
+        mesh.verticies.push(Vertex::new([0.0, 0.5, 0.5, 1.0]));
+        mesh.verticies.push(Vertex::new([2.0, 0.0, 0.5, 1.0]));
+        mesh.normals.push(Vector::new([0, 0, 0]));
+        mesh.polygons.push(IndexPoly::new(0, 1, 2, 0));
+
+        let layout = AttributeLayout::new(&[Attribute::TexCoord]);
+        mesh.set_attributes(VertexAttributes::new(
+            layout,
+            vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0],
+        ));
+
+        mesh.clip_polygons();
+
+        // Clipping against the right plane adds verticies part way along the edges to the third vertex.
+        assert!(mesh.verticies.len() > 3);
+        assert_eq!(mesh.attributes.len(), mesh.verticies.len());
+        for polygon in mesh.iter_visible_polygons() {
+            for (vertex, attributes) in polygon.verticies.iter().zip(polygon.attributes) {
+                // The texture coordinates are a linear function of position across the polygon.
+                let expected = [vertex[X] / 2.0, vertex[Y] * 2.0];
+                assert!((attributes[0] - expected[0]).abs() < 1e-9);
+                assert!((attributes[1] - expected[1]).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cull_polygons() {
+        // Half of a closed cube faces away from the camera.
+        let mut mesh = Mesh::default();
+        mesh.load_cube(100.0);
+        mesh.physics.position = Point::new([0, 0, 400]);
+        mesh.physics.set_euler_angles(Orientation3D::new(30, 45, 0));
+        let camera = Camera::default();
+        let projection = Projection::perspective(45.0, 4.0 / 3.0, 100.0, 1000.0);
+
+        let culled = mesh.run_pipeline(&camera, &projection, [160.0, 120.0]);
+        assert_eq!(
+            culled.stats(),
+            PipelineStats {
+                triangles: 12,
+                culled: 6
+            }
+        );
+        for polygon in culled.iter_visible_polygons() {
+            assert!(polygon.normal[Z] < 0.0);
+        }
+
+        mesh.culling.mode = CullMode::Front;
+        let culled = mesh.run_pipeline(&camera, &projection, [160.0, 120.0]);
+        assert_eq!(culled.stats().culled, 6);
+        for polygon in culled.iter_visible_polygons() {
+            assert!(polygon.normal[Z] > 0.0);
+        }
+
+        mesh.culling.mode = CullMode::None;
+        let culled = mesh.run_pipeline(&camera, &projection, [160.0, 120.0]);
+        assert_eq!(culled.stats().culled, 0);
+        assert_eq!(culled.iter_visible_polygons().count(), 12);
+    }
+
+    #[test]
+    fn test_visible_edges() {
+        // A cube has 12 edges and 6 face diagonals, with front facing half showing all but 3 of the edges.
+        let mut mesh = Mesh::default();
+        mesh.load_cube(100.0);
+        mesh.physics.position = Point::new([0, 0, 400]);
+        mesh.physics.set_euler_angles(Orientation3D::new(30, 45, 0));
+        mesh.culling.mode = CullMode::None;
+        let camera = Camera::default();
+        let projection = Projection::perspective(45.0, 4.0 / 3.0, 100.0, 1000.0);
+
+        let mut edges = EdgeList::default();
+        let mesh_pipe = mesh.run_pipeline(&camera, &projection, [160.0, 120.0]);
+        mesh_pipe.find_visible_edges(&mut edges);
+        assert_eq!(edges.len(), 18);
+        assert_eq!(mesh_pipe.iter_edges(&edges).count(), 18);
+
+        // Filling the list again replaces its edges.
+        mesh.culling.mode = CullMode::Back;
+        let mesh_pipe = mesh.run_pipeline(&camera, &projection, [160.0, 120.0]);
+        mesh_pipe.find_visible_edges(&mut edges);
+        assert_eq!(edges.len(), 12);
+    }
+
+    #[test]
+    fn test_sort_back_to_front() {
+        let mut mesh = Mesh::default();
+        mesh.load_cube(100.0);
+        mesh.physics.position = Point::new([0, 0, 400]);
+        mesh.physics.set_euler_angles(Orientation3D::new(30, 45, 0));
+        mesh.culling.mode = CullMode::None;
+        let camera = Camera::default();
+        let projection = Projection::perspective(45.0, 4.0 / 3.0, 100.0, 1000.0);
+
+        let mut mesh_pipe = mesh.run_pipeline(&camera, &projection, [160.0, 120.0]);
+        let average_depth = mesh_pipe.screen_depth();
+        mesh_pipe.sort_back_to_front();
+
+        // Each polygon is at least as close as the one before it.
+        let depths: Vec<f64> = mesh_pipe
+            .iter_visible_polygons()
+            .map(|polygon| polygon.verticies.iter().map(|vertex| vertex[Z]).sum())
+            .collect();
+        assert!(depths.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(depths[0] / 3.0 < average_depth && average_depth < depths[11] / 3.0);
+
+        mesh_pipe.sort_front_to_back();
+        let reversed: Vec<f64> = mesh_pipe
+            .iter_visible_polygons()
+            .map(|polygon| polygon.verticies.iter().map(|vertex| vertex[Z]).sum())
+            .collect();
+        assert!(reversed.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    #[test]
+    fn test_camera_view() {
+        let mut mesh = Mesh::default();
+        mesh.load_cube(100.0);
+        mesh.physics.position = Point::new([0, 0, 400]);
+        mesh.physics.set_euler_angles(Orientation3D::new(30, 45, 0));
+        let projection = Projection::perspective(45.0, 4.0 / 3.0, 100.0, 1000.0);
+        let expected = mesh.run_pipeline(&Camera::default(), &projection, [160.0, 120.0]);
+
+        // Moving the camera and the mesh together leaves the mesh in the same place on screen.
+        let mut moved = mesh.clone();
+        moved.physics.position = Point::new([100, 50, 900]);
+        let camera = Camera::look_at(
+            &Point::new([100, 50, 500]),
+            &Point::new([100, 50, 900]),
+            &Vector::new([0, 1, 0]),
+        );
+        let moved = moved.run_pipeline(&camera, &projection, [160.0, 120.0]);
+        assert_eq!(moved.verticies.len(), expected.verticies.len());
+        for (actual, expected) in moved.verticies.iter().zip(&expected.verticies) {
+            assert!(actual.vector_from(expected).magnitude() < 1e-9);
+        }
+
+        // Lighting still happens in world space, so the stored positions move with the mesh.
+        let offset = moved
+            .attributes
+            .layout()
+            .offset(Attribute::Position)
+            .unwrap();
+        assert!(
+            (moved.attributes.get(0)[offset + 2] - expected.attributes.get(0)[offset + 2] - 500.0)
+                .abs()
+                < 1e-9
+        );
+
+        // Turning the camera to look at a mesh off to the side brings it into the middle of the screen.
+        mesh.physics.position = Point::new([400, 0, 0]);
+        let camera = Camera::look_at(
+            &Point::new([0, 0, 0]),
+            &Point::new([400, 0, 0]),
+            &Vector::new([0, 1, 0]),
+        );
+        let turned = mesh.run_pipeline(&camera, &projection, [160.0, 120.0]);
+        assert_eq!(turned.stats().triangles, 12);
+        let verticies = &turned.verticies[..8];
+        let centre = verticies.iter().fold([0.0; 2], |centre, vertex| {
+            [centre[0] + (vertex[X] / 8.0), centre[1] + (vertex[Y] / 8.0)]
+        });
+        assert!((centre[0] - 80.0).abs() < 5.0 && (centre[1] - 60.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_world_bounds() {
+        let mut mesh = Mesh::default();
+        assert_eq!(mesh.world_bounds(), None);
+
+        mesh.load_cube(100.0);
+        mesh.physics.position = Point::new([10, 0, 400]);
+        let bounds = mesh.world_bounds().unwrap();
+        assert_eq!(bounds.min(), Point::new([-40, -50, 350]));
+        assert_eq!(bounds.max(), Point::new([60, 50, 450]));
+
+        // Turning the cube 45 degrees about the y axis widens its box in x and z by a factor of root 2.
+        mesh.physics.set_euler_angles(Orientation3D::new(0, 45, 0));
+        let bounds = mesh.world_bounds().unwrap();
+        let half_diagonal = 50.0 * 2.0_f64.sqrt();
+        assert!((bounds.max()[X] - (10.0 + half_diagonal)).abs() < 1e-9);
+        assert!((bounds.min()[Z] - (400.0 - half_diagonal)).abs() < 1e-9);
+        assert!((bounds.max()[Y] - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_vertex_normals_crease() {
+        // Every corner of a cube is a crease below 90 degrees, so each vertex is split for its 3 faces.
+        let mut mesh = Mesh::default();
+        mesh.load_cube(2.0);
+        mesh.compute_vertex_normals(80.0, NormalWeighting::Angle);
+        assert_eq!(mesh.verticies.len(), 24);
+        assert_eq!(mesh.attributes.len(), 24);
+
+        for polygon in mesh.iter_all_polygons() {
+            let normal = Vector::<3>::new(polygon.attributes[0].try_into().unwrap());
+            for attributes in polygon.attributes {
+                assert_eq!(attributes, normal.0);
+            }
+            assert!(
+                normal
+                    .0
+                    .iter()
+                    .filter(|&&component| component.abs() == 1.0)
+                    .count()
+                    == 1
+            );
+        }
+
+        // Above 90 degrees the faces are averaged. Weighting by angle gives each face an equal share whichever way it's
+        // split into triangles, so the normals point away from the cube's centre.
+        let mut mesh = Mesh::default();
+        mesh.load_cube(2.0);
+        mesh.compute_vertex_normals(100.0, NormalWeighting::Angle);
+        assert_eq!(mesh.verticies.len(), 8);
+        for (index, vertex) in mesh.verticies.iter().enumerate() {
+            let normal = mesh.attributes.get(index);
+            for (coordinate, component) in vertex.0.iter().zip(normal) {
+                assert!((component - (coordinate / f64::sqrt(3.0))).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_plane_faces_up() {
+        let mut mesh = Mesh::default();
+        mesh.load_plane(20.0, 10.0);
+        mesh.find_normals();
+
+        assert_eq!(mesh.polygons.len(), 2);
+        for polygon in mesh.iter_all_polygons() {
+            assert!(polygon.normal.dot(&Vector::new([0, 1, 0])) > 0.999);
+        }
+        let bounds = mesh.world_bounds().unwrap();
+        assert_eq!(bounds.min(), Point::new([-10, 0, -5]));
+        assert_eq!(bounds.max(), Point::new([10, 0, 5]));
+    }
+
+    #[test]
+    fn test_sphere_normals_face_outwards() {
+        let mut mesh = Mesh::default();
+        mesh.load_sphere(10.0, 6, 8);
+        mesh.find_normals();
+
+        assert_eq!(mesh.verticies.len(), 2 + (5 * 8));
+        assert_eq!(mesh.polygons.len(), 2 * 8 * 5);
+        for polygon in mesh.iter_all_polygons() {
+            let centre = polygon.verticies[0].vector_from(&Vertex::new([0.0, 0.0, 0.0, 1.0]));
+            assert!(polygon.normal.dot(&centre.demote()) > 0.0);
+        }
+    }
+}
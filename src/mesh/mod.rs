@@ -1,19 +1,26 @@
 //! Implementation of primitive geometric data types. E.g. 3D point, 3D vector, etc.
 //!
 
+mod attribute;
+mod culling;
 mod matrix;
 mod polygon;
 mod vertex;
+#[allow(clippy::module_inception)]
 mod mesh;
 // mod static_mesh;
 // mod dynamic_mesh;
 
 pub mod geometry;
+#[allow(unused_imports)]
+pub use self::attribute::AttributeLayout;
 pub use self::{
+    attribute::{Attribute, VertexAttributes, MAX_STRIDE},
+    culling::{CullMode, Culling, Winding},
     matrix::Matrix4X4,
-    polygon::{IndexPoly, Polygon, RefPoly},
+    polygon::{IndexPoly, RefEdge, RefPoly},
     vertex::Vertex,
-    mesh::Mesh,
+    mesh::{screen_depth, EdgeList, Mesh, NormalWeighting, PipelineStats, SCREEN_DEPTH},
     // static_mesh::StaticMesh,
     // dynamic_mesh::DynamicMesh,
 };
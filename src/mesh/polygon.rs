@@ -1,4 +1,7 @@
-use super::{geometry::Vector, Vertex};
+use super::{attribute::AttributeLayout, geometry::Vector, Vertex};
+
+/// Layout used by polygons without any vertex attributes.
+static NO_ATTRIBUTES: AttributeLayout = AttributeLayout::empty();
 
 ///
 /// Polygon
@@ -21,5 +24,80 @@ impl<T, U> Polygon<T, U> {
 /// Polygon where verticies are indexes to lists.
 pub type IndexPoly = Polygon<usize, usize>;
 
-/// Polygons where all members are references.
-pub type RefPoly<'a> = Polygon<&'a Vertex, &'a Vector<3>>;
+///
+/// Polygons where all members are references. Each vertex's attributes are stored according to the layout.
+///
+#[derive(Copy, Clone)]
+pub struct RefPoly<'a> {
+    pub verticies: [&'a Vertex; 3],
+    pub attributes: [&'a [f64]; 3],
+    pub layout: &'a AttributeLayout,
+
+    pub normal: &'a Vector<3>,
+}
+impl<'a> RefPoly<'a> {
+    /// Return a new polygon without any vertex attributes.
+    ///
+    pub fn new(
+        p1: &'a Vertex,
+        p2: &'a Vertex,
+        p3: &'a Vertex,
+        normal: &'a Vector<3>,
+    ) -> RefPoly<'a> {
+        RefPoly {
+            verticies: [p1, p2, p3],
+            attributes: [&[]; 3],
+            layout: &NO_ATTRIBUTES,
+            normal,
+        }
+    }
+
+    /// Return the polygon with the given vertex attributes.
+    ///
+    pub fn with_attributes(
+        self,
+        attributes: [&'a [f64]; 3],
+        layout: &'a AttributeLayout,
+    ) -> RefPoly<'a> {
+        RefPoly {
+            attributes,
+            layout,
+            ..self
+        }
+    }
+}
+
+///
+/// Edges where all members are references. Each vertex's attributes are stored according to the layout.
+///
+#[derive(Copy, Clone)]
+pub struct RefEdge<'a> {
+    pub verticies: [&'a Vertex; 2],
+    pub attributes: [&'a [f64]; 2],
+    pub layout: &'a AttributeLayout,
+}
+impl<'a> RefEdge<'a> {
+    /// Return a new edge without any vertex attributes.
+    ///
+    pub fn new(from: &'a Vertex, to: &'a Vertex) -> RefEdge<'a> {
+        RefEdge {
+            verticies: [from, to],
+            attributes: [&[]; 2],
+            layout: &NO_ATTRIBUTES,
+        }
+    }
+
+    /// Return the edge with the given vertex attributes.
+    ///
+    pub fn with_attributes(
+        self,
+        attributes: [&'a [f64]; 2],
+        layout: &'a AttributeLayout,
+    ) -> RefEdge<'a> {
+        RefEdge {
+            attributes,
+            layout,
+            ..self
+        }
+    }
+}
@@ -6,13 +6,13 @@ use super::{
 use std::ops::Mul;
 
 ////////////////////////////////////////////////////////////////////////////////
-///Types & Traits //////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
 pub type Vertex = Point<4>;
 
 ////////////////////////////////////////////////////////////////////////////////
-/// Implementations ////////////////////////////////////////////////////////////
+// Implementations /////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
 
 impl Mul<Matrix4X4> for Vertex {
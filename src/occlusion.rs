@@ -0,0 +1,539 @@
+//! Implementation of occlusion culling against a hierarchical depth pyramid.
+//!
+//! The first level of the pyramid holds the furthest depth of each pixel of a render target, and each level above it
+//! holds the furthest depth of each 2x2 block of texels in the level below, up to a single texel covering the whole
+//! target. A screen space box is tested against the level where it covers at most 2x2 texels. If the closest depth in
+//! the box fails the depth test against each of them, everything inside the box is hidden and doesn't need drawing.
+//!
+
+use crate::{
+    camera::Camera,
+    framebuffer::{DepthTest, RenderTarget},
+    mesh::{
+        geometry::{
+            BBox,
+            Dim::{W, X, Y, Z},
+            Point,
+        },
+        screen_depth, Mesh, Vertex,
+    },
+    projection::Projection,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// How far in front of the pyramid a box's closest depth is moved before it's tested. Depths interpolated across a
+/// polygon can be rounded slightly in front of its verticies, and the polygon shouldn't be hidden by its own depths.
+const DEPTH_BIAS: f64 = 1e-6;
+
+/// A hierarchy of successively smaller copies of a render target's depth buffer, where each texel holds the furthest
+/// depth of the pixels it covers.
+///
+pub struct DepthPyramid {
+    /// The levels of the pyramid, starting with the full size level.
+    levels: Vec<DepthLevel>,
+    /// The depth test of the target the pyramid was built from.
+    depth_test: DepthTest,
+}
+
+/// A level of a depth pyramid. Texels are stored as rows, starting with the bottom row.
+///
+#[derive(Default)]
+struct DepthLevel {
+    width: u32,
+    height: u32,
+    depths: Vec<f64>,
+}
+
+/// Counts of the work skipped by occlusion culling.
+///
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct OcclusionStats {
+    /// Meshes tested against the pyramid.
+    pub objects_tested: usize,
+    /// Meshes found to be hidden, which weren't drawn.
+    pub objects_occluded: usize,
+    /// Triangles tested against the pyramid.
+    pub triangles_tested: usize,
+    /// Triangles found to be hidden, which weren't rasterized.
+    pub triangles_occluded: usize,
+}
+
+/// Tests meshes and their triangles against a depth pyramid and counts how many are hidden.
+///
+#[derive(Default)]
+pub struct OcclusionCuller {
+    stats: OcclusionStats,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl DepthPyramid {
+    /// Return a new empty pyramid, which doesn't hide anything until it has been built.
+    ///
+    pub fn new() -> DepthPyramid {
+        DepthPyramid {
+            levels: Vec::new(),
+            depth_test: DepthTest::Greater,
+        }
+    }
+}
+
+impl Default for DepthPyramid {
+    fn default() -> Self {
+        DepthPyramid::new()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl DepthPyramid {
+    /// Build the pyramid from the depth buffer of a render target. Anti-aliased pixels use the depth of their
+    /// furthest sample. The levels' buffers are reused, so rebuilding a pyramid for a target of the same size doesn't
+    /// allocate.
+    ///
+    pub fn build<T: RenderTarget>(&mut self, target: &T) {
+        let (width, height) = (target.width(), target.height());
+        self.depth_test = target.depth_test();
+
+        let mut levels = 0;
+        let (mut level_width, mut level_height) = (width, height);
+        if width > 0 && height > 0 {
+            levels = 1;
+            while level_width > 1 || level_height > 1 {
+                level_width = level_width.div_ceil(2);
+                level_height = level_height.div_ceil(2);
+                levels += 1;
+            }
+        }
+        self.levels.resize_with(levels, DepthLevel::default);
+        if levels == 0 {
+            return;
+        }
+
+        let base = &mut self.levels[0];
+        base.resize(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                base.set(x, y, target.get_depth(x, y));
+            }
+        }
+
+        for level in 1..levels {
+            let (below, above) = self.levels.split_at_mut(level);
+            let (below, above) = (&below[level - 1], &mut above[0]);
+            above.resize(below.width.div_ceil(2), below.height.div_ceil(2));
+
+            for y in 0..above.height {
+                for x in 0..above.width {
+                    // Texels on the edge of a level with an odd size only cover 1 row or column below.
+                    let mut furthest = f64::MAX;
+                    for (column, row) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                        let (column, row) = ((x * 2) + column, (y * 2) + row);
+                        if column < below.width && row < below.height {
+                            furthest = furthest.min(below.get(column, row));
+                        }
+                    }
+                    above.set(x, y, furthest);
+                }
+            }
+        }
+    }
+
+    /// Return true if everything inside a screen space box, given as x, y and screen depth, would fail the depth test
+    /// against the depths the pyramid was built from. Boxes that lie outside the target aren't hidden.
+    ///
+    pub fn is_occluded(&self, bounds: &BBox<3>) -> bool {
+        let base = match self.levels.first() {
+            Some(base) => base,
+            None => return false,
+        };
+        let (min, max) = (bounds.min(), bounds.max());
+
+        // Find the pixels the box overlaps, including those it only touches the edge of.
+        let (first_x, last_x) = (min[X].floor(), max[X].floor());
+        let (first_y, last_y) = (min[Y].floor(), max[Y].floor());
+        if last_x < 0.0
+            || last_y < 0.0
+            || first_x >= base.width as f64
+            || first_y >= base.height as f64
+        {
+            return false;
+        }
+        let (first_x, last_x) = (first_x.max(0.0) as u32, (last_x as u32).min(base.width - 1));
+        let (first_y, last_y) = (
+            first_y.max(0.0) as u32,
+            (last_y as u32).min(base.height - 1),
+        );
+
+        // Use the lowest level where the pixels fall within 2x2 texels. The top level's single texel covers them all.
+        let mut level = 0;
+        while (last_x >> level) - (first_x >> level) > 1
+            || (last_y >> level) - (first_y >> level) > 1
+        {
+            level += 1;
+        }
+        let texels = &self.levels[level];
+
+        let depth = max[Z] + DEPTH_BIAS;
+        for y in (first_y >> level)..=(last_y >> level) {
+            for x in (first_x >> level)..=(last_x >> level) {
+                if self.depth_test.passes(depth, texels.get(x, y)) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl DepthLevel {
+    /// Set the size of the level. Its depths are left as they are.
+    ///
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.depths.resize((width * height) as usize, 0.0);
+    }
+
+    /// Return the depth of a texel.
+    ///
+    fn get(&self, x: u32, y: u32) -> f64 {
+        self.depths[((y * self.width) + x) as usize]
+    }
+
+    /// Set the depth of a texel.
+    ///
+    fn set(&mut self, x: u32, y: u32, depth: f64) {
+        self.depths[((y * self.width) + x) as usize] = depth;
+    }
+}
+
+impl OcclusionCuller {
+    /// Return true if a mesh's world space bounding box is hidden behind the depths in a pyramid once it's viewed from
+    /// a camera and projected onto the screen. Meshes whose box crosses the near plane are never hidden.
+    ///
+    pub fn is_occluded(
+        &mut self,
+        pyramid: &DepthPyramid,
+        mesh: &Mesh,
+        camera: &Camera,
+        projection: &Projection,
+        window_size: [f64; 2],
+    ) -> bool {
+        let occluded = mesh
+            .world_bounds()
+            .and_then(|bounds| screen_bounds(&bounds, camera, projection, window_size))
+            .is_some_and(|bounds| pyramid.is_occluded(&bounds));
+
+        self.stats.objects_tested += 1;
+        if occluded {
+            self.stats.objects_occluded += 1;
+        }
+        occluded
+    }
+
+    /// Remove the visible polygons of a mesh that has been run through the pipeline whose screen space bounding box
+    /// is hidden behind the depths in a pyramid, so that they aren't rasterized.
+    ///
+    pub fn cull_triangles(&mut self, pyramid: &DepthPyramid, mesh: &mut Mesh) {
+        let triangles = mesh.iter_visible_polygons().count();
+        let occluded = mesh.retain_visible_polygons(|verticies| {
+            let bounds = BBox::from_points(verticies.map(|vertex| vertex.demote())).unwrap();
+            !pyramid.is_occluded(&bounds)
+        });
+
+        self.stats.triangles_tested += triangles;
+        self.stats.triangles_occluded += occluded;
+    }
+
+    /// Return the counts of the work skipped since the stats were last reset.
+    ///
+    pub fn stats(&self) -> OcclusionStats {
+        self.stats
+    }
+
+    /// Reset the counts of skipped work, such as at the start of each frame.
+    ///
+    pub fn reset_stats(&mut self) {
+        self.stats = OcclusionStats::default();
+    }
+}
+
+/// Return the screen space bounding box, as x, y and screen depth, of a world space bounding box viewed from a camera
+/// and projected onto a screen of the given size. Returns None if the box crosses the near plane, as its projection
+/// isn't bounded.
+///
+fn screen_bounds(
+    bounds: &BBox<3>,
+    camera: &Camera,
+    projection: &Projection,
+    window_size: [f64; 2],
+) -> Option<BBox<3>> {
+    let reversed_z = projection.reversed_z();
+    let view_projection = camera.view_matrix() * *projection.matrix();
+    let corners = bounds
+        .corners()
+        .map(|corner| Vertex::new([corner[X], corner[Y], corner[Z], 1.0]) * view_projection);
+    let in_front = |corner: &Vertex| match reversed_z {
+        false => corner[Z] >= 0.0,
+        true => corner[Z] <= corner[W],
+    };
+    if corners
+        .iter()
+        .any(|corner| corner[W] <= 0.0 || !in_front(corner))
+    {
+        return None;
+    }
+
+    BBox::from_points(corners.map(|corner| {
+        let w = corner[W];
+        Point::new([
+            ((corner[X] / w) + 1.0) * (window_size[0] / 2.0),
+            ((corner[Y] / w) + 1.0) * (window_size[1] / 2.0),
+            screen_depth(corner[Z] / w, reversed_z),
+        ])
+    }))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        framebuffer::{DrawType, FrameBuffer, LineStyle},
+        lighting::Material,
+        mesh::SCREEN_DEPTH,
+        rasterizer::RasterMethod,
+        shader::{LitShader, ShadingMode},
+    };
+
+    const WIDTH: u32 = 160;
+    const HEIGHT: u32 = 120;
+
+    /// Return a screen space box from its opposite corners.
+    ///
+    fn screen_box(min: [f64; 3], max: [f64; 3]) -> BBox<3> {
+        BBox::new(Point::new(min), Point::new(max))
+    }
+
+    /// Return a frame buffer with its left half filled at a depth of 500.
+    ///
+    fn half_filled() -> FrameBuffer {
+        let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+        for y in 0..HEIGHT {
+            for x in 0..(WIDTH / 2) {
+                buffer.set_depth(x, y, 500.0);
+            }
+        }
+        buffer
+    }
+
+    /// Return a large wall close to the camera and a small cube hidden behind it, and the camera and projection
+    /// they're drawn with.
+    ///
+    fn wall_and_cube() -> (Mesh, Mesh, Camera, Projection) {
+        let mut wall = Mesh::default();
+        wall.load_cube(400.0);
+        wall.physics.position = Point::new([0, 0, 500]);
+
+        let mut cube = Mesh::default();
+        cube.load_cube(50.0);
+        cube.physics.position = Point::new([0, 0, 900]);
+
+        let projection = Projection::perspective(45.0, WIDTH as f64 / HEIGHT as f64, 100.0, 1000.0);
+        (wall, cube, Camera::default(), projection)
+    }
+
+    #[test]
+    fn test_pyramid_levels() {
+        let mut pyramid = DepthPyramid::new();
+        pyramid.build(&half_filled());
+
+        // Each level halves the size of the one below, rounding up, until a single texel is left.
+        let sizes: Vec<(u32, u32)> = pyramid
+            .levels
+            .iter()
+            .map(|level| (level.width, level.height))
+            .collect();
+        assert_eq!(
+            sizes,
+            [
+                (160, 120),
+                (80, 60),
+                (40, 30),
+                (20, 15),
+                (10, 8),
+                (5, 4),
+                (3, 2),
+                (2, 1),
+                (1, 1)
+            ]
+        );
+
+        // Texels hold the furthest depth they cover, so the texels straddling the filled half are empty.
+        assert_eq!(pyramid.levels[3].get(3, 7), 500.0);
+        assert_eq!(pyramid.levels[5].get(2, 0), 0.0);
+        assert_eq!(pyramid.levels[8].get(0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_pyramid_occlusion() {
+        let mut pyramid = DepthPyramid::new();
+        assert!(!pyramid.is_occluded(&screen_box([10.0, 10.0, 100.0], [20.0, 20.0, 200.0])));
+        pyramid.build(&half_filled());
+
+        // Boxes behind the filled half are hidden, whatever their size.
+        assert!(pyramid.is_occluded(&screen_box([10.0, 10.0, 100.0], [20.0, 20.0, 200.0])));
+        assert!(pyramid.is_occluded(&screen_box([0.0, 0.0, 100.0], [63.5, 63.5, 499.0])));
+
+        // Boxes are tested against at most 2x2 texels, so a box can be left visible by the empty pixels its texels
+        // cover even if it's hidden itself.
+        assert!(!pyramid.is_occluded(&screen_box([0.0, 0.0, 100.0], [79.5, 119.0, 499.0])));
+
+        // Boxes with any part in front of the filled depth, or overlapping the empty half, aren't.
+        assert!(!pyramid.is_occluded(&screen_box([10.0, 10.0, 100.0], [20.0, 20.0, 600.0])));
+        assert!(!pyramid.is_occluded(&screen_box([70.0, 10.0, 100.0], [80.0, 20.0, 200.0])));
+
+        // Boxes partly off screen are only tested where they're on it, and those completely off it aren't hidden.
+        assert!(pyramid.is_occluded(&screen_box([-50.0, -50.0, 100.0], [30.0, 30.0, 200.0])));
+        assert!(!pyramid.is_occluded(&screen_box([-50.0, 10.0, 100.0], [-10.0, 20.0, 200.0])));
+    }
+
+    #[test]
+    fn test_pyramid_depth_test() {
+        // Level with the stored depth only passes when the target tests with GreaterEqual.
+        let mut buffer = half_filled();
+        let level = screen_box(
+            [10.0, 10.0, 100.0],
+            [20.0, 20.0, 500.0 - (DEPTH_BIAS * 2.0)],
+        );
+
+        let mut pyramid = DepthPyramid::new();
+        pyramid.build(&buffer);
+        assert!(pyramid.is_occluded(&level));
+
+        buffer.set_depth_test(DepthTest::GreaterEqual);
+        pyramid.build(&buffer);
+        assert!(!pyramid.is_occluded(&screen_box([10.0, 10.0, 100.0], [20.0, 20.0, 500.0])));
+        assert!(pyramid.is_occluded(&screen_box([10.0, 10.0, 100.0], [20.0, 20.0, 499.0])));
+    }
+
+    #[test]
+    fn test_occlusion_culler() {
+        let (wall, cube, camera, projection) = wall_and_cube();
+        let size = [WIDTH as f64, HEIGHT as f64];
+        let material = Material::new([1.0, 1.0, 1.0]);
+        let shader = LitShader::new(&material, &[], ShadingMode::Flat);
+        let line_style = LineStyle::new([255, 255, 255, 255]);
+
+        let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+        let mut pyramid = DepthPyramid::new();
+        let mut culler = OcclusionCuller::default();
+
+        // Nothing is hidden before the wall is drawn. The wall is drawn with the half-space rasterizer, which
+        // doesn't leave gaps along the edges between its triangles.
+        pyramid.build(&buffer);
+        assert!(!culler.is_occluded(&pyramid, &cube, &camera, &projection, size));
+
+        let wall_pipe = wall.run_pipeline(&camera, &projection, size);
+        buffer.draw_mesh(
+            &wall_pipe,
+            DrawType::Fill,
+            RasterMethod::HalfSpace,
+            &shader,
+            &line_style,
+        );
+        pyramid.build(&buffer);
+        assert!(culler.is_occluded(&pyramid, &cube, &camera, &projection, size));
+        assert!(!culler.is_occluded(&pyramid, &wall, &camera, &projection, size));
+
+        // Every triangle of the cube is hidden behind the wall, which keeps its own front faces.
+        let mut cube_pipe = cube.run_pipeline(&camera, &projection, size);
+        let cube_triangles = cube_pipe.iter_visible_polygons().count();
+        assert!(cube_triangles > 0);
+        culler.cull_triangles(&pyramid, &mut cube_pipe);
+        assert_eq!(cube_pipe.iter_visible_polygons().count(), 0);
+
+        let mut wall_pipe = wall_pipe;
+        let wall_triangles = wall_pipe.iter_visible_polygons().count();
+        culler.cull_triangles(&pyramid, &mut wall_pipe);
+        assert_eq!(wall_pipe.iter_visible_polygons().count(), wall_triangles);
+
+        assert_eq!(
+            culler.stats(),
+            OcclusionStats {
+                objects_tested: 3,
+                objects_occluded: 1,
+                triangles_tested: cube_triangles + wall_triangles,
+                triangles_occluded: cube_triangles,
+            }
+        );
+        culler.reset_stats();
+        assert_eq!(culler.stats(), OcclusionStats::default());
+    }
+
+    #[test]
+    fn test_reversed_z() {
+        let (wall, cube, camera, mut projection) = wall_and_cube();
+        projection.set_reversed_z(true);
+        projection.set_far_plane(None);
+        let size = [WIDTH as f64, HEIGHT as f64];
+        let material = Material::new([1.0, 1.0, 1.0]);
+        let shader = LitShader::new(&material, &[], ShadingMode::Flat);
+        let line_style = LineStyle::new([255, 255, 255, 255]);
+
+        // Reversed depths still leave closer fragments with larger screen depths, so the wall hides the cube.
+        let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+        let wall_pipe = wall.run_pipeline(&camera, &projection, size);
+        buffer.draw_mesh(
+            &wall_pipe,
+            DrawType::Fill,
+            RasterMethod::HalfSpace,
+            &shader,
+            &line_style,
+        );
+        let mut pyramid = DepthPyramid::new();
+        pyramid.build(&buffer);
+
+        let mut culler = OcclusionCuller::default();
+        assert!(culler.is_occluded(&pyramid, &cube, &camera, &projection, size));
+        assert!(!culler.is_occluded(&pyramid, &wall, &camera, &projection, size));
+
+        let mut cube_pipe = cube.run_pipeline(&camera, &projection, size);
+        assert!(cube_pipe.iter_visible_polygons().count() > 0);
+        culler.cull_triangles(&pyramid, &mut cube_pipe);
+        assert_eq!(cube_pipe.iter_visible_polygons().count(), 0);
+    }
+
+    #[test]
+    fn test_near_plane_not_occluded() {
+        let (_, mut cube, camera, projection) = wall_and_cube();
+        cube.physics.position = Point::new([0, 0, 90]);
+
+        // A full depth buffer hides everything that can be bounded on screen.
+        let mut buffer = FrameBuffer::new(WIDTH, HEIGHT);
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                buffer.set_depth(x, y, SCREEN_DEPTH);
+            }
+        }
+        let mut pyramid = DepthPyramid::new();
+        pyramid.build(&buffer);
+
+        let mut culler = OcclusionCuller::default();
+        let size = [WIDTH as f64, HEIGHT as f64];
+        assert!(!culler.is_occluded(&pyramid, &cube, &camera, &projection, size));
+        cube.physics.position = Point::new([0, 0, 300]);
+        assert!(culler.is_occluded(&pyramid, &cube, &camera, &projection, size));
+    }
+}
@@ -1,15 +1,20 @@
 //! Implementation of a structure containg the kinematic states that represent an objects physical location.
 //!
 
-use crate::mesh::geometry::{Orientation3D, Point};
+use crate::mesh::{
+    geometry::{Orientation3D, Point, Quaternion},
+    Matrix4X4,
+};
 
 ///
 /// Representation of an objects kinematic state within a cartesian coordinate system.
+/// The orientation is stored as a quaternion so that rotations can be combined without gimbal lock. Euler angles and
+/// the rotation matrix are derived from it.
 ///
 #[derive(Clone)]
 pub struct PhysicalState {
     pub position: Point<3>,
-    pub orientation: Orientation3D,
+    pub orientation: Quaternion,
 }
 
 impl Default for PhysicalState {
@@ -30,7 +35,93 @@ impl PhysicalState {
     pub fn new() -> PhysicalState {
         PhysicalState {
             position: Point::new([0, 0, 0]),
-            orientation: Orientation3D::new(0, 0, 0),
+            orientation: Quaternion::identity(),
         }
     }
 }
+
+#[allow(dead_code)]
+impl PhysicalState {
+    /// Return the orientation as a quaternion.
+    ///
+    pub fn rotation(&self) -> Quaternion {
+        self.orientation
+    }
+
+    /// Set the orientation from a quaternion, which is normalised so that it's a pure rotation.
+    ///
+    pub fn set_rotation(&mut self, rotation: &Quaternion) {
+        self.orientation = rotation.normalise();
+    }
+
+    /// Return the orientation as Euler angles, which rotate about x, then y, then z.
+    ///
+    pub fn euler_angles(&self) -> Orientation3D {
+        Orientation3D::from(self.orientation)
+    }
+
+    /// Set the orientation from Euler angles, which rotate about x, then y, then z.
+    ///
+    pub fn set_euler_angles(&mut self, orientation: Orientation3D) {
+        self.orientation = Quaternion::from(orientation);
+    }
+
+    /// Return the matrix rotating model space directions into world space.
+    ///
+    pub fn rotation_matrix(&self) -> Matrix4X4 {
+        self.orientation.matrix()
+    }
+
+    /// Apply a further rotation after the current orientation. Rotations are combined as quaternions, so they don't
+    /// suffer from gimbal lock the way adding Euler angles does. The result is normalised so that rounding errors
+    /// don't build up as rotations are applied every frame.
+    ///
+    pub fn rotate(&mut self, rotation: &Quaternion) {
+        self.set_rotation(&(self.orientation * *rotation));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::geometry::Vector;
+
+    /// Assert that every element of 2 matrices is equal, allowing for rounding errors.
+    ///
+    fn assert_matrices_close(a: &Matrix4X4, b: &Matrix4X4) {
+        for (row_a, row_b) in a.0.iter().zip(b.0.iter()) {
+            for (a, b) in row_a.iter().zip(row_b.iter()) {
+                assert!((a - b).abs() < 1e-9, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate() {
+        let yaw = Quaternion::from_axis_angle(&Vector::new([0, 1, 0]), 90.0);
+        let pitch = Quaternion::from_axis_angle(&Vector::new([1, 0, 0]), 30.0);
+
+        // Rotations are applied after the current orientation, in the same order as their matrices.
+        let mut state = PhysicalState::new();
+        state.rotate(&yaw);
+        state.rotate(&pitch);
+        assert_matrices_close(&state.rotation_matrix(), &(yaw.matrix() * pitch.matrix()));
+
+        // Turning about z at the gimbal lock left by a 90 degree yaw comes back to where it started.
+        let mut state = PhysicalState::new();
+        state.set_euler_angles(Orientation3D::new(0, 90, 0));
+        let start = state.rotation_matrix();
+        let roll = Quaternion::from_axis_angle(&Vector::new([0, 0, 1]), 10.0);
+        for _ in 0..36 {
+            state.rotate(&roll);
+        }
+        assert_matrices_close(&state.rotation_matrix(), &start);
+
+        // Rounding errors don't build up over many small rotations.
+        let nudge = Quaternion::from(Orientation3D::new(1, 0.6, 3));
+        for _ in 0..10000 {
+            state.rotate(&nudge);
+        }
+        assert!((state.rotation().magnitude() - 1.0).abs() < 1e-12);
+    }
+}
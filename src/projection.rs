@@ -0,0 +1,481 @@
+//! Implementation of camera projections, which map view space onto clip space.
+//!
+//! View space has the camera at its origin looking down +Z, with +X to the right and +Y up. A projection keeps its
+//! parameters alongside the matrix built from them, so any of them can be changed at runtime and the matrix is
+//! rebuilt straight away.
+//!
+
+use crate::mesh::{geometry::Point, Matrix4X4};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// The angle a perspective projection sees across, in degrees. The other axis is found from the aspect ratio.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum FieldOfView {
+    /// The angle between the bottom and top of the view.
+    Vertical(f64),
+    /// The angle between the left and right of the view.
+    Horizontal(f64),
+}
+
+/// The shape of the volume a projection sees.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Frustum {
+    /// A perspective view centred on the Z axis.
+    Perspective(FieldOfView),
+    /// A perspective view through a window on the near plane, given by the view space coordinates of its edges.
+    /// The window doesn't have to be centred on the Z axis, and isn't stretched to fit the aspect ratio.
+    OffCentre {
+        left: f64,
+        right: f64,
+        bottom: f64,
+        top: f64,
+    },
+    /// A parallel view centred on the Z axis, given the height it sees in view space units.
+    Orthographic { height: f64 },
+}
+
+/// A projection from view space onto clip space.
+/// Depths between the near and far planes are mapped from 0 to 1 in NDC space, or from 1 to 0 with reversed-Z,
+/// which spreads the precision of the depth buffer more evenly. Perspective projections can have an infinite far
+/// plane, in which case nothing is clipped for being too far away. Orthographic projections can't, so they keep
+/// using the last finite far plane they were given while the far plane is infinite.
+///
+#[derive(Clone, Copy)]
+pub struct Projection {
+    frustum: Frustum,
+    aspect_ratio: f64,
+    near_plane: f64,
+    far_plane: Option<f64>,
+    finite_far_plane: f64,
+    reversed_z: bool,
+
+    matrix: Matrix4X4,
+}
+
+/// The far plane orthographic projections use while the far plane is infinite, if they've never been given a finite
+/// one.
+///
+const ORTHOGRAPHIC_FAR_PLANE: f64 = 1000.0;
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Projection {
+    /// Return a new projection. A far plane of None is infinitely far away.
+    ///
+    pub fn new(
+        frustum: Frustum,
+        aspect_ratio: f64,
+        near_plane: f64,
+        far_plane: Option<f64>,
+    ) -> Projection {
+        let mut projection = Projection {
+            frustum,
+            aspect_ratio,
+            near_plane,
+            far_plane,
+            finite_far_plane: far_plane.unwrap_or(ORTHOGRAPHIC_FAR_PLANE),
+            reversed_z: false,
+            matrix: Matrix4X4([[0.0; 4]; 4]),
+        };
+        projection.update();
+        projection
+    }
+
+    /// Return a new perspective projection with the given vertical field of view in degrees.
+    ///
+    pub fn perspective(fov: f64, aspect_ratio: f64, near_plane: f64, far_plane: f64) -> Projection {
+        Projection::new(
+            Frustum::Perspective(FieldOfView::Vertical(fov)),
+            aspect_ratio,
+            near_plane,
+            Some(far_plane),
+        )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+impl Projection {
+    /// Return the matrix moving view space into clip space.
+    ///
+    pub fn matrix(&self) -> &Matrix4X4 {
+        &self.matrix
+    }
+
+    /// Return the shape of the volume the projection sees.
+    ///
+    pub fn frustum(&self) -> Frustum {
+        self.frustum
+    }
+
+    /// Return the ratio of the view's width to its height.
+    ///
+    pub fn aspect_ratio(&self) -> f64 {
+        self.aspect_ratio
+    }
+
+    /// Return the distance to the near plane.
+    ///
+    pub fn near_plane(&self) -> f64 {
+        self.near_plane
+    }
+
+    /// Return the distance to the far plane, or None if it's infinitely far away. Orthographic projections always
+    /// return the finite far plane they're using.
+    ///
+    pub fn far_plane(&self) -> Option<f64> {
+        match self.frustum {
+            Frustum::Orthographic { .. } => Some(self.orthographic_far_plane()),
+            _ => self.far_plane,
+        }
+    }
+
+    /// Return true if depths are reversed, so that the near plane is at 1 in NDC space and the far plane at 0.
+    ///
+    pub fn reversed_z(&self) -> bool {
+        self.reversed_z
+    }
+
+    /// Change the shape of the volume the projection sees.
+    ///
+    pub fn set_frustum(&mut self, frustum: Frustum) {
+        self.frustum = frustum;
+        self.update();
+    }
+
+    /// Change the projection to a perspective projection with the given field of view.
+    ///
+    pub fn set_field_of_view(&mut self, fov: FieldOfView) {
+        self.set_frustum(Frustum::Perspective(fov));
+    }
+
+    /// Change the ratio of the view's width to its height.
+    ///
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f64) {
+        self.aspect_ratio = aspect_ratio;
+        self.update();
+    }
+
+    /// Change the aspect ratio to match the size of the target being drawn into, such as when a window is resized.
+    ///
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.set_aspect_ratio(width as f64 / height.max(1) as f64);
+    }
+
+    /// Change the distance to the near plane.
+    ///
+    pub fn set_near_plane(&mut self, near_plane: f64) {
+        self.near_plane = near_plane;
+        self.update();
+    }
+
+    /// Change the distance to the far plane, or make it infinitely far away with None. Orthographic projections keep
+    /// using the last finite far plane until they're changed back to a perspective projection.
+    ///
+    pub fn set_far_plane(&mut self, far_plane: Option<f64>) {
+        self.far_plane = far_plane;
+        if let Some(far_plane) = far_plane {
+            self.finite_far_plane = far_plane;
+        }
+        self.update();
+    }
+
+    /// Change whether depths are reversed.
+    ///
+    pub fn set_reversed_z(&mut self, reversed_z: bool) {
+        self.reversed_z = reversed_z;
+        self.update();
+    }
+
+    /// Return the view space corners of the slice of the view between 2 distances from the camera. The near corners
+    /// come first, each starting from the bottom left and working along the bottom edge and then the top edge.
+    ///
+    pub fn view_corners(&self, near: f64, far: f64) -> [Point<3>; 8] {
+        let matrix = self.matrix.0;
+        let mut corners = [Point::default(); 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let z = if i < 4 { near } else { far };
+            let (ndc_x, ndc_y) = (
+                if i % 2 == 0 { -1.0 } else { 1.0 },
+                if (i / 2) % 2 == 0 { -1.0 } else { 1.0 },
+            );
+
+            // Perspective projections divide by z, while orthographic ones keep a constant w.
+            *corner = match self.frustum {
+                Frustum::Orthographic { .. } => Point::new([
+                    (ndc_x - matrix[3][0]) / matrix[0][0],
+                    (ndc_y - matrix[3][1]) / matrix[1][1],
+                    z,
+                ]),
+                _ => Point::new([
+                    z * (ndc_x - matrix[2][0]) / matrix[0][0],
+                    z * (ndc_y - matrix[2][1]) / matrix[1][1],
+                    z,
+                ]),
+            };
+        }
+        corners
+    }
+
+    /// Rebuild the matrix from the projection's parameters.
+    ///
+    fn update(&mut self) {
+        let near = self.near_plane;
+
+        self.matrix = match self.frustum {
+            Frustum::Perspective(fov) => {
+                let (half_width, half_height) = match fov {
+                    FieldOfView::Vertical(fov) => {
+                        let half_height = near * (fov.to_radians() / 2.0).tan();
+                        (half_height * self.aspect_ratio, half_height)
+                    }
+                    FieldOfView::Horizontal(fov) => {
+                        let half_width = near * (fov.to_radians() / 2.0).tan();
+                        (half_width, half_width / self.aspect_ratio)
+                    }
+                };
+                self.perspective_matrix(-half_width, half_width, -half_height, half_height)
+            }
+            Frustum::OffCentre {
+                left,
+                right,
+                bottom,
+                top,
+            } => self.perspective_matrix(left, right, bottom, top),
+            Frustum::Orthographic { height } => {
+                let far = self.orthographic_far_plane();
+                let (half_width, half_height) = (height * self.aspect_ratio / 2.0, height / 2.0);
+                let mut matrix = Matrix4X4::new_orthographic(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    near,
+                    far,
+                );
+                if self.reversed_z {
+                    matrix.0[2][2] = -1.0 / (far - near);
+                    matrix.0[3][2] = far / (far - near);
+                }
+                matrix
+            }
+        };
+    }
+
+    /// Return the far plane used by orthographic projections, which is the last finite one while the far plane is
+    /// infinite.
+    ///
+    fn orthographic_far_plane(&self) -> f64 {
+        self.far_plane.unwrap_or(self.finite_far_plane)
+    }
+
+    /// Return a perspective matrix looking through a window on the near plane. Depths are mapped using the near and
+    /// far planes and whether they're reversed.
+    ///
+    fn perspective_matrix(&self, left: f64, right: f64, bottom: f64, top: f64) -> Matrix4X4 {
+        let near = self.near_plane;
+        let (width, height) = (right - left, top - bottom);
+
+        // The depth is z * depth_scale + depth_offset, before it's divided by w = z.
+        let (depth_scale, depth_offset) = match (self.far_plane, self.reversed_z) {
+            (Some(far), false) => (far / (far - near), -(far * near) / (far - near)),
+            (Some(far), true) => (-near / (far - near), (far * near) / (far - near)),
+            (None, false) => (1.0, -near),
+            (None, true) => (0.0, near),
+        };
+
+        Matrix4X4([
+            [(2.0 * near) / width, 0.0, 0.0, 0.0],
+            [0.0, (2.0 * near) / height, 0.0, 0.0],
+            [
+                -(right + left) / width,
+                -(top + bottom) / height,
+                depth_scale,
+                1.0,
+            ],
+            [0.0, 0.0, depth_offset, 0.0],
+        ])
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{
+        geometry::Dim::{W, X, Y, Z},
+        Vertex,
+    };
+
+    /// Return the NDC X and Y coordinates of a view space point.
+    ///
+    fn project(projection: &Projection, point: [f64; 3]) -> [f64; 2] {
+        let clip = Vertex::new([point[0], point[1], point[2], 1.0]) * *projection.matrix();
+        [clip[X] / clip[W], clip[Y] / clip[W]]
+    }
+
+    /// Return the NDC depth of a view space distance.
+    ///
+    fn ndc_depth(projection: &Projection, z: f64) -> f64 {
+        let clip = Vertex::new([0.0, 0.0, z, 1.0]) * *projection.matrix();
+        clip[Z] / clip[W]
+    }
+
+    fn coordinates(point: &Point<3>) -> [f64; 3] {
+        [point[X], point[Y], point[Z]]
+    }
+
+    fn assert_near<const N: usize>(actual: [f64; N], expected: [f64; N]) {
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-9, "{:?}", (actual, expected));
+        }
+    }
+
+    #[test]
+    fn test_field_of_view_is_degrees() {
+        // A 90 degree field of view sees as far to the side as it does ahead.
+        let mut projection = Projection::perspective(90.0, 2.0, 1.0, 100.0);
+        assert_near(project(&projection, [20.0, 10.0, 10.0]), [1.0, 1.0]);
+
+        projection.set_field_of_view(FieldOfView::Horizontal(90.0));
+        assert_near(project(&projection, [10.0, 5.0, 10.0]), [1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_depth_range() {
+        let mut projection = Projection::perspective(60.0, 1.0, 10.0, 1000.0);
+        assert_near([ndc_depth(&projection, 10.0)], [0.0]);
+        assert_near([ndc_depth(&projection, 1000.0)], [1.0]);
+
+        projection.set_reversed_z(true);
+        assert_near([ndc_depth(&projection, 10.0)], [1.0]);
+        assert_near([ndc_depth(&projection, 1000.0)], [0.0]);
+
+        // An infinite far plane approaches the far depth without reaching it.
+        projection.set_far_plane(None);
+        assert_near([ndc_depth(&projection, 10.0)], [1.0]);
+        assert!((0.0..1e-7).contains(&ndc_depth(&projection, 1e9)));
+        assert!(ndc_depth(&projection, 1e9) > 0.0);
+        projection.set_reversed_z(false);
+        assert_near([ndc_depth(&projection, 10.0)], [0.0]);
+        assert!(((1.0 - 1e-7)..1.0).contains(&ndc_depth(&projection, 1e9)));
+
+        // Orthographic depths are linear.
+        let mut projection = Projection::new(
+            Frustum::Orthographic { height: 100.0 },
+            1.0,
+            10.0,
+            Some(110.0),
+        );
+        assert_near([ndc_depth(&projection, 35.0)], [0.25]);
+        projection.set_reversed_z(true);
+        assert_near([ndc_depth(&projection, 35.0)], [0.75]);
+    }
+
+    #[test]
+    fn test_orthographic_and_off_centre() {
+        // Orthographic projections ignore distance.
+        let projection = Projection::new(
+            Frustum::Orthographic { height: 100.0 },
+            2.0,
+            10.0,
+            Some(110.0),
+        );
+        assert_near(project(&projection, [100.0, 50.0, 20.0]), [1.0, 1.0]);
+        assert_near(project(&projection, [-100.0, -50.0, 100.0]), [-1.0, -1.0]);
+
+        // A window on the near plane to the right of the Z axis.
+        let projection = Projection::new(
+            Frustum::OffCentre {
+                left: 0.0,
+                right: 20.0,
+                bottom: -5.0,
+                top: 5.0,
+            },
+            1.0,
+            10.0,
+            Some(100.0),
+        );
+        assert_near(project(&projection, [0.0, -5.0, 10.0]), [-1.0, -1.0]);
+        assert_near(project(&projection, [40.0, 10.0, 20.0]), [1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_parameters_rebuild_matrix() {
+        let mut projection = Projection::perspective(90.0, 1.0, 1.0, 100.0);
+        assert_near(project(&projection, [10.0, 10.0, 10.0]), [1.0, 1.0]);
+
+        // Resizing keeps the vertical field of view and widens the horizontal one.
+        projection.resize(200, 100);
+        assert_near(project(&projection, [10.0, 10.0, 10.0]), [0.5, 1.0]);
+
+        projection.set_near_plane(5.0);
+        assert_near([ndc_depth(&projection, 5.0)], [0.0]);
+        projection.set_far_plane(Some(50.0));
+        assert_near([ndc_depth(&projection, 50.0)], [1.0]);
+    }
+
+    #[test]
+    fn test_orthographic_infinite_far_plane() {
+        let orthographic = Frustum::Orthographic { height: 100.0 };
+        let perspective = Frustum::Perspective(FieldOfView::Vertical(60.0));
+
+        // Orthographic projections keep the last finite far plane while the far plane is infinite.
+        let mut projection = Projection::new(orthographic, 1.0, 10.0, Some(110.0));
+        projection.set_far_plane(None);
+        assert_eq!(projection.far_plane(), Some(110.0));
+        assert_near([ndc_depth(&projection, 110.0)], [1.0]);
+
+        // Switching to a perspective projection makes the far plane infinite, and switching back restores it.
+        projection.set_frustum(perspective);
+        assert_eq!(projection.far_plane(), None);
+        assert!(ndc_depth(&projection, 1e9) < 1.0);
+        projection.set_frustum(orthographic);
+        assert_near([ndc_depth(&projection, 110.0)], [1.0]);
+
+        // A new finite far plane replaces the one that's kept.
+        projection.set_far_plane(Some(210.0));
+        projection.set_frustum(perspective);
+        projection.set_far_plane(None);
+        projection.set_frustum(orthographic);
+        assert_eq!(projection.far_plane(), Some(210.0));
+        assert_near([ndc_depth(&projection, 210.0)], [1.0]);
+
+        // Projections that start with an infinite far plane still have one to fall back to.
+        let mut projection = Projection::new(perspective, 1.0, 10.0, None);
+        projection.set_frustum(orthographic);
+        assert_eq!(projection.far_plane(), Some(ORTHOGRAPHIC_FAR_PLANE));
+        assert_near([ndc_depth(&projection, ORTHOGRAPHIC_FAR_PLANE)], [1.0]);
+    }
+
+    #[test]
+    fn test_view_corners() {
+        let projection = Projection::perspective(90.0, 2.0, 1.0, 100.0);
+        let corners = projection.view_corners(10.0, 20.0);
+        assert_near(coordinates(&corners[0]), [-20.0, -10.0, 10.0]);
+        assert_near(coordinates(&corners[7]), [40.0, 20.0, 20.0]);
+
+        let projection = Projection::new(
+            Frustum::Orthographic { height: 100.0 },
+            2.0,
+            10.0,
+            Some(110.0),
+        );
+        let corners = projection.view_corners(10.0, 20.0);
+        assert_near(coordinates(&corners[0]), [-100.0, -50.0, 10.0]);
+        assert_near(coordinates(&corners[7]), [100.0, 50.0, 20.0]);
+    }
+}
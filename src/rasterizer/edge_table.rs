@@ -0,0 +1,245 @@
+use crate::mesh::{geometry::Dim, RefPoly, Vertex};
+use std::mem::swap;
+
+///
+/// Error handling
+///
+type Result<T> = std::result::Result<T, Error>;
+pub enum Error {
+    NoEdge,
+}
+
+///
+/// A sub-struct of EdgeList containg x and z coordinates, along with the screen space barycentric weights of the
+/// polygon's verticies at that point.
+///
+#[derive(Clone, Copy)]
+pub struct XZPair {
+    pub x: i32,
+    pub z: f64,
+    pub weights: [f64; 3],
+}
+
+///
+/// A sub-struct of EdgeTable holding the XZPair's drawn into a row. Only the first and last pairs are ever used, so
+/// they're the only ones kept.
+///
+#[derive(Clone, Copy)]
+pub struct EdgeList {
+    first: Option<XZPair>,
+    last: Option<XZPair>,
+}
+impl EdgeList {
+    pub fn new() -> EdgeList {
+        EdgeList {
+            first: None,
+            last: None,
+        }
+    }
+}
+impl EdgeList {
+    pub fn push(&mut self, xzpair: XZPair) {
+        if self.first.is_none() {
+            self.first = Some(xzpair);
+        }
+        self.last = Some(xzpair);
+    }
+
+    ///
+    /// Return the first and last edges from the list as an array
+    ///
+    /// # Errors
+    /// NoEdge: No edges in the list
+    ///
+    pub fn get_edges(&self) -> Result<[&XZPair; 2]> {
+        match (&self.first, &self.last) {
+            (Some(pair1), Some(pair2)) => Ok([pair1, pair2]),
+            _ => Err(Error::NoEdge),
+        }
+    }
+}
+
+///
+/// Edge table required for the rasterization process.
+/// A table can be rebuilt for each polygon, reusing the rows allocated for earlier polygons.
+///
+#[derive(Default)]
+pub struct EdgeTable {
+    table: Vec<EdgeList>,
+    pub ymin: i32,
+    pub ymax: i32,
+}
+// Constructor function and helpers
+impl EdgeTable {
+    ///
+    /// Create a new EdgeTable from a screen space polygon.
+    ///
+    #[allow(dead_code)]
+    pub fn new(poly: RefPoly) -> EdgeTable {
+        let mut edge_table = EdgeTable::default();
+        edge_table.build(poly);
+        edge_table
+    }
+
+    ///
+    /// Replace the contents of the table with the edges of a screen space polygon.
+    ///
+    pub fn build(&mut self, poly: RefPoly) {
+        let verticies = poly.verticies;
+        let x = |index: usize| verticies[index][Dim::X];
+        let y = |index: usize| verticies[index][Dim::Y];
+
+        // Order the verticies in increasing order of X
+        let [mut vert1, mut vert2, mut vert3] = [0, 1, 2];
+        if x(vert2) < x(vert1) {
+            swap(&mut vert1, &mut vert2);
+        }
+        if x(vert3) < x(vert1) {
+            swap(&mut vert1, &mut vert3);
+        }
+        if x(vert3) < x(vert2) {
+            swap(&mut vert2, &mut vert3);
+        }
+
+        // Add enough elements to the table to encompass the polygon in the Y axis
+        let (ymin, ymax) = {
+            let (min, max) = EdgeTable::min_max(y(vert1), y(vert2), y(vert3));
+            (min as i32, max as i32)
+        };
+        let table = &mut self.table;
+        table.clear();
+        table.resize(((ymax - ymin) + 1) as usize, EdgeList::new());
+
+        // Declare lines in clockwise order around the polygon but keep the leftmost point first.
+        let mut line1 = {
+            let gradient = (y(vert2) - y(vert1)) / (x(vert2) - x(vert1));
+            (vert1, vert2, gradient)
+        };
+        let mut line2 = {
+            let gradient = (y(vert3) - y(vert2)) / (x(vert3) - x(vert2));
+            (vert2, vert3, gradient)
+        };
+        let mut line3 = {
+            let gradient = (y(vert3) - y(vert1)) / (x(vert3) - x(vert1));
+            (vert1, vert3, gradient)
+        };
+
+        // Order the lines into the order they should be drawn
+        if (line1.2 > 0.0 && line3.2 < 0.0) || (line1.2 < 0.0 && line3.2 > 0.0) {
+            swap(&mut line2, &mut line3);
+        } else if line1.2.abs() < line3.2.abs() {
+            swap(&mut line1, &mut line3);
+        }
+
+        EdgeTable::draw_line(&verticies, line1.0, line1.1, table, ymin);
+        EdgeTable::draw_line(&verticies, line2.0, line2.1, table, ymin);
+        EdgeTable::draw_line(&verticies, line3.0, line3.1, table, ymin);
+
+        self.ymin = ymin;
+        self.ymax = ymax;
+    }
+
+    ///
+    /// Return the minimum and maximum values of 3 parameters as a tuple (min, max)
+    ///
+    fn min_max(val1: f64, val2: f64, val3: f64) -> (f64, f64) {
+        let (mut min, mut max) = if val1 < val2 {
+            (val1, val2)
+        } else {
+            (val2, val1)
+        };
+
+        if val3 < min {
+            min = val3;
+        } else if val3 > max {
+            max = val3;
+        }
+
+        (min, max)
+    }
+
+    ///
+    /// Draw a line between 2 of a polygon's verticies into an edge table using brezenham's algorithm.
+    /// Z and the verticies' weights are interpolated along the line.
+    ///
+    fn draw_line(
+        verticies: &[&Vertex; 3],
+        from: usize,
+        to: usize,
+        table: &mut [EdgeList],
+        yoffset: i32,
+    ) {
+        let (p1, p2) = (verticies[from], verticies[to]);
+        let (x1, y1, z1) = (p1[Dim::X] as i32, p1[Dim::Y] as i32, p1[Dim::Z]);
+        let (x2, y2, z2) = (p2[Dim::X] as i32, p2[Dim::Y] as i32, p2[Dim::Z]);
+
+        let dx = (x2 - x1).abs();
+        let dy = (y2 - y1).abs();
+
+        let xs = if x1 < x2 { 1 } else { -1 };
+        let ys = if y1 < y2 { 1 } else { -1 };
+
+        // Return the point at a fraction t of the way along the line.
+        let xzpair = |x: i32, t: f64| {
+            let mut weights = [0.0; 3];
+            weights[from] = 1.0 - t;
+            weights[to] += t;
+
+            XZPair {
+                x,
+                z: z1 + ((z2 - z1) * t),
+                weights,
+            }
+        };
+
+        if dx >= dy {
+            // X is the driving axis
+            let mut ygain = (2 * dy) - dx;
+            let mut y = y1;
+            let mut x = x1;
+
+            for step in 0..=dx {
+                let t = if dx == 0 {
+                    0.0
+                } else {
+                    step as f64 / dx as f64
+                };
+                table[(y - yoffset) as usize].push(xzpair(x, t));
+
+                if ygain > 0 {
+                    y += ys;
+                    ygain -= 2 * dx;
+                }
+
+                ygain += 2 * dy;
+                x += xs;
+            }
+        } else {
+            // Y is the driving axis
+            let mut xgain = (2 * dx) - dy;
+            let mut x = x1;
+            let mut y = y1;
+
+            for step in 0..=dy {
+                let t = step as f64 / dy as f64;
+                table[(y - yoffset) as usize].push(xzpair(x, t));
+
+                if xgain > 0 {
+                    x += xs;
+                    xgain -= 2 * dy;
+                }
+
+                xgain += 2 * dx;
+                y += ys;
+            }
+        }
+    }
+}
+impl EdgeTable {
+    ///
+    /// Iterate imutably over a slice of an EdgeList.
+    ///
+    pub fn iter_between(&self, first: usize, last: usize) -> std::slice::Iter<'_, EdgeList> {
+        self.table[first..last].iter()
+    }
+}
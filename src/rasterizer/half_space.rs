@@ -0,0 +1,468 @@
+//! Implementation of a half-space triangle rasterizer.
+//!
+//! Verticies are snapped to a fixed point sub-pixel grid and each pixel centre within the triangle's bounds is tested
+//! against the triangle's 3 edge functions. Pixels lying exactly on an edge are only covered if the edge is a top or
+//! left edge, so triangles sharing an edge never both cover the same pixel. Anti-aliased targets test several sample
+//! positions within each pixel instead of its centre, following the same rules.
+//!
+
+use super::{Fragment, Rect, MAX_SAMPLES};
+use crate::mesh::{geometry::Dim, RefPoly, Vertex};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Number of bits of sub-pixel precision.
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL_ONE: i64 = 1 << SUBPIXEL_BITS;
+const SUBPIXEL_HALF: i64 = SUBPIXEL_ONE / 2;
+
+/// The samples of a pixel covered by a triangle.
+///
+pub struct PixelCoverage {
+    pub x: u32,
+    pub y: u32,
+
+    /// Bit i is set if the triangle covers sample i.
+    pub mask: u32,
+    /// The screen space barycentric weights of the triangle's verticies at each covered sample.
+    pub weights: [[f64; 3]; MAX_SAMPLES],
+    /// The depth of the triangle at each covered sample.
+    pub depths: [f64; MAX_SAMPLES],
+}
+
+/// An edge function for the edge running from a to b.
+///
+struct Edge {
+    x_step: i64,
+    y_step: i64,
+    bias: i64,
+}
+
+/// A triangle ready to be rasterized, with anticlockwise winding.
+///
+struct Setup {
+    /// The index of the triangle's vertex weighted by each edge.
+    order: [usize; 3],
+    area: i64,
+    edges: [Edge; 3],
+
+    /// The first and last pixel in each axis that the triangle might cover.
+    xrange: [i64; 2],
+    yrange: [i64; 2],
+    /// The edge functions evaluated at the bottom left corner of the first pixel.
+    origin: [i64; 3],
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Implementations /////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Rasterize a screen space triangle, calling f for each pixel within the given bounds that it covers.
+/// Both clockwise and anticlockwise triangles are rasterized. The triangle's verticies must hold 1/w in their W
+/// component. Limiting the bounds never changes which pixels are covered or how their fragments are interpolated.
+///
+pub fn rasterize_triangle<F>(poly: &RefPoly, bounds: Rect, mut f: F)
+where
+    F: FnMut(Fragment),
+{
+    let setup = match Setup::new(poly, bounds) {
+        Some(setup) => setup,
+        None => return,
+    };
+
+    // Evaluate the edge functions at pixel centres.
+    let centre = setup.offsets([SUBPIXEL_HALF, SUBPIXEL_HALF]);
+
+    setup.for_each_pixel(|x, y, w| {
+        let w = add(w, centre);
+        if setup.covers(w) {
+            let weights = setup.weights(w);
+            f(Fragment::new(x, y, depth(poly, weights), weights, poly));
+        }
+    });
+}
+
+/// Rasterize a screen space triangle, calling f for each pixel within the given bounds where it covers at least one
+/// of the given sample positions. Positions are given within a pixel with its bottom left corner at the origin, and
+/// there can be at most MAX_SAMPLES of them.
+///
+pub fn rasterize_triangle_samples<F>(poly: &RefPoly, bounds: Rect, samples: &[[f64; 2]], mut f: F)
+where
+    F: FnMut(&PixelCoverage),
+{
+    let setup = match Setup::new(poly, bounds) {
+        Some(setup) => setup,
+        None => return,
+    };
+
+    // Offset each edge function from the pixel's corner to each sample, snapped to the sub-pixel grid.
+    let mut offsets = [[0; 3]; MAX_SAMPLES];
+    for (offset, position) in offsets.iter_mut().zip(samples.iter()) {
+        *offset = setup
+            .offsets(position.map(|coordinate| (coordinate * SUBPIXEL_ONE as f64).round() as i64));
+    }
+    let offsets = &offsets[..samples.len().min(MAX_SAMPLES)];
+
+    setup.for_each_pixel(|x, y, w| {
+        let mut coverage = PixelCoverage {
+            x,
+            y,
+            mask: 0,
+            weights: [[0.0; 3]; MAX_SAMPLES],
+            depths: [0.0; MAX_SAMPLES],
+        };
+
+        for (sample, &offset) in offsets.iter().enumerate() {
+            let w = add(w, offset);
+            if setup.covers(w) {
+                let weights = setup.weights(w);
+                coverage.mask |= 1 << sample;
+                coverage.weights[sample] = weights;
+                coverage.depths[sample] = depth(poly, weights);
+            }
+        }
+
+        if coverage.mask != 0 {
+            f(&coverage);
+        }
+    });
+}
+
+impl Setup {
+    /// Return a triangle ready to be rasterized within the given bounds, or None if it has no area or lies outside
+    /// them.
+    ///
+    fn new(poly: &RefPoly, bounds: Rect) -> Option<Setup> {
+        let mut verts = poly.verticies.map(snap);
+        let mut order = [0, 1, 2];
+
+        // Make the winding anticlockwise so the inside of every edge is to its left.
+        let mut area = orient(verts[0], verts[1], verts[2]);
+        if area < 0 {
+            verts.swap(1, 2);
+            order.swap(1, 2);
+            area = -area;
+        }
+        if area == 0 || bounds.width == 0 || bounds.height == 0 {
+            return None;
+        }
+
+        // Find the pixels bounding the triangle, limited to the bounds.
+        let xmin = verts.iter().map(|v| v[0]).min().unwrap() >> SUBPIXEL_BITS;
+        let xmax = verts.iter().map(|v| v[0]).max().unwrap() >> SUBPIXEL_BITS;
+        let ymin = verts.iter().map(|v| v[1]).min().unwrap() >> SUBPIXEL_BITS;
+        let ymax = verts.iter().map(|v| v[1]).max().unwrap() >> SUBPIXEL_BITS;
+
+        let (left, right) = (bounds.x as i64, bounds.right() as i64 - 1);
+        let (bottom, top) = (bounds.y as i64, bounds.top() as i64 - 1);
+        if xmax < left || xmin > right || ymax < bottom || ymin > top {
+            return None;
+        }
+
+        let xrange = [xmin.max(left), xmax.min(right)];
+        let yrange = [ymin.max(bottom), ymax.min(top)];
+
+        // Each edge function is opposite the vertex it weights.
+        let corner = [xrange[0] << SUBPIXEL_BITS, yrange[0] << SUBPIXEL_BITS];
+        Some(Setup {
+            order,
+            area,
+            edges: [
+                Edge::new(verts[1], verts[2]),
+                Edge::new(verts[2], verts[0]),
+                Edge::new(verts[0], verts[1]),
+            ],
+            xrange,
+            yrange,
+            origin: [
+                orient(verts[1], verts[2], corner),
+                orient(verts[2], verts[0], corner),
+                orient(verts[0], verts[1], corner),
+            ],
+        })
+    }
+
+    /// Call f with the coordinates of each pixel in the triangle's bounds and its edge functions evaluated at the
+    /// pixel's bottom left corner.
+    ///
+    fn for_each_pixel<F>(&self, mut f: F)
+    where
+        F: FnMut(u32, u32, [i64; 3]),
+    {
+        let mut row = self.origin;
+
+        for y in self.yrange[0]..=self.yrange[1] {
+            let mut w = row;
+
+            for x in self.xrange[0]..=self.xrange[1] {
+                f(x as u32, y as u32, w);
+
+                w.iter_mut()
+                    .zip(self.edges.iter())
+                    .for_each(|(w, edge)| *w += edge.x_step);
+            }
+
+            row.iter_mut()
+                .zip(self.edges.iter())
+                .for_each(|(w, edge)| *w += edge.y_step);
+        }
+    }
+
+    /// Return how much each edge function changes when moving by an offset on the sub-pixel grid.
+    ///
+    fn offsets(&self, offset: [i64; 2]) -> [i64; 3] {
+        self.edges.each_ref().map(|edge| edge.offset(offset))
+    }
+
+    /// Return true if a point with the given edge function values is inside the triangle.
+    ///
+    fn covers(&self, w: [i64; 3]) -> bool {
+        w.iter()
+            .zip(self.edges.iter())
+            .all(|(w, edge)| w + edge.bias >= 0)
+    }
+
+    /// Return the screen space weights of the triangle's verticies at a point with the given edge function values.
+    ///
+    fn weights(&self, w: [i64; 3]) -> [f64; 3] {
+        let mut weights = [0.0; 3];
+        for (i, &vertex) in self.order.iter().enumerate() {
+            weights[vertex] = w[i] as f64 / self.area as f64;
+        }
+        weights
+    }
+}
+
+/// Return the depth of a triangle at a point given the screen space weights of its verticies. Depth is affine in
+/// screen space so is interpolated with the screen space weights.
+///
+fn depth(poly: &RefPoly, weights: [f64; 3]) -> f64 {
+    weights
+        .iter()
+        .zip(poly.verticies.iter())
+        .fold(0.0, |sum, (weight, vertex)| sum + (weight * vertex[Dim::Z]))
+}
+
+/// Add 2 sets of edge function values.
+///
+fn add(a: [i64; 3], b: [i64; 3]) -> [i64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+/// Snap a vertex's screen coordinates to the sub-pixel grid.
+///
+fn snap(vertex: &Vertex) -> [i64; 2] {
+    [
+        (vertex[Dim::X] * SUBPIXEL_ONE as f64).round() as i64,
+        (vertex[Dim::Y] * SUBPIXEL_ONE as f64).round() as i64,
+    ]
+}
+
+/// Return twice the signed area of the triangle abc. Positive if anticlockwise.
+///
+fn orient(a: [i64; 2], b: [i64; 2], c: [i64; 2]) -> i64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+impl Edge {
+    fn new(a: [i64; 2], b: [i64; 2]) -> Edge {
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+
+        // With anticlockwise winding and Y pointing up, left edges point down and top edges point left.
+        let top_left = dy < 0 || (dy == 0 && dx < 0);
+
+        Edge {
+            x_step: -dy * SUBPIXEL_ONE,
+            y_step: dx * SUBPIXEL_ONE,
+            bias: if top_left { 0 } else { -1 },
+        }
+    }
+
+    /// Return how much the edge function changes when moving by an offset on the sub-pixel grid.
+    ///
+    fn offset(&self, offset: [i64; 2]) -> i64 {
+        ((self.x_step * offset[0]) + (self.y_step * offset[1])) / SUBPIXEL_ONE
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::geometry::Vector;
+
+    /// Rasterize triangles and return the number of times each pixel was covered.
+    ///
+    fn coverage(triangles: &[[[f64; 2]; 3]], width: u32, height: u32) -> Vec<u32> {
+        let normal = Vector::new([0, 0, -1]);
+        let mut counts = vec![0; (width * height) as usize];
+
+        for triangle in triangles {
+            let verts = triangle.map(|[x, y]| Vertex::new([x, y, 0.0, 1.0]));
+            let poly = RefPoly::new(&verts[0], &verts[1], &verts[2], &normal);
+
+            rasterize_triangle(&poly, Rect::new(0, 0, width, height), |fragment| {
+                counts[(fragment.y * width + fragment.x) as usize] += 1;
+            });
+        }
+        counts
+    }
+
+    #[test]
+    fn test_shared_edge() {
+        // A square split along its diagonal, with verticies on pixel centres and edges.
+        let counts = coverage(
+            &[
+                [[1.0, 1.0], [9.0, 1.0], [9.0, 9.0]],
+                [[1.0, 1.0], [9.0, 9.0], [1.0, 9.0]],
+            ],
+            10,
+            10,
+        );
+
+        for y in 0..10 {
+            for x in 0..10 {
+                let expected = u32::from((1..9).contains(&x) && (1..9).contains(&y));
+                assert_eq!(counts[y * 10 + x], expected, "pixel {}, {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fan_is_watertight() {
+        // A fan of thin triangles around a centre vertex with sub-pixel coordinates, wound both ways.
+        let centre = [16.3, 15.7];
+        let points: Vec<[f64; 2]> = (0..17)
+            .map(|i| {
+                let angle = (i as f64 / 17.0) * std::f64::consts::TAU;
+                [
+                    centre[0] + 14.1 * angle.cos(),
+                    centre[1] + 13.9 * angle.sin(),
+                ]
+            })
+            .collect();
+
+        let triangles: Vec<_> = (0..points.len())
+            .map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                if i % 2 == 0 {
+                    [centre, a, b]
+                } else {
+                    [centre, b, a]
+                }
+            })
+            .collect();
+
+        let counts = coverage(&triangles, 32, 32);
+
+        // Every pixel with its centre inside the fan is covered exactly once.
+        for y in 0..32 {
+            for x in 0..32 {
+                let centre = [x as f64 + 0.5, y as f64 + 0.5];
+                let inside = (0..points.len()).all(|i| {
+                    let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                    (b[0] - a[0]) * (centre[1] - a[1]) - (b[1] - a[1]) * (centre[0] - a[0]) > 0.0
+                });
+                assert_eq!(counts[y * 32 + x], u32::from(inside), "pixel {}, {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sample_coverage() {
+        // A square split along its diagonal, with a vertical edge through the middle of the pixels in column 2.
+        let verts = [
+            Vertex::new([0.0, 0.0, 0.0, 1.0]),
+            Vertex::new([2.5, 0.0, 0.0, 1.0]),
+            Vertex::new([2.5, 4.0, 0.0, 1.0]),
+            Vertex::new([0.0, 4.0, 0.0, 1.0]),
+        ];
+        let normal = Vector::new([0, 0, -1]);
+        let triangles = [
+            RefPoly::new(&verts[0], &verts[1], &verts[2], &normal),
+            RefPoly::new(&verts[0], &verts[2], &verts[3], &normal),
+        ];
+        let samples = [[0.25, 0.25], [0.75, 0.25], [0.25, 0.75], [0.75, 0.75]];
+
+        let mut masks = [0; 16];
+        for triangle in &triangles {
+            rasterize_triangle_samples(triangle, Rect::new(0, 0, 4, 4), &samples, |coverage| {
+                let pixel = &mut masks[(coverage.y * 4 + coverage.x) as usize];
+
+                // No sample is covered by both triangles.
+                assert_eq!(*pixel & coverage.mask, 0);
+                *pixel |= coverage.mask;
+            });
+        }
+
+        // Only the left column of samples in column 2 is covered.
+        for (i, mask) in masks.iter().enumerate() {
+            let expected = match i % 4 {
+                0 | 1 => 0b1111,
+                2 => 0b0101,
+                _ => 0,
+            };
+            assert_eq!(*mask, expected, "pixel {}", i);
+        }
+    }
+
+    #[test]
+    fn test_weights() {
+        let verts = [
+            Vertex::new([0.0, 0.0, 0.0, 1.0]),
+            Vertex::new([4.0, 0.0, 0.0, 1.0]),
+            Vertex::new([0.0, 4.0, 0.0, 1.0]),
+        ];
+        let normal = Vector::new([0, 0, -1]);
+        let poly = RefPoly::new(&verts[2], &verts[0], &verts[1], &normal);
+
+        rasterize_triangle(&poly, Rect::new(0, 0, 4, 4), |fragment| {
+            let sum: f64 = fragment.weights.iter().sum();
+            assert!((sum - 1.0).abs() < 1e-9);
+
+            // Interpolating the verticies' X coordinates gives the pixel centre.
+            let [x] = fragment.interpolate([[0.0], [0.0], [4.0]]);
+            assert!((x - (fragment.x as f64 + 0.5)).abs() < 1e-9);
+        });
+    }
+
+    #[test]
+    fn test_split_bounds() {
+        let verts = [
+            Vertex::new([1.3, 2.1, 10.0, 1.0]),
+            Vertex::new([17.6, 5.4, 20.0, 0.5]),
+            Vertex::new([6.2, 14.9, 30.0, 0.25]),
+        ];
+        let normal = Vector::new([0, 0, -1]);
+        let poly = RefPoly::new(&verts[0], &verts[1], &verts[2], &normal);
+
+        let mut whole = Vec::new();
+        rasterize_triangle(&poly, Rect::new(0, 0, 20, 16), |fragment| {
+            whole.push((fragment.x, fragment.y, fragment.depth, fragment.weights))
+        });
+
+        // Rasterizing within bounds that split the target gives exactly the same fragments.
+        let mut split = Vec::new();
+        for bounds in [
+            Rect::new(0, 0, 7, 16),
+            Rect::new(7, 0, 13, 5),
+            Rect::new(7, 5, 13, 11),
+        ] {
+            rasterize_triangle(&poly, bounds, |fragment| {
+                assert!(bounds.contains(fragment.x, fragment.y));
+                split.push((fragment.x, fragment.y, fragment.depth, fragment.weights))
+            });
+        }
+
+        let key = |fragment: &(u32, u32, f64, [f64; 3])| (fragment.1, fragment.0);
+        whole.sort_by_key(key);
+        split.sort_by_key(key);
+        assert!(!whole.is_empty());
+        assert_eq!(whole, split);
+    }
+}
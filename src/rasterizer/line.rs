@@ -0,0 +1,312 @@
+//! Implementation of a line rasterizer that draws aliased lines by stepping along their major axis, or anti-aliased
+//! lines with Xiaolin Wu's algorithm.
+//!
+//! Lines are clipped to the target before they're rasterized and depth is interpolated linearly along them. Pixel
+//! centres lie half way between integer screen coordinates, as they do for triangles.
+//!
+
+use super::view_distance;
+use crate::mesh::{geometry::Dim, RefEdge, Vertex};
+use std::mem::swap;
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// A pixel covered by a line.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct LineFragment {
+    pub x: u32,
+    pub y: u32,
+    pub depth: f64,
+
+    /// The fraction of the pixel covered by the line, between 0 and 1. Always 1 for aliased lines.
+    pub coverage: f64,
+
+    /// How far along the line the pixel is in screen space, from 0 at its first vertex to 1 at its second.
+    pub t: f64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Implementations /////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl LineFragment {
+    /// Return the fragment's distance from the camera, interpolated from the view space positions the pipeline
+    /// stores for the edge's verticies. The edge's verticies must hold 1/w in their W component, which is used to
+    /// correct the interpolation for perspective.
+    ///
+    pub fn view_distance(&self, edge: &RefEdge) -> f64 {
+        let [from, to] = edge.verticies;
+        let mut weights = [(1.0 - self.t) * from[Dim::W], self.t * to[Dim::W]];
+        let sum: f64 = weights.iter().sum();
+        if sum != 0.0 {
+            weights.iter_mut().for_each(|weight| *weight /= sum);
+        }
+        view_distance(&weights, &edge.verticies, &edge.attributes, edge.layout)
+    }
+}
+
+/// Rasterize a line between 2 screen space verticies, calling f for each pixel within the given width and height
+/// that it covers.
+///
+pub fn rasterize_line<F>(
+    from: &Vertex,
+    to: &Vertex,
+    width: u32,
+    height: u32,
+    anti_aliased: bool,
+    mut f: F,
+) where
+    F: FnMut(LineFragment),
+{
+    // The last value of each endpoint tracks how far along the original line it is once the line is clipped.
+    let endpoints = clip_line(
+        [from[Dim::X], from[Dim::Y], from[Dim::Z], 0.0],
+        [to[Dim::X], to[Dim::Y], to[Dim::Z], 1.0],
+        width as f64,
+        height as f64,
+    );
+    let (mut start, mut end) = match endpoints {
+        Some(endpoints) => endpoints,
+        None => return,
+    };
+
+    // Move pixel centres onto integer coordinates.
+    for coordinate in start[0..2].iter_mut().chain(end[0..2].iter_mut()) {
+        *coordinate -= 0.5;
+    }
+
+    // Step along X, swapping the axes of steep lines.
+    let steep = (end[1] - start[1]).abs() > (end[0] - start[0]).abs();
+    if steep {
+        start.swap(0, 1);
+        end.swap(0, 1);
+    }
+    if start[0] > end[0] {
+        swap(&mut start, &mut end);
+    }
+
+    let mut plot = |major: i64, minor: i64, coverage: f64| {
+        let (x, y) = if steep {
+            (minor, major)
+        } else {
+            (major, minor)
+        };
+        if coverage > 0.0 && x >= 0 && y >= 0 && x < width as i64 && y < height as i64 {
+            let length = end[0] - start[0];
+            let t = if length == 0.0 {
+                0.0
+            } else {
+                ((major as f64 - start[0]) / length).clamp(0.0, 1.0)
+            };
+
+            f(LineFragment {
+                x: x as u32,
+                y: y as u32,
+                depth: start[2] + ((end[2] - start[2]) * t),
+                coverage,
+                t: start[3] + ((end[3] - start[3]) * t),
+            });
+        }
+    };
+
+    let gradient = if end[0] == start[0] {
+        0.0
+    } else {
+        (end[1] - start[1]) / (end[0] - start[0])
+    };
+    let minor_at = |major: f64| start[1] + (gradient * (major - start[0]));
+    let (first, last) = (start[0].round() as i64, end[0].round() as i64);
+
+    if !anti_aliased {
+        for major in first..=last {
+            plot(major, minor_at(major as f64).round() as i64, 1.0);
+        }
+        return;
+    }
+
+    // Each endpoint only covers the part of its pixel that the line reaches.
+    let first_gap = 1.0 - fract(start[0] + 0.5);
+    let last_gap = fract(end[0] + 0.5);
+    let (first_gap, last_gap) = if first == last {
+        (end[0] - start[0], 0.0)
+    } else {
+        (first_gap, last_gap)
+    };
+
+    for (major, gap) in [(first, first_gap), (last, last_gap)] {
+        let minor = minor_at(major as f64);
+        plot(major, minor.floor() as i64, (1.0 - fract(minor)) * gap);
+        plot(major, minor.floor() as i64 + 1, fract(minor) * gap);
+    }
+
+    // Split each pixel's coverage between the 2 pixels the line passes between.
+    for major in (first + 1)..last {
+        let minor = minor_at(major as f64);
+        plot(major, minor.floor() as i64, 1.0 - fract(minor));
+        plot(major, minor.floor() as i64 + 1, fract(minor));
+    }
+}
+
+/// Clip a line to the rectangle from the origin to the given width and height using the Liang-Barsky algorithm,
+/// returning its new endpoints with the values after X and Y interpolated along it, or None if it lies entirely
+/// outside.
+///
+fn clip_line<const N: usize>(
+    from: [f64; N],
+    to: [f64; N],
+    width: f64,
+    height: f64,
+) -> Option<([f64; N], [f64; N])> {
+    let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+    let (mut t0, mut t1) = (0.0f64, 1.0f64);
+
+    // Each boundary as the rate the line moves towards its outside, and the distance to it from the start.
+    for (p, q) in [
+        (-dx, from[0]),
+        (dx, width - from[0]),
+        (-dy, from[1]),
+        (dy, height - from[1]),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+
+    let point = |t: f64| {
+        let mut point = from;
+        point
+            .iter_mut()
+            .zip(to.iter())
+            .for_each(|(coordinate, to)| *coordinate += (to - *coordinate) * t);
+        point
+    };
+    Some((point(t0), point(t1)))
+}
+
+/// Return the fractional part of a number, which is always positive.
+///
+fn fract(value: f64) -> f64 {
+    value - value.floor()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{Attribute, AttributeLayout};
+
+    fn rasterize(from: [f64; 3], to: [f64; 3], anti_aliased: bool) -> Vec<LineFragment> {
+        let mut fragments = Vec::new();
+        rasterize_line(
+            &Vertex::new([from[0], from[1], from[2], 1.0]),
+            &Vertex::new([to[0], to[1], to[2], 1.0]),
+            10,
+            10,
+            anti_aliased,
+            |fragment| fragments.push(fragment),
+        );
+        fragments
+    }
+
+    #[test]
+    fn test_clip_line() {
+        let clipped = clip_line([-5.0, 5.0, 0.0], [15.0, 5.0, 20.0], 10.0, 10.0);
+        assert_eq!(clipped, Some(([0.0, 5.0, 5.0], [10.0, 5.0, 15.0])));
+
+        let inside = clip_line([1.0, 1.0, 0.0], [2.0, 3.0, 0.0], 10.0, 10.0);
+        assert_eq!(inside, Some(([1.0, 1.0, 0.0], [2.0, 3.0, 0.0])));
+
+        assert_eq!(
+            clip_line([-5.0, 5.0, 0.0], [-1.0, 15.0, 0.0], 10.0, 10.0),
+            None
+        );
+        assert_eq!(
+            clip_line([2.0, 11.0, 0.0], [8.0, 11.0, 0.0], 10.0, 10.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_aliased_line() {
+        let fragments = rasterize([1.5, 2.5, 0.0], [5.5, 4.5, 8.0], false);
+
+        // One pixel for each column, without gaps.
+        let pixels: Vec<(u32, u32)> = fragments.iter().map(|f| (f.x, f.y)).collect();
+        assert_eq!(pixels, [(1, 2), (2, 3), (3, 3), (4, 4), (5, 4)]);
+        assert_eq!(fragments[2].depth, 4.0);
+        assert!(fragments.iter().all(|fragment| fragment.coverage == 1.0));
+
+        // Steep lines step along Y instead.
+        let fragments = rasterize([2.5, 0.5, 0.0], [3.5, 8.5, 0.0], false);
+        assert_eq!(fragments.len(), 9);
+    }
+
+    #[test]
+    fn test_anti_aliased_coverage() {
+        // A line along the boundary between 2 rows covers each half equally. It starts and ends at pixel centres,
+        // so only covers half of the pixels at its ends.
+        let fragments = rasterize([2.5, 4.0, 0.0], [7.5, 4.0, 0.0], true);
+        assert_eq!(fragments.len(), 12);
+        for fragment in &fragments {
+            assert!(fragment.y == 3 || fragment.y == 4);
+            let expected = if fragment.x == 2 || fragment.x == 7 {
+                0.25
+            } else {
+                0.5
+            };
+            assert_eq!(fragment.coverage, expected);
+        }
+
+        // The coverage of each column of a diagonal line adds up to the whole pixel.
+        let fragments = rasterize([0.5, 0.5, 0.0], [8.5, 3.5, 0.0], true);
+        for x in 1..8 {
+            let coverage: f64 = fragments
+                .iter()
+                .filter(|fragment| fragment.x == x)
+                .map(|fragment| fragment.coverage)
+                .sum();
+            assert!((coverage - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_view_distance() {
+        let from = Vertex::new([0.5, 0.5, 0.0, 1.0 / 300.0]);
+        let to = Vertex::new([8.5, 0.5, 0.0, 1.0 / 100.0]);
+        let edge = RefEdge::new(&from, &to);
+        let mut fragments = Vec::new();
+        rasterize_line(&from, &to, 10, 10, false, |fragment| {
+            fragments.push(fragment)
+        });
+
+        // Half way across the screen, 1/w is half way between the verticies' and the fragment is only a quarter of the
+        // way along the edge in view space.
+        let fragment = fragments.iter().find(|fragment| fragment.x == 4).unwrap();
+        assert_eq!(fragment.t, 0.5);
+        assert!((fragment.view_distance(&edge) - 150.0).abs() < 1e-9);
+
+        // Stored view positions are used over w.
+        let layout = AttributeLayout::new(&[Attribute::ViewPosition]);
+        let positions = [[0.0, 0.0, 400.0], [400.0, 0.0, 400.0]];
+        let edge = edge.with_attributes([&positions[0], &positions[1]], &layout);
+        assert!((fragment.view_distance(&edge) - 500.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,316 @@
+//! Implementations of the algorithms used to rasterize screen space polygons.
+//!
+
+mod edge_table;
+mod half_space;
+mod line;
+mod sampling;
+
+use crate::mesh::{geometry::Dim, Attribute, AttributeLayout, RefPoly, Vertex, MAX_STRIDE};
+
+pub use self::{
+    edge_table::EdgeTable,
+    half_space::{rasterize_triangle, rasterize_triangle_samples},
+    line::rasterize_line,
+    sampling::{AntiAliasing, SampleCount, SamplePattern, MAX_SAMPLES},
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// The algorithm used to fill polygons.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum RasterMethod {
+    /// Walk the polygon's edges into a table of spans.
+    EdgeTable,
+    /// Test each pixel against the polygon's edge functions.
+    HalfSpace,
+}
+
+/// A rectangle of pixels, given by its bottom left pixel and its size.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A pixel covered by a polygon.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct Fragment {
+    pub x: u32,
+    pub y: u32,
+    pub depth: f64,
+
+    /// Perspective correct barycentric weights of the polygon's verticies.
+    pub weights: [f64; 3],
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Rect {
+    /// Return a new rectangle with its bottom left pixel at the given coordinates.
+    ///
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+impl Fragment {
+    /// Return a new fragment given its depth and the screen space barycentric weights of a polygon's verticies.
+    /// The polygon's verticies must hold 1/w in their W component, which is used to correct the weights for
+    /// perspective.
+    ///
+    pub fn new(
+        x: u32,
+        y: u32,
+        depth: f64,
+        screen_weights: [f64; 3],
+        polygon: &RefPoly,
+    ) -> Fragment {
+        Fragment {
+            x,
+            y,
+            depth,
+            weights: perspective_correct(screen_weights, polygon),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Rect {
+    /// Return the X coordinate one past the rectangle's right hand column.
+    ///
+    pub fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    /// Return the Y coordinate one past the rectangle's top row.
+    ///
+    pub fn top(&self) -> u32 {
+        self.y + self.height
+    }
+
+    /// Return true if a pixel lies inside the rectangle.
+    ///
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        (self.x..self.right()).contains(&x) && (self.y..self.top()).contains(&y)
+    }
+}
+
+impl Fragment {
+    /// Interpolate a value given for each of the polygon's verticies.
+    ///
+    pub fn interpolate<const N: usize>(&self, values: [[f64; N]; 3]) -> [f64; N] {
+        let mut result = [0.0; N];
+        for (weight, value) in self.weights.iter().zip(values.iter()) {
+            result
+                .iter_mut()
+                .zip(value.iter())
+                .for_each(|(result, value)| *result += weight * value);
+        }
+        result
+    }
+
+    /// Interpolate the polygon's vertex attributes. The values are stored in the order given by the polygon's
+    /// layout and any unused values are left as 0.
+    ///
+    pub fn interpolate_attributes(&self, polygon: &RefPoly) -> [f64; MAX_STRIDE] {
+        let mut result = [0.0; MAX_STRIDE];
+        for (weight, values) in self.weights.iter().zip(polygon.attributes.iter()) {
+            result
+                .iter_mut()
+                .zip(values.iter())
+                .for_each(|(result, value)| *result += weight * value);
+        }
+        result
+    }
+
+    /// Return the fragment's distance from the camera, interpolated from the view space positions the pipeline
+    /// stores for the polygon's verticies.
+    ///
+    pub fn view_distance(&self, polygon: &RefPoly) -> f64 {
+        view_distance(
+            &self.weights,
+            &polygon.verticies,
+            &polygon.attributes,
+            polygon.layout,
+        )
+    }
+
+    /// Return the perspective correct weights of the polygon's verticies at a point offset from the fragment in
+    /// screen space. Used to find how quickly interpolated values change between neighbouring pixels.
+    ///
+    pub fn offset_weights(&self, polygon: &RefPoly, dx: f64, dy: f64) -> [f64; 3] {
+        let [p0, p1, p2] = polygon.verticies;
+        let area = ((p1[Dim::X] - p0[Dim::X]) * (p2[Dim::Y] - p0[Dim::Y]))
+            - ((p2[Dim::X] - p0[Dim::X]) * (p1[Dim::Y] - p0[Dim::Y]));
+        if area == 0.0 {
+            return self.weights;
+        }
+
+        // Undo the perspective correction to get back to the screen space weights.
+        let mut screen_weights = [0.0; 3];
+        for (screen_weight, (weight, vertex)) in screen_weights
+            .iter_mut()
+            .zip(self.weights.iter().zip(polygon.verticies.iter()))
+        {
+            *screen_weight = weight / vertex[Dim::W];
+        }
+        let sum: f64 = screen_weights.iter().sum();
+        if sum != 0.0 {
+            screen_weights.iter_mut().for_each(|weight| *weight /= sum);
+        }
+
+        // Screen space weights change linearly across the polygon.
+        let gradients = [
+            (p1[Dim::Y] - p2[Dim::Y], p2[Dim::X] - p1[Dim::X]),
+            (p2[Dim::Y] - p0[Dim::Y], p0[Dim::X] - p2[Dim::X]),
+            (p0[Dim::Y] - p1[Dim::Y], p1[Dim::X] - p0[Dim::X]),
+        ];
+        for (weight, (x_gradient, y_gradient)) in screen_weights.iter_mut().zip(gradients) {
+            *weight += ((x_gradient * dx) + (y_gradient * dy)) / area;
+        }
+
+        perspective_correct(screen_weights, polygon)
+    }
+}
+
+/// Return the distance from the camera of a point on a primitive, given the perspective correct weights of its
+/// verticies. The length of the view space position interpolated from the verticies' attributes is used, falling back
+/// to the depth interpolated from the w of each vertex if a vertex shader didn't pass the positions on. That's only
+/// the depth in front of the camera, and only for perspective projections.
+///
+fn view_distance(
+    weights: &[f64],
+    verticies: &[&Vertex],
+    attributes: &[&[f64]],
+    layout: &AttributeLayout,
+) -> f64 {
+    match layout.offset(Attribute::ViewPosition) {
+        Some(offset) => {
+            let mut position = [0.0; 3];
+            for (weight, values) in weights.iter().zip(attributes.iter()) {
+                position
+                    .iter_mut()
+                    .zip(values[offset..(offset + 3)].iter())
+                    .for_each(|(coordinate, value)| *coordinate += weight * value);
+            }
+            let [x, y, z] = position;
+            ((x * x) + (y * y) + (z * z)).sqrt()
+        }
+        None => weights
+            .iter()
+            .zip(verticies.iter())
+            .map(|(weight, vertex)| weight / vertex[Dim::W])
+            .sum(),
+    }
+}
+
+/// Correct screen space barycentric weights for perspective, given a polygon whose verticies hold 1/w in their W
+/// component.
+///
+fn perspective_correct(screen_weights: [f64; 3], polygon: &RefPoly) -> [f64; 3] {
+    let mut weights = [0.0; 3];
+    weights
+        .iter_mut()
+        .zip(screen_weights.iter().zip(polygon.verticies.iter()))
+        .for_each(|(weight, (screen_weight, vertex))| *weight = screen_weight * vertex[Dim::W]);
+
+    let sum: f64 = weights.iter().sum();
+    if sum != 0.0 {
+        weights.iter_mut().for_each(|weight| *weight /= sum);
+    }
+    weights
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::geometry::Vector;
+
+    #[test]
+    fn test_perspective_correct_weights() {
+        // A line of verticies where the first is 3 times further from the camera than the others.
+        let verts = [
+            Vertex::new([0.0, 0.0, 0.0, 1.0 / 300.0]),
+            Vertex::new([8.0, 0.0, 0.0, 1.0 / 100.0]),
+            Vertex::new([0.0, 8.0, 0.0, 1.0 / 100.0]),
+        ];
+        let normal = Vector::new([0, 0, -1]);
+        let poly = RefPoly::new(&verts[0], &verts[1], &verts[2], &normal);
+
+        // Half way across the screen between the first 2 verticies is only a quarter of the way in view space.
+        let fragment = Fragment::new(4, 0, 0.0, [0.5, 0.5, 0.0], &poly);
+        assert!((fragment.weights[0] - 0.25).abs() < 1e-9);
+        assert!((fragment.weights[1] - 0.75).abs() < 1e-9);
+
+        let [u, v] = fragment.interpolate([[0.0, 1.0], [1.0, 1.0], [0.0, 0.0]]);
+        assert!((u - 0.75).abs() < 1e-9);
+        assert!((v - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_view_distance() {
+        let verts = [
+            Vertex::new([0.0, 0.0, 0.0, 1.0 / 300.0]),
+            Vertex::new([8.0, 0.0, 0.0, 1.0 / 100.0]),
+            Vertex::new([0.0, 8.0, 0.0, 1.0 / 100.0]),
+        ];
+        let normal = Vector::new([0, 0, -1]);
+        let poly = RefPoly::new(&verts[0], &verts[1], &verts[2], &normal);
+
+        // Half way across the screen between the first 2 verticies, 1/w is half way between theirs.
+        let fragment = Fragment::new(4, 0, 0.0, [0.5, 0.5, 0.0], &poly);
+        assert!((fragment.view_distance(&poly) - 150.0).abs() < 1e-9);
+
+        // Stored view positions are used over w, as w is only the depth, and doesn't follow it for orthographic
+        // projections.
+        let layout = AttributeLayout::new(&[Attribute::ViewPosition]);
+        let positions = [[0.0, 0.0, 400.0], [400.0, 0.0, 400.0], [0.0, 400.0, 400.0]];
+        let poly = poly.with_attributes([&positions[0], &positions[1], &positions[2]], &layout);
+        assert!((fragment.view_distance(&poly) - 500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_offset_weights() {
+        let verts = [
+            Vertex::new([0.0, 0.0, 0.0, 1.0 / 300.0]),
+            Vertex::new([8.0, 0.0, 0.0, 1.0 / 100.0]),
+            Vertex::new([0.0, 8.0, 0.0, 1.0 / 100.0]),
+        ];
+        let normal = Vector::new([0, 0, -1]);
+        let poly = RefPoly::new(&verts[0], &verts[1], &verts[2], &normal);
+
+        // Moving 2 pixels right from the first vertex matches a fragment created there.
+        let fragment = Fragment::new(2, 0, 0.0, [0.75, 0.25, 0.0], &poly);
+        let expected = Fragment::new(4, 0, 0.0, [0.5, 0.5, 0.0], &poly);
+        let weights = fragment.offset_weights(&poly, 2.0, 0.0);
+        for (weight, expected) in weights.iter().zip(expected.weights.iter()) {
+            assert!((weight - expected).abs() < 1e-9);
+        }
+
+        let weights = fragment.offset_weights(&poly, -2.0, 4.0);
+        assert!((weights[0] - 0.25).abs() < 1e-9);
+        assert!((weights[1]).abs() < 1e-9);
+    }
+}
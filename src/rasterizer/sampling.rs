@@ -0,0 +1,173 @@
+//! The anti-aliasing modes a render target can use, and the positions within each pixel that they sample coverage at.
+//!
+//! Supersampling shades every covered sample separately, while multisampling tests coverage and depth for each sample
+//! but only shades each pixel once. Either way the samples are averaged into the final colour of the pixel when the
+//! target is resolved.
+//!
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// The most samples a pixel can have.
+pub const MAX_SAMPLES: usize = 8;
+
+/// How many samples each pixel has.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SampleCount {
+    X2,
+    X4,
+    X8,
+}
+
+/// How a pixel's samples are arranged.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SamplePattern {
+    /// An evenly spaced grid. Near horizontal and vertical edges only get as many levels of coverage as there are
+    /// rows or columns of samples.
+    Grid,
+    /// A grid rotated so that no 2 samples share a row or column, which gives edges close to horizontal or vertical
+    /// a level of coverage for every sample.
+    RotatedGrid,
+}
+
+/// How a render target smooths the edges of the polygons drawn into it.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum AntiAliasing {
+    /// Sample the centre of each pixel.
+    Off,
+    /// Test coverage and depth and run the fragment shader for each sample.
+    Supersample(SampleCount, SamplePattern),
+    /// Test coverage and depth for each sample, but run the fragment shader once for each pixel.
+    Multisample(SampleCount, SamplePattern),
+}
+
+/// Sample positions within a pixel, with the pixel's bottom left corner at the origin.
+const CENTRE: [[f64; 2]; 1] = [[0.5, 0.5]];
+
+const GRID_2: [[f64; 2]; 2] = [[0.25, 0.5], [0.75, 0.5]];
+const GRID_4: [[f64; 2]; 4] = [[0.25, 0.25], [0.75, 0.25], [0.25, 0.75], [0.75, 0.75]];
+const GRID_8: [[f64; 2]; 8] = [
+    [0.125, 0.25],
+    [0.375, 0.25],
+    [0.625, 0.25],
+    [0.875, 0.25],
+    [0.125, 0.75],
+    [0.375, 0.75],
+    [0.625, 0.75],
+    [0.875, 0.75],
+];
+
+// The standard rotated patterns used by GPUs, given in sixteenths of a pixel from its centre.
+const ROTATED_2: [[f64; 2]; 2] = sixteenths([[4, 4], [-4, -4]]);
+const ROTATED_4: [[f64; 2]; 4] = sixteenths([[-2, -6], [6, -2], [-6, 2], [2, 6]]);
+const ROTATED_8: [[f64; 2]; 8] = sixteenths([
+    [1, -3],
+    [-1, 3],
+    [5, 1],
+    [-3, -5],
+    [-5, 5],
+    [-7, -1],
+    [3, 7],
+    [7, -7],
+]);
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl AntiAliasing {
+    /// Return the position of each sample within a pixel, with the pixel's bottom left corner at the origin.
+    ///
+    pub fn sample_positions(&self) -> &'static [[f64; 2]] {
+        let (count, pattern) = match *self {
+            AntiAliasing::Off => return &CENTRE,
+            AntiAliasing::Supersample(count, pattern) => (count, pattern),
+            AntiAliasing::Multisample(count, pattern) => (count, pattern),
+        };
+
+        match (count, pattern) {
+            (SampleCount::X2, SamplePattern::Grid) => &GRID_2,
+            (SampleCount::X4, SamplePattern::Grid) => &GRID_4,
+            (SampleCount::X8, SamplePattern::Grid) => &GRID_8,
+            (SampleCount::X2, SamplePattern::RotatedGrid) => &ROTATED_2,
+            (SampleCount::X4, SamplePattern::RotatedGrid) => &ROTATED_4,
+            (SampleCount::X8, SamplePattern::RotatedGrid) => &ROTATED_8,
+        }
+    }
+
+    /// Return the number of samples in each pixel.
+    ///
+    pub fn samples(&self) -> usize {
+        self.sample_positions().len()
+    }
+}
+
+/// Convert sample offsets from a pixel's centre in sixteenths of a pixel into positions within the pixel.
+///
+const fn sixteenths<const N: usize>(offsets: [[i32; 2]; N]) -> [[f64; 2]; N] {
+    let mut positions = [[0.0; 2]; N];
+    let mut i = 0;
+    while i < N {
+        positions[i] = [
+            0.5 + (offsets[i][0] as f64 / 16.0),
+            0.5 + (offsets[i][1] as f64 / 16.0),
+        ];
+        i += 1;
+    }
+    positions
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_positions() {
+        let counts = [
+            (SampleCount::X2, 2),
+            (SampleCount::X4, 4),
+            (SampleCount::X8, 8),
+        ];
+        for (count, expected) in counts {
+            for pattern in [SamplePattern::Grid, SamplePattern::RotatedGrid] {
+                let positions = AntiAliasing::Multisample(count, pattern).sample_positions();
+                assert_eq!(positions.len(), expected);
+
+                // Every sample lies inside the pixel and the samples are centred on the pixel's centre.
+                assert!(positions
+                    .iter()
+                    .flatten()
+                    .all(|&coordinate| (0.0..1.0).contains(&coordinate)));
+                for axis in 0..2 {
+                    let mean: f64 = positions.iter().map(|position| position[axis]).sum::<f64>()
+                        / expected as f64;
+                    assert!((mean - 0.5).abs() < 1e-9);
+                }
+            }
+        }
+
+        assert_eq!(AntiAliasing::Off.samples(), 1);
+    }
+
+    #[test]
+    fn test_rotated_grid_columns() {
+        // No 2 samples of a rotated grid share a row or column.
+        for count in [SampleCount::X2, SampleCount::X4, SampleCount::X8] {
+            let positions =
+                AntiAliasing::Supersample(count, SamplePattern::RotatedGrid).sample_positions();
+            for (i, a) in positions.iter().enumerate() {
+                for b in &positions[(i + 1)..] {
+                    assert!(a[0] != b[0] && a[1] != b[1]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Fragment shaders for effects that the fixed function pipeline can't produce.
+//!
+
+use super::{FragmentInput, FragmentShader};
+use crate::mesh::{
+    geometry::{Point, Vector},
+    Attribute, RefPoly,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Fragment shader that lights surfaces with a few flat bands of colour from a single directional light and draws
+/// dark outlines around their silhouettes.
+///
+pub struct ToonShader {
+    pub colour: [f64; 3],
+    /// The direction the light travels in.
+    pub direction: Vector<3>,
+    pub bands: u32,
+    /// Fragments whose normal is closer than this to perpendicular to the view direction, measured as the cosine of
+    /// the angle between them, are drawn as outline. 0 disables outlines.
+    pub outline: f64,
+    /// The world space position of the camera, which outlines are found from.
+    pub eye: Point<3>,
+}
+
+/// Fragment shader that colours each fragment by its world space normal, mapping each component from -1 to 1 onto a
+/// colour channel from 0 to 1.
+///
+pub struct NormalShader;
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl ToonShader {
+    /// Return a new shader with the given colour, lit from a direction in the given number of bands, with outlines
+    /// seen from the origin of world space.
+    ///
+    pub fn new(colour: [f64; 3], direction: Vector<3>, bands: u32) -> ToonShader {
+        ToonShader {
+            colour,
+            direction,
+            bands,
+            outline: 0.3,
+            eye: Point::default(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl FragmentShader for ToonShader {
+    type PolygonData = ();
+
+    fn prepare(&self, _polygon: &RefPoly) {}
+
+    fn shade(&self, _data: &(), input: &FragmentInput) -> Option<[f64; 4]> {
+        let normal = input.normal();
+
+        if let Some(position) = input.varying(Attribute::Position) {
+            let to_eye = Point::new(position).vector_to(&self.eye).normalise();
+            if normal.dot(&to_eye).abs() < self.outline {
+                return Some([0.0, 0.0, 0.0, 1.0]);
+            }
+        }
+
+        let lambert = normal.dot(&-self.direction.normalise()).max(0.0);
+        let bands = self.bands.max(1) as f64;
+        let level = (lambert * bands).ceil().max(1.0) / bands;
+
+        let [r, g, b] = self.colour.map(|channel| channel * level);
+        Some([r, g, b, 1.0])
+    }
+}
+
+impl FragmentShader for NormalShader {
+    type PolygonData = ();
+
+    fn prepare(&self, _polygon: &RefPoly) {}
+
+    fn shade(&self, _data: &(), input: &FragmentInput) -> Option<[f64; 4]> {
+        let [x, y, z] = input.normal().0.map(|component| (component + 1.0) / 2.0);
+        Some([x, y, z, 1.0])
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mesh::{AttributeLayout, Vertex},
+        rasterizer::Fragment,
+    };
+
+    #[test]
+    fn test_toon_bands() {
+        let vertex = Vertex::new([0.0, 0.0, 0.0, 1.0]);
+        let normal = Vector::new([0, 0, -1]);
+        let layout = AttributeLayout::new(&[Attribute::Normal]);
+        let facing = [0.0, 0.0, -1.0];
+        let tilted = [0.0, 0.8, -0.6];
+        let polygon = RefPoly::new(&vertex, &vertex, &vertex, &normal)
+            .with_attributes([&facing, &tilted, &tilted], &layout);
+
+        let mut shader = ToonShader::new([1.0, 0.5, 0.0], Vector::new([0, 0, 1]), 4);
+        shader.outline = 0.0;
+        let shade = |weights| {
+            let fragment = Fragment {
+                x: 0,
+                y: 0,
+                depth: 0.0,
+                weights,
+            };
+            shader.shade(&(), &FragmentInput::new(&fragment, &polygon))
+        };
+
+        // Facing the light is fully lit, and at 0.6 the lighting is rounded up to the band above.
+        assert_eq!(shade([1.0, 0.0, 0.0]), Some([1.0, 0.5, 0.0, 1.0]));
+        assert_eq!(shade([0.0, 0.5, 0.5]), Some([0.75, 0.375, 0.0, 1.0]));
+    }
+}
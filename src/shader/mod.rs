@@ -0,0 +1,150 @@
+//! Programmable vertex and fragment shaders that plug into the software pipeline.
+//!
+//! A vertex shader moves each view space vertex into homogeneous clip space and writes the varyings that are clipped
+//! and interpolated across each polygon along with it. A fragment shader is given the interpolated varyings of each
+//! pixel a polygon covers and returns its colour, or discards it. The standard shaders reproduce the fixed function
+//! pipeline, and the effect shaders show what else can be done with them.
+//!
+
+mod effects;
+mod standard;
+
+use crate::{
+    framebuffer::BlendMode,
+    mesh::{
+        geometry::{Dim, Vector},
+        Attribute, AttributeLayout, RefPoly, Vertex, MAX_STRIDE,
+    },
+    rasterizer::Fragment,
+};
+
+pub use self::{
+    effects::{NormalShader, ToonShader},
+    standard::{LitShader, ProjectionShader, ShadingMode},
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// A view space vertex and its attributes, given to a vertex shader. The position attribute still holds the vertex's
+/// world space position.
+///
+pub struct VertexInput<'a> {
+    pub vertex: &'a Vertex,
+    pub attributes: &'a [f64],
+    #[allow(dead_code)]
+    pub layout: &'a AttributeLayout,
+}
+
+/// A fragment and its interpolated varyings, given to a fragment shader.
+///
+pub struct FragmentInput<'a> {
+    pub fragment: &'a Fragment,
+    pub polygon: &'a RefPoly<'a>,
+
+    /// The polygon's varyings interpolated at the fragment, stored in the order given by the polygon's layout.
+    pub varyings: [f64; MAX_STRIDE],
+}
+
+/// Trait for a program run on each vertex of a mesh once it has been moved into the view space of a camera.
+///
+pub trait VertexShader {
+    /// Return the layout of the varyings written for each vertex, given the layout of the mesh's vertex attributes.
+    /// The attributes are passed through unchanged by default.
+    ///
+    fn varyings(&self, attributes: &AttributeLayout) -> AttributeLayout {
+        *attributes
+    }
+
+    /// Return a vertex transformed into homogeneous clip space, and write its varyings in the layout returned by
+    /// varyings.
+    ///
+    fn shade(&self, input: &VertexInput, varyings: &mut [f64]) -> Vertex;
+
+    /// Return true if the clip space depths written by the shader are reversed, with the near plane at w and the far
+    /// plane at 0. Depths run from the near plane at 0 to the far plane at w by default.
+    ///
+    fn reversed_z(&self) -> bool {
+        false
+    }
+}
+
+/// Trait for a program run on each fragment of a polygon that passes the depth test.
+///
+pub trait FragmentShader {
+    /// Values worked out once for each polygon and shared by all of its fragments.
+    ///
+    type PolygonData;
+
+    /// Return the values shared by a screen space polygon's fragments, before any of them are shaded.
+    ///
+    fn prepare(&self, polygon: &RefPoly) -> Self::PolygonData;
+
+    /// Return the RGBA colour of a fragment with each channel between 0 and 1, or None to discard it. Discarded
+    /// fragments leave both the colour and depth buffers untouched.
+    ///
+    fn shade(&self, data: &Self::PolygonData, input: &FragmentInput) -> Option<[f64; 4]>;
+
+    /// Return how the shaded fragments are blended into the target. Fragments are opaque by default.
+    ///
+    fn blend_mode(&self) -> BlendMode {
+        BlendMode::Opaque
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl<'a> FragmentInput<'a> {
+    /// Return the input for a fragment, interpolating the polygon's varyings.
+    ///
+    pub fn new(fragment: &'a Fragment, polygon: &'a RefPoly<'a>) -> FragmentInput<'a> {
+        FragmentInput {
+            fragment,
+            polygon,
+            varyings: fragment.interpolate_attributes(polygon),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+impl VertexInput<'_> {
+    /// Return the values of one of the vertex's attributes, if it has it.
+    ///
+    pub fn attribute<const N: usize>(&self, attribute: Attribute) -> Option<[f64; N]> {
+        self.layout.read(self.attributes, attribute)
+    }
+
+    /// Return the vertex's view space coordinates without W.
+    ///
+    pub fn position(&self) -> [f64; 3] {
+        [
+            self.vertex[Dim::X],
+            self.vertex[Dim::Y],
+            self.vertex[Dim::Z],
+        ]
+    }
+}
+
+impl FragmentInput<'_> {
+    /// Return the interpolated values of one of the polygon's varyings, if it has it.
+    ///
+    pub fn varying<const N: usize>(&self, attribute: Attribute) -> Option<[f64; N]> {
+        self.polygon.layout.read(&self.varyings, attribute)
+    }
+
+    /// Return the unit normal at the fragment. This is the interpolated vertex normal if the polygon has one, and
+    /// the polygon's normal otherwise.
+    ///
+    pub fn normal(&self) -> Vector<3> {
+        self.varying(Attribute::Normal)
+            .map_or(*self.polygon.normal, Vector::new)
+            .normalise()
+    }
+}
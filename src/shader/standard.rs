@@ -0,0 +1,259 @@
+//! The standard shaders, which project verticies with a projection matrix and light fragments with a material.
+//!
+
+use super::{FragmentInput, FragmentShader, VertexInput, VertexShader};
+use crate::{
+    framebuffer::BlendMode,
+    lighting::{Light, Material},
+    mesh::{
+        geometry::{Point, Vector},
+        Attribute, Matrix4X4, RefPoly, Vertex,
+    },
+    projection::Projection,
+    rasterizer::Fragment,
+    shadow::ShadowMap,
+    texture::{Filter, Texture},
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// How lighting is evaluated across a polygon.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ShadingMode {
+    /// Light each fragment using the polygon's normal.
+    Flat,
+    /// Light each vertex using its normal and interpolate the lit colours.
+    Gouraud,
+    /// Interpolate the vertex normals and light each fragment.
+    Phong,
+}
+
+/// Vertex shader that applies a projection matrix to each vertex and passes its attributes through unchanged.
+///
+pub struct ProjectionShader {
+    pub projection: Matrix4X4,
+    pub reversed_z: bool,
+}
+
+/// Fragment shader that lights each fragment with a material and a list of lights.
+/// The material's texture is only applied to polygons with texture coordinates. Polygons without vertex normals
+/// use their polygon normal for every vertex.
+///
+pub struct LitShader<'a> {
+    pub material: &'a Material,
+    pub lights: &'a [Light],
+    pub shading: ShadingMode,
+    /// The shadow map of each light, in the same order as the lights. Lights without one aren't shadowed.
+    pub shadows: &'a [Option<ShadowMap>],
+    /// The world space position of the camera, which specular highlights are seen from.
+    pub eye: Point<3>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl ProjectionShader {
+    /// Return a new shader using the given projection matrix, which maps depths from 0 at the near plane to 1 at the
+    /// far plane.
+    ///
+    pub fn new(projection: Matrix4X4) -> ProjectionShader {
+        ProjectionShader {
+            projection,
+            reversed_z: false,
+        }
+    }
+
+    /// Return a new shader using a projection's matrix and depth range.
+    ///
+    pub fn with_projection(projection: &Projection) -> ProjectionShader {
+        ProjectionShader {
+            projection: *projection.matrix(),
+            reversed_z: projection.reversed_z(),
+        }
+    }
+}
+
+impl<'a> LitShader<'a> {
+    /// Return a new shader lighting fragments with the given material and lights, without shadows, seen from the
+    /// origin of world space.
+    ///
+    pub fn new(material: &'a Material, lights: &'a [Light], shading: ShadingMode) -> LitShader<'a> {
+        LitShader {
+            material,
+            lights,
+            shading,
+            shadows: &[],
+            eye: Point::default(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl VertexShader for ProjectionShader {
+    fn shade(&self, input: &VertexInput, varyings: &mut [f64]) -> Vertex {
+        varyings.copy_from_slice(input.attributes);
+        *input.vertex * self.projection
+    }
+
+    fn reversed_z(&self) -> bool {
+        self.reversed_z
+    }
+}
+
+/// Gouraud shading lights the verticies once for each polygon and interpolates the result.
+///
+impl FragmentShader for LitShader<'_> {
+    type PolygonData = [[f64; 4]; 3];
+
+    fn prepare(&self, polygon: &RefPoly) -> [[f64; 4]; 3] {
+        match self.shading {
+            ShadingMode::Gouraud => {
+                light_verticies(polygon, self.material, self.lights, self.shadows, &self.eye)
+            }
+            _ => [[0.0; 4]; 3],
+        }
+    }
+
+    /// The surface colour is the fragment's texture colour multiplied by its interpolated vertex colour, or white if
+    /// the polygon has neither. With Gouraud shading the interpolated vertex colours have already been lit, so only
+    /// the texture is applied.
+    ///
+    fn shade(&self, vertex_colours: &[[f64; 4]; 3], input: &FragmentInput) -> Option<[f64; 4]> {
+        let (polygon, fragment) = (input.polygon, input.fragment);
+
+        let texel = self
+            .material
+            .texture
+            .as_ref()
+            .zip(input.varying::<2>(Attribute::TexCoord))
+            .map(|(texture, uv)| {
+                let lod = match texture.filter {
+                    Filter::Trilinear => texture_lod(polygon, fragment, uv, texture),
+                    _ => 0.0,
+                };
+                texture.sample(uv, lod)
+            })
+            .unwrap_or([1.0; 4]);
+
+        let colour = match self.shading {
+            ShadingMode::Gouraud => {
+                let mut colour = fragment.interpolate(*vertex_colours);
+                colour
+                    .iter_mut()
+                    .zip(texel.iter())
+                    .for_each(|(channel, texel)| *channel *= texel);
+                colour
+            }
+            ShadingMode::Flat | ShadingMode::Phong => {
+                let mut surface = input.varying(Attribute::Colour).unwrap_or([1.0; 4]);
+                surface
+                    .iter_mut()
+                    .zip(texel.iter())
+                    .for_each(|(channel, texel)| *channel *= texel);
+
+                let normal = match self.shading {
+                    ShadingMode::Phong => input
+                        .varying(Attribute::Normal)
+                        .map_or(*polygon.normal, Vector::new),
+                    _ => *polygon.normal,
+                };
+                let position = Point::new(input.varying(Attribute::Position).unwrap_or_default());
+
+                self.material.shade_shadowed(
+                    self.lights,
+                    |light| shadow_visibility(self.shadows, light, &position, &normal),
+                    &position,
+                    &normal,
+                    &self.eye,
+                    surface,
+                )
+            }
+        };
+        Some(colour)
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.material.blend
+    }
+}
+
+/// Return the colour of each of a polygon's verticies lit by a list of lights and shadowed by their shadow maps, using
+/// their vertex colours, normals and positions, as seen from an eye position.
+///
+fn light_verticies(
+    polygon: &RefPoly,
+    material: &Material,
+    lights: &[Light],
+    shadows: &[Option<ShadowMap>],
+    eye: &Point<3>,
+) -> [[f64; 4]; 3] {
+    let layout = polygon.layout;
+    polygon.attributes.map(|attributes| {
+        let surface = layout
+            .read(attributes, Attribute::Colour)
+            .unwrap_or([1.0; 4]);
+        let normal = layout
+            .read(attributes, Attribute::Normal)
+            .map_or(*polygon.normal, Vector::new);
+        let position = Point::new(
+            layout
+                .read(attributes, Attribute::Position)
+                .unwrap_or_default(),
+        );
+
+        material.shade_shadowed(
+            lights,
+            |light| shadow_visibility(shadows, light, &position, &normal),
+            &position,
+            &normal,
+            eye,
+            surface,
+        )
+    })
+}
+
+/// Return the fraction of a light reaching a point, from the light's shadow map if it has one.
+///
+fn shadow_visibility(
+    shadows: &[Option<ShadowMap>],
+    light: usize,
+    position: &Point<3>,
+    normal: &Vector<3>,
+) -> f64 {
+    shadows
+        .get(light)
+        .and_then(Option::as_ref)
+        .map_or(1.0, |shadow_map| shadow_map.visibility(position, normal))
+}
+
+/// Return the mipmap level of detail for a fragment: the base 2 logarithm of the number of texels crossed when moving
+/// 1 pixel across the screen.
+///
+fn texture_lod(polygon: &RefPoly, fragment: &Fragment, uv: [f64; 2], texture: &Texture) -> f64 {
+    let offset = polygon.layout.offset(Attribute::TexCoord).unwrap();
+    let size = [texture.width() as f64, texture.height() as f64];
+    let texel_distance = |dx: f64, dy: f64| {
+        let weights = fragment.offset_weights(polygon, dx, dy);
+        let mut distance = 0.0;
+        for (i, size) in size.iter().enumerate() {
+            let coordinate: f64 = weights
+                .iter()
+                .zip(polygon.attributes.iter())
+                .map(|(weight, attributes)| weight * attributes[offset + i])
+                .sum();
+            distance += ((coordinate - uv[i]) * size).powi(2);
+        }
+        distance.sqrt()
+    };
+
+    texel_distance(1.0, 0.0)
+        .max(texel_distance(0.0, 1.0))
+        .log2()
+}
@@ -0,0 +1,518 @@
+//! Implementation of shadow maps, which let directional and spot lights cast shadows.
+//!
+//! A shadow map holds the depth of the scene from a light's point of view, drawn by the same rasterizer as the screen.
+//! A surface point is in shadow if something in the map is closer to the light than it is. Each point is tested
+//! against a grid of texels around it and the results are averaged, which is known as percentage closer filtering
+//! and softens the edges of shadows. Directional lights split the view into cascades, each with its own map, so that
+//! shadows close to the camera get more texels than those far away.
+//!
+
+use crate::{
+    camera::Camera,
+    framebuffer::{FrameBuffer, RenderTarget},
+    lighting::Light,
+    mesh::{
+        geometry::{
+            Dim::{W, X, Y, Z},
+            Point, Vector,
+        },
+        screen_depth, Matrix4X4, Mesh, Vertex,
+    },
+    projection::Projection,
+    rasterizer::RasterMethod,
+    shader::ProjectionShader,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// The distance of a spot light's near plane from the light.
+const SPOT_NEAR_PLANE: f64 = 1.0;
+
+/// How a light's shadows are drawn and sampled.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct ShadowSettings {
+    /// The width and height of each map in texels.
+    pub resolution: u32,
+    /// How far surface points are moved towards the light before they're tested, in world units. Stops surfaces
+    /// shadowing themselves where their depth is rounded behind the depth they left in the map.
+    pub bias: f64,
+    /// How far surface points are moved along their normal before they're tested, in world units. Scaled by how
+    /// steeply the light hits the surface, where a single texel covers the largest range of depths.
+    pub normal_bias: f64,
+    /// The number of texels either side of a point that are tested when filtering. 0 gives hard shadows.
+    pub filter_radius: u32,
+    /// The number of cascades directional lights split the view into. Spot lights always have a single map.
+    pub cascades: usize,
+    /// How far from the camera directional lights cast shadows, and how far from a spot light it casts them.
+    pub range: f64,
+    /// How the view is split between cascades, from 0 for evenly spaced splits to 1 for splits that grow
+    /// logarithmically with distance.
+    pub split_lambda: f64,
+}
+
+/// The depths of the scene from a light's point of view, which are used to find how much of the light reaches
+/// points in the scene.
+///
+pub struct ShadowMap {
+    pub settings: ShadowSettings,
+
+    /// The light the map was last rendered for.
+    light: Option<Light>,
+    /// The camera the cascades were last fitted to the view of.
+    camera: Camera,
+    cascades: Vec<Cascade>,
+
+    /// The casters run through the light's pipeline. Kept between casters to reuse its allocations.
+    pipe: Mesh,
+    /// The distances dividing the view between cascades, and the far distance and projection matrix of each cascade.
+    /// Kept between renders to reuse their allocations.
+    splits: Vec<f64>,
+    projections: Vec<(f64, Matrix4X4)>,
+}
+
+/// A single map covering part of a shadow map's light.
+///
+struct Cascade {
+    /// How far from the camera the cascade reaches.
+    far: f64,
+    /// The matrix moving world space into the light's clip space.
+    view_projection: Matrix4X4,
+    buffer: FrameBuffer,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl ShadowSettings {
+    /// Return settings for maps of the given resolution reaching the given range, with a single cascade, softened
+    /// edges and a small bias.
+    ///
+    pub fn new(resolution: u32, range: f64) -> ShadowSettings {
+        ShadowSettings {
+            resolution,
+            bias: 1.0,
+            normal_bias: 2.0,
+            filter_radius: 1,
+            cascades: 1,
+            range,
+            split_lambda: 0.75,
+        }
+    }
+}
+
+impl ShadowMap {
+    /// Return a new shadow map, which doesn't shadow anything until it has been rendered.
+    ///
+    pub fn new(settings: ShadowSettings) -> ShadowMap {
+        ShadowMap {
+            settings,
+            light: None,
+            camera: Camera::new(),
+            cascades: Vec::new(),
+            pipe: Mesh::default(),
+            splits: Vec::new(),
+            projections: Vec::new(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl ShadowMap {
+    /// Render the depths of the casters from a light's point of view. Directional light cascades are fitted around
+    /// the view of a camera using the given projection. Ambient and point lights can't cast shadows, so the map is
+    /// left empty.
+    ///
+    pub fn render(
+        &mut self,
+        light: &Light,
+        casters: &[&Mesh],
+        camera: &Camera,
+        camera_projection: &Projection,
+    ) {
+        let light_camera = match *light {
+            Light::Directional { direction, .. } => {
+                self.directional_cascades(&direction, casters, camera, camera_projection)
+            }
+            Light::Spot {
+                position,
+                direction,
+                outer_angle,
+                ..
+            } => {
+                let target = position + direction;
+                let light_camera = Camera::look_at(&position, &target, &up_vector(&direction));
+                let projection = Matrix4X4::new_projection(
+                    1.0,
+                    SPOT_NEAR_PLANE,
+                    self.settings.range,
+                    (outer_angle * 2.0).min(179.0),
+                );
+                self.projections.clear();
+                self.projections.push((f64::INFINITY, projection));
+                light_camera
+            }
+            Light::Ambient { .. } | Light::Point { .. } => {
+                self.projections.clear();
+                Camera::new()
+            }
+        };
+        self.light = Some(*light);
+        self.camera.clone_from(camera);
+
+        let resolution = self.settings.resolution;
+        let view = light_camera.view_matrix();
+        self.cascades.truncate(self.projections.len());
+        while self.cascades.len() < self.projections.len() {
+            self.cascades.push(Cascade {
+                far: 0.0,
+                view_projection: view,
+                buffer: FrameBuffer::new(resolution, resolution),
+            });
+        }
+
+        let size = [resolution as f64; 2];
+        for (cascade, &(far, projection)) in self.cascades.iter_mut().zip(&self.projections) {
+            cascade.far = far;
+            cascade.view_projection = view * projection;
+            if cascade.buffer.width() != resolution {
+                cascade.buffer.resize(resolution, resolution);
+            }
+            cascade.buffer.clear();
+
+            let shader = ProjectionShader::new(projection);
+            for caster in casters {
+                caster.run_pipeline_with_shader_into(&shader, &light_camera, size, &mut self.pipe);
+                cascade
+                    .buffer
+                    .draw_depth(&self.pipe, RasterMethod::HalfSpace);
+            }
+        }
+    }
+
+    /// Return the fraction of the light that reaches a surface point with the given normal, from 0 in full shadow to
+    /// 1 fully lit. Points outside the map are fully lit.
+    ///
+    pub fn visibility(&self, position: &Point<3>, normal: &Vector<3>) -> f64 {
+        let to_light = match self.light {
+            Some(Light::Directional { direction, .. }) => -direction.normalise(),
+            Some(Light::Spot {
+                position: light_position,
+                ..
+            }) => position.vector_to(&light_position).normalise(),
+            _ => return 1.0,
+        };
+
+        // The point's distance along the camera's view direction picks the cascade.
+        let distance = self.camera.to_view_space(position)[Z];
+        let cascade = match self.cascades.iter().find(|cascade| distance <= cascade.far) {
+            Some(cascade) => cascade,
+            None => return 1.0,
+        };
+
+        // Move the point off the surface, furthest where the light grazes it.
+        let normal = normal.normalise();
+        let cos = normal.dot(&to_light).clamp(-1.0, 1.0);
+        let sin = (1.0 - (cos * cos)).sqrt();
+        let offset =
+            (to_light * self.settings.bias) + &(normal * (self.settings.normal_bias * sin));
+        let mut point = *position;
+        point.translate(&offset);
+
+        let clip = Vertex::new([point[X], point[Y], point[Z], 1.0]) * cascade.view_projection;
+        let w = clip[W];
+        let depth = clip[Z] / w;
+        if w <= 0.0 || !(0.0..=1.0).contains(&depth) {
+            return 1.0;
+        }
+
+        let resolution = self.settings.resolution as f64;
+        let x = ((clip[X] / w) + 1.0) * (resolution / 2.0);
+        let y = ((clip[Y] / w) + 1.0) * (resolution / 2.0);
+        if !(0.0..resolution).contains(&x) || !(0.0..resolution).contains(&y) {
+            return 1.0;
+        }
+
+        cascade.filter(
+            x as u32,
+            y as u32,
+            screen_depth(depth, false),
+            self.settings.filter_radius,
+        )
+    }
+
+    /// Find the distances that divide the view between a light's cascades, starting with the end of the first.
+    ///
+    fn find_cascade_splits(&mut self, near_plane: f64) {
+        let ShadowSettings {
+            range,
+            split_lambda,
+            ..
+        } = self.settings;
+        let cascades = self.settings.cascades.max(1);
+
+        self.splits.clear();
+        self.splits.extend((1..=cascades).map(|i| {
+            let fraction = i as f64 / cascades as f64;
+            let logarithmic = near_plane * (range / near_plane).powf(fraction);
+            let uniform = near_plane + ((range - near_plane) * fraction);
+            (split_lambda * logarithmic) + ((1.0 - split_lambda) * uniform)
+        }));
+    }
+
+    /// Find the far distance and projection matrix of each of a directional light's cascades, and return the camera
+    /// the light is viewed from. Each cascade is an orthographic projection around the bounding sphere of its slice
+    /// of the camera's view, which extends back towards the light to take in every caster. The sphere's centre is
+    /// snapped to whole texels, so that the edges of shadows don't shimmer as the view moves.
+    ///
+    fn directional_cascades(
+        &mut self,
+        direction: &Vector<3>,
+        casters: &[&Mesh],
+        camera: &Camera,
+        camera_projection: &Projection,
+    ) -> Camera {
+        let direction = direction.normalise();
+        let light_camera = Camera::look_at(
+            &Point::default(),
+            &Point::new([direction[X], direction[Y], direction[Z]]),
+            &up_vector(&direction),
+        );
+        let to_light_space = |point: &Point<3>| light_camera.to_view_space(point).0;
+
+        // The closest any caster gets to the light.
+        let casters_near = casters
+            .iter()
+            .filter_map(|caster| caster.world_bounds())
+            .flat_map(|bounds| bounds.corners())
+            .map(|corner| to_light_space(&corner)[2])
+            .fold(f64::MAX, f64::min);
+
+        let near_plane = camera_projection.near_plane();
+        let mut near = near_plane;
+        let resolution = self.settings.resolution.max(1) as f64;
+        self.find_cascade_splits(near_plane);
+        self.projections.clear();
+        self.projections.extend(self.splits.iter().map(|&far| {
+            let corners = camera_projection
+                .view_corners(near, far)
+                .map(|corner| camera.to_world_space(&corner));
+            near = far;
+
+            let mut centre = Point::new([0.0; 3]);
+            for corner in &corners {
+                centre.translate(&(corner.vector_from(&Point::default()) / 8.0));
+            }
+            let radius = corners
+                .iter()
+                .map(|corner| corner.vector_from(&centre).magnitude())
+                .fold(0.0, f64::max);
+
+            let [x, y, z] = to_light_space(&centre);
+            let texel = (radius * 2.0) / resolution;
+            let (x, y) = ((x / texel).round() * texel, (y / texel).round() * texel);
+            let projection = Matrix4X4::new_orthographic(
+                x - radius,
+                x + radius,
+                y - radius,
+                y + radius,
+                casters_near.min(z - radius),
+                z + radius,
+            );
+            (far, projection)
+        }));
+        light_camera
+    }
+}
+
+impl Cascade {
+    /// Return the fraction of texels in a square around a texel whose depths are no closer to the light than a
+    /// depth. Texels beyond the edge of the map repeat those on the edge.
+    ///
+    fn filter(&self, x: u32, y: u32, depth: f64, radius: u32) -> f64 {
+        let (x, y, radius) = (x as i64, y as i64, radius as i64);
+        let last = self.buffer.width() as i64 - 1;
+
+        let mut lit = 0;
+        for texel_y in (y - radius)..=(y + radius) {
+            for texel_x in (x - radius)..=(x + radius) {
+                let (texel_x, texel_y) = (texel_x.clamp(0, last), texel_y.clamp(0, last));
+                if depth >= self.buffer.get_depth(texel_x as u32, texel_y as u32) {
+                    lit += 1;
+                }
+            }
+        }
+        lit as f64 / ((radius * 2) + 1).pow(2) as f64
+    }
+}
+
+/// Return a vector that isn't parallel to a light's direction, to use as the up direction of its view.
+///
+fn up_vector(direction: &Vector<3>) -> Vector<3> {
+    if direction.normalise()[Y].abs() > 0.99 {
+        Vector::new([0, 0, 1])
+    } else {
+        Vector::new([0, 1, 0])
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lighting::Attenuation;
+
+    /// Return a cube floating above the origin, which shadows the ground below it from a light shining down.
+    ///
+    fn occluder() -> Mesh {
+        let mut cube = Mesh::default();
+        cube.load_cube(100.0);
+        cube.physics.position = Point::new([0, 200, 400]);
+        cube
+    }
+
+    fn camera_projection() -> Projection {
+        Projection::perspective(45.0, 4.0 / 3.0, 100.0, 1000.0)
+    }
+
+    /// Render a shadow map from the view of a camera at the origin of world space.
+    ///
+    fn render(map: &mut ShadowMap, light: &Light, casters: &[&Mesh]) {
+        map.render(light, casters, &Camera::default(), &camera_projection());
+    }
+
+    #[test]
+    fn test_directional_shadow() {
+        let cube = occluder();
+        let light = Light::Directional {
+            direction: Vector::new([0, -1, 0]),
+            colour: [1.0, 1.0, 1.0],
+        };
+        let mut settings = ShadowSettings::new(256, 1000.0);
+        settings.filter_radius = 0;
+        let mut map = ShadowMap::new(settings);
+
+        // Nothing is shadowed before the map is rendered.
+        let (ground, up) = (Point::new([0, 0, 400]), Vector::new([0, 1, 0]));
+        assert_eq!(map.visibility(&ground, &up), 1.0);
+        render(&mut map, &light, &[&cube]);
+
+        // The ground below the cube is in shadow, while the ground beside it and the cube's top aren't.
+        assert_eq!(map.visibility(&ground, &up), 0.0);
+        assert_eq!(map.visibility(&Point::new([150, 0, 400]), &up), 1.0);
+        for (x, z) in [(0, 400), (40, 360), (-40, 440)] {
+            assert_eq!(map.visibility(&Point::new([x, 250, z]), &up), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_filtered_edges() {
+        let cube = occluder();
+        let light = Light::Directional {
+            direction: Vector::new([0, -1, 0]),
+            colour: [1.0, 1.0, 1.0],
+        };
+        let mut settings = ShadowSettings::new(128, 1000.0);
+        settings.filter_radius = 2;
+        let mut map = ShadowMap::new(settings);
+        render(&mut map, &light, &[&cube]);
+
+        // Points along the edge of the shadow are partly lit, and get lighter moving out of it.
+        let up = Vector::new([0, 1, 0]);
+        let visibility: Vec<f64> = [40.0, 50.0, 60.0]
+            .iter()
+            .map(|&x| map.visibility(&Point::new([x, 0.0, 400.0]), &up))
+            .collect();
+        assert!(visibility[1] > 0.0 && visibility[1] < 1.0);
+        assert!(visibility.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(visibility[0] < visibility[2]);
+    }
+
+    #[test]
+    fn test_cascades() {
+        let light = Light::Directional {
+            direction: Vector::new([1, -2, 1]),
+            colour: [1.0, 1.0, 1.0],
+        };
+        let mut settings = ShadowSettings::new(128, 1000.0);
+        settings.cascades = 3;
+        let mut map = ShadowMap::new(settings);
+        render(&mut map, &light, &[&occluder()]);
+
+        // The splits cover the view from the near plane out to the range, and grow with distance.
+        let splits: Vec<f64> = map.cascades.iter().map(|cascade| cascade.far).collect();
+        assert_eq!(splits.len(), 3);
+        assert!((splits[2] - 1000.0).abs() < 1e-9);
+        assert!(100.0 < splits[0] && splits[0] - 100.0 < splits[1] - splits[0]);
+        assert!(splits[1] - splits[0] < splits[2] - splits[1]);
+
+        // The light falls diagonally, so the cube's shadow lands beyond it. Shadows are found in every cascade.
+        let up = Vector::new([0, 1, 0]);
+        for (z, cascade) in [(100.0, 0), (400.0, 1), (800.0, 2)] {
+            let mut cube = occluder();
+            cube.physics.position = Point::new([0.0, 200.0, z]);
+            render(&mut map, &light, &[&cube]);
+
+            let shadow = Point::new([100.0, 0.0, z + 100.0]);
+            let index = splits.iter().position(|&far| shadow[Z] <= far);
+            assert_eq!(index, Some(cascade));
+            assert_eq!(map.visibility(&shadow, &up), 0.0);
+            assert_eq!(map.visibility(&Point::new([-100.0, 0.0, z]), &up), 1.0);
+        }
+
+        // Points beyond the range aren't shadowed.
+        let mut cube = occluder();
+        cube.physics.position = Point::new([0, 200, 1000]);
+        render(&mut map, &light, &[&cube]);
+        assert_eq!(map.visibility(&Point::new([100, 0, 1100]), &up), 1.0);
+
+        // The cascades are fitted to the camera's view, so moving the camera towards the cube brings it into range.
+        let camera = Camera::look_at(
+            &Point::new([0, 0, 600]),
+            &Point::new([0, 0, 1000]),
+            &Vector::new([0, 1, 0]),
+        );
+        map.render(&light, &[&cube], &camera, &camera_projection());
+        assert_eq!(map.visibility(&Point::new([100, 0, 1100]), &up), 0.0);
+    }
+
+    #[test]
+    fn test_spot_shadow() {
+        let cube = occluder();
+        let light = Light::Spot {
+            position: Point::new([0, 500, 400]),
+            direction: Vector::new([0, -1, 0]),
+            colour: [1.0, 1.0, 1.0],
+            attenuation: Attenuation::none(),
+            inner_angle: 30.0,
+            outer_angle: 40.0,
+        };
+        let mut map = ShadowMap::new(ShadowSettings::new(256, 1000.0));
+        render(&mut map, &light, &[&cube]);
+
+        // The cube's shadow spreads out from under it, as the light shines from a point.
+        let up = Vector::new([0, 1, 0]);
+        assert_eq!(map.visibility(&Point::new([0, 0, 400]), &up), 0.0);
+        assert_eq!(map.visibility(&Point::new([65, 0, 400]), &up), 0.0);
+        assert_eq!(map.visibility(&Point::new([150, 0, 400]), &up), 1.0);
+        assert_eq!(map.visibility(&Point::new([0, 250, 400]), &up), 1.0);
+
+        // Point lights don't cast shadows.
+        let point = Light::Point {
+            position: Point::new([0, 500, 400]),
+            colour: [1.0, 1.0, 1.0],
+            attenuation: Attenuation::none(),
+        };
+        render(&mut map, &point, &[&cube]);
+        assert_eq!(map.visibility(&Point::new([0, 0, 400]), &up), 1.0);
+    }
+}
@@ -0,0 +1,377 @@
+//! Implementation of textures made of RGBA texels that can be sampled with a number of filtering and wrapping modes.
+//!
+//! Each texture holds a chain of mipmap levels, each half the size of the one before, which are used by trilinear
+//! filtering to avoid aliasing when the texture is minified. Texture coordinates have their origin in the top left
+//! corner of the texture, with U increasing to the right and V increasing downwards.
+//!
+
+use crate::framebuffer::Colour;
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// How texels are combined to find the colour at a texture coordinate.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Filter {
+    /// Use the single closest texel.
+    Nearest,
+    /// Blend the 4 closest texels.
+    Bilinear,
+    /// Blend between bilinear samples of the 2 mipmap levels closest to the texel to pixel ratio.
+    Trilinear,
+}
+
+/// How texture coordinates outside of 0 to 1 are mapped onto the texture.
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum WrapMode {
+    /// Tile the texture.
+    Repeat,
+    /// Use the texels along the texture's edge.
+    Clamp,
+    /// Tile the texture, flipping every other tile.
+    Mirror,
+}
+
+/// A single mipmap level stored as rows of texels, starting with the top row.
+///
+#[derive(Clone)]
+struct MipLevel {
+    width: u32,
+    height: u32,
+    texels: Vec<Colour>,
+}
+
+/// A texture along with the filtering and wrapping modes used to sample it.
+///
+#[derive(Clone)]
+pub struct Texture {
+    levels: Vec<MipLevel>,
+
+    pub filter: Filter,
+    pub wrap: WrapMode,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Texture {
+    /// Return a new texture and generate its mipmap levels.
+    /// The texels are given as rows starting with the top row. Textures are sampled bilinearly with repeat wrapping
+    /// by default.
+    ///
+    /// # Panics
+    /// The texture is empty or the number of texels doesn't match its size.
+    ///
+    pub fn new(width: u32, height: u32, texels: Vec<Colour>) -> Texture {
+        assert!(width > 0 && height > 0, "Error: textures can't be empty");
+        assert_eq!(
+            texels.len(),
+            (width * height) as usize,
+            "Error: texel count doesn't match the texture's size"
+        );
+
+        let mut levels = vec![MipLevel {
+            width,
+            height,
+            texels,
+        }];
+        while let Some(level) = levels.last().unwrap().downsample() {
+            levels.push(level);
+        }
+
+        Texture {
+            levels,
+            filter: Filter::Bilinear,
+            wrap: WrapMode::Repeat,
+        }
+    }
+
+    /// Return a texture of 2 alternating colours with the given number of texels along each side of a square.
+    ///
+    pub fn checkerboard(size: u32, square: u32, colours: [Colour; 2]) -> Texture {
+        let texels = (0..size)
+            .flat_map(|y| {
+                (0..size).map(move |x| colours[(((x / square) + (y / square)) % 2) as usize])
+            })
+            .collect();
+
+        Texture::new(size, size, texels)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Texture {
+    /// Return the width of the full size texture in texels.
+    ///
+    pub fn width(&self) -> u32 {
+        self.levels[0].width
+    }
+
+    /// Return the height of the full size texture in texels.
+    ///
+    pub fn height(&self) -> u32 {
+        self.levels[0].height
+    }
+
+    /// Return the number of mipmap levels, including the full size texture.
+    ///
+    #[allow(dead_code)]
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Return the colour at a texture coordinate with each channel between 0 and 1.
+    /// The level of detail is the base 2 logarithm of the number of texels covered by a pixel, and is only used by
+    /// trilinear filtering.
+    ///
+    pub fn sample(&self, uv: [f64; 2], lod: f64) -> [f64; 4] {
+        match self.filter {
+            Filter::Nearest => self.levels[0].nearest(uv, self.wrap),
+            Filter::Bilinear => self.levels[0].bilinear(uv, self.wrap),
+            Filter::Trilinear => {
+                // A degenerate pixel footprint can give a NaN or infinite level of detail, which is treated as the
+                // full size texture.
+                let max_level = self.levels.len() - 1;
+                let lod = if lod.is_finite() {
+                    lod.clamp(0.0, max_level as f64)
+                } else {
+                    0.0
+                };
+                let (lower, t) = (lod.floor() as usize, lod.fract());
+
+                let colour = self.levels[lower].bilinear(uv, self.wrap);
+                if t == 0.0 {
+                    colour
+                } else {
+                    let next = self.levels[(lower + 1).min(max_level)].bilinear(uv, self.wrap);
+                    lerp(colour, next, t)
+                }
+            }
+        }
+    }
+}
+
+impl MipLevel {
+    /// Return the next smaller mipmap level, with each texel the average of the texels it covers, or None if this is
+    /// the smallest level. Texels split between 2 texels of an odd sized level count towards both.
+    ///
+    fn downsample(&self) -> Option<MipLevel> {
+        if self.width == 1 && self.height == 1 {
+            return None;
+        }
+
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let area = (self.width as f64 / width as f64) * (self.height as f64 / height as f64);
+        let mut texels = Vec::with_capacity((width * height) as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 4];
+                for (texel_y, y_coverage) in footprint(y, self.height, height) {
+                    for (texel_x, x_coverage) in footprint(x, self.width, width) {
+                        let texel = self.texel(texel_x, texel_y);
+                        let coverage = x_coverage * y_coverage;
+                        sum.iter_mut()
+                            .zip(texel.iter())
+                            .for_each(|(sum, &channel)| *sum += channel as f64 * coverage);
+                    }
+                }
+                texels.push(sum.map(|channel| (channel / area).round() as u8));
+            }
+        }
+
+        Some(MipLevel {
+            width,
+            height,
+            texels,
+        })
+    }
+
+    /// Return a texel, clamping coordinates past the level's edge.
+    ///
+    fn texel(&self, x: u32, y: u32) -> Colour {
+        let x = x.min(self.width - 1);
+        let y = y.min(self.height - 1);
+        self.texels[((y * self.width) + x) as usize]
+    }
+
+    /// Return a texel after wrapping its coordinates onto the level.
+    ///
+    fn wrapped_texel(&self, x: i64, y: i64, wrap: WrapMode) -> [f64; 4] {
+        let x = wrap_coordinate(x, self.width, wrap);
+        let y = wrap_coordinate(y, self.height, wrap);
+        self.texel(x, y).map(|channel| channel as f64 / 255.0)
+    }
+
+    /// Return the texel closest to a texture coordinate.
+    ///
+    fn nearest(&self, uv: [f64; 2], wrap: WrapMode) -> [f64; 4] {
+        let x = (uv[0] * self.width as f64).floor() as i64;
+        let y = (uv[1] * self.height as f64).floor() as i64;
+        self.wrapped_texel(x, y, wrap)
+    }
+
+    /// Return the blend of the 4 texels closest to a texture coordinate.
+    ///
+    fn bilinear(&self, uv: [f64; 2], wrap: WrapMode) -> [f64; 4] {
+        // Texel centres lie half way between texel coordinates.
+        let x = (uv[0] * self.width as f64) - 0.5;
+        let y = (uv[1] * self.height as f64) - 0.5;
+        let (x0, y0) = (x.floor() as i64, y.floor() as i64);
+        let (tx, ty) = (x - x.floor(), y - y.floor());
+
+        let top = lerp(
+            self.wrapped_texel(x0, y0, wrap),
+            self.wrapped_texel(x0 + 1, y0, wrap),
+            tx,
+        );
+        let bottom = lerp(
+            self.wrapped_texel(x0, y0 + 1, wrap),
+            self.wrapped_texel(x0 + 1, y0 + 1, wrap),
+            tx,
+        );
+        lerp(top, bottom, ty)
+    }
+}
+
+/// Map a texel coordinate onto a texture of the given size.
+///
+fn wrap_coordinate(coordinate: i64, size: u32, wrap: WrapMode) -> u32 {
+    let size = size as i64;
+    let wrapped = match wrap {
+        WrapMode::Repeat => coordinate.rem_euclid(size),
+        WrapMode::Clamp => coordinate.clamp(0, size - 1),
+        WrapMode::Mirror => {
+            let coordinate = coordinate.rem_euclid(size * 2);
+            if coordinate < size {
+                coordinate
+            } else {
+                (size * 2) - 1 - coordinate
+            }
+        }
+    };
+    wrapped as u32
+}
+
+/// Return the texels along one side of a mipmap level covered by a texel of the next level, with how much of each
+/// is covered. A side of odd length doesn't halve evenly, so the texel it shares between 2 of the next level's is
+/// partly covered by each.
+///
+fn footprint(index: u32, length: u32, next_length: u32) -> impl Iterator<Item = (u32, f64)> {
+    let scale = length as f64 / next_length as f64;
+    let (start, end) = (index as f64 * scale, (index + 1) as f64 * scale);
+    (start.floor() as u32..(end.ceil() as u32).min(length)).map(move |texel| {
+        let covered = end.min(texel as f64 + 1.0) - start.max(texel as f64);
+        (texel, covered)
+    })
+}
+
+/// Linearly interpolate between 2 colours.
+///
+fn lerp(from: [f64; 4], to: [f64; 4], t: f64) -> [f64; 4] {
+    let mut result = from;
+    result
+        .iter_mut()
+        .zip(to.iter())
+        .for_each(|(channel, to)| *channel += (to - *channel) * t);
+    result
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Colour = [0, 0, 0, 255];
+    const WHITE: Colour = [255, 255, 255, 255];
+
+    #[test]
+    fn test_mipmap_levels() {
+        let texture = Texture::checkerboard(8, 1, [BLACK, WHITE]);
+        assert_eq!(texture.level_count(), 4);
+
+        // Every level below the full size averages the checkerboard out to grey.
+        let smallest = &texture.levels[3];
+        assert_eq!((smallest.width, smallest.height), (1, 1));
+        assert_eq!(smallest.texels[0], [128, 128, 128, 255]);
+
+        let texture = Texture::new(4, 1, vec![BLACK; 4]);
+        assert_eq!(texture.level_count(), 3);
+
+        // Odd sizes share their middle texels between the next level's, and don't drop their last row or column.
+        let texture = Texture::new(5, 3, [BLACK, BLACK, BLACK, BLACK, WHITE].repeat(3));
+        let next = &texture.levels[1];
+        assert_eq!((next.width, next.height), (2, 1));
+        assert_eq!(next.texels, vec![[0, 0, 0, 255], [102, 102, 102, 255]]);
+    }
+
+    #[test]
+    fn test_wrap_coordinate() {
+        assert_eq!(wrap_coordinate(-1, 4, WrapMode::Repeat), 3);
+        assert_eq!(wrap_coordinate(5, 4, WrapMode::Repeat), 1);
+        assert_eq!(wrap_coordinate(-1, 4, WrapMode::Clamp), 0);
+        assert_eq!(wrap_coordinate(5, 4, WrapMode::Clamp), 3);
+        assert_eq!(wrap_coordinate(-1, 4, WrapMode::Mirror), 0);
+        assert_eq!(wrap_coordinate(5, 4, WrapMode::Mirror), 2);
+        assert_eq!(wrap_coordinate(9, 4, WrapMode::Mirror), 1);
+    }
+
+    #[test]
+    fn test_sample_filters() {
+        let mut texture = Texture::new(2, 1, vec![BLACK, WHITE]);
+
+        texture.filter = Filter::Nearest;
+        assert_eq!(texture.sample([0.49, 0.5], 0.0), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(texture.sample([0.51, 0.5], 0.0), [1.0, 1.0, 1.0, 1.0]);
+
+        // Half way between the texel centres.
+        texture.filter = Filter::Bilinear;
+        texture.wrap = WrapMode::Clamp;
+        let colour = texture.sample([0.5, 0.5], 0.0);
+        assert!((colour[0] - 0.5).abs() < 1e-9);
+
+        // Repeating wraps the left edge around to the white texel.
+        texture.wrap = WrapMode::Repeat;
+        let colour = texture.sample([0.0, 0.5], 0.0);
+        assert!((colour[0] - 0.5).abs() < 1e-9);
+        texture.wrap = WrapMode::Clamp;
+        assert_eq!(texture.sample([0.0, 0.5], 0.0)[0], 0.0);
+
+        // The smallest level is the average of both texels.
+        texture.filter = Filter::Trilinear;
+        let colour = texture.sample([0.0, 0.5], 0.5);
+        assert!((colour[0] - (128.0 / 255.0 / 2.0)).abs() < 1e-9);
+        let colour = texture.sample([0.0, 0.5], 4.0);
+        assert!((colour[0] - (128.0 / 255.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sample_non_finite_lod() {
+        // A non-finite level of detail samples the full size texture, even when it's the only level.
+        let mut texture = Texture::new(1, 1, vec![WHITE]);
+        texture.filter = Filter::Trilinear;
+        assert_eq!(texture.level_count(), 1);
+        for lod in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(texture.sample([0.5, 0.5], lod), [1.0, 1.0, 1.0, 1.0]);
+        }
+
+        let mut texture = Texture::new(2, 1, vec![BLACK, WHITE]);
+        texture.filter = Filter::Trilinear;
+        texture.wrap = WrapMode::Clamp;
+        assert_eq!(
+            texture.sample([0.0, 0.5], f64::NAN),
+            texture.sample([0.0, 0.5], 0.0)
+        );
+    }
+}
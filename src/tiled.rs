@@ -0,0 +1,406 @@
+//! Implementation of a renderer that splits a frame buffer into tiles and rasterizes them in parallel.
+//!
+//! Each visible polygon is binned into the tiles its bounding box overlaps, and a pool of worker threads takes tiles
+//! one at a time and draws their polygons into them. A tile draws its polygons in the same order as the mesh and
+//! every pixel belongs to a single tile, so the result is identical to drawing the mesh on a single thread.
+//!
+//! The worker threads are started along with the renderer and live until it's dropped, so drawing a mesh doesn't
+//! pay for spawning them. The tiles and the bins are kept between meshes too, so that once they've grown to fit
+//! drawing a mesh doesn't allocate.
+//!
+
+use crate::{
+    framebuffer::{release_tiles, BlendMode, DrawType, FrameBuffer, LineStyle, RenderTarget, Tile},
+    mesh::{geometry::Dim, EdgeList, Mesh, Vertex},
+    rasterizer::{RasterMethod, Rect},
+    shader::FragmentShader,
+};
+use std::{
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Types & Traits //////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Draws meshes into a frame buffer by splitting it into square tiles that are drawn on a pool of threads.
+///
+pub struct TiledRenderer {
+    workers: Vec<Worker>,
+    tile_size: u32,
+
+    /// Receives the result of each job once a worker has finished running it. Behind a mutex so that jobs can
+    /// borrow the renderer.
+    finished: Mutex<Receiver<thread::Result<()>>>,
+
+    /// The tiles the target is split into, released between meshes so that their row lists and edge tables are
+    /// reused.
+    tiles: Vec<Tile<'static>>,
+    /// The indices of the visible polygons overlapping each tile. Kept between meshes to reuse their allocations.
+    bins: Vec<Vec<usize>>,
+    /// The edges of the mesh being drawn. Kept between meshes to reuse its allocations.
+    edges: EdgeList,
+}
+
+/// A thread in the renderer's pool, and the channel its jobs are sent down. The channels are bounded, so their
+/// buffers are allocated up front rather than as jobs are sent.
+///
+struct Worker {
+    jobs: SyncSender<Job>,
+    handle: JoinHandle<()>,
+}
+
+/// A closure borrowed from draw_mesh for a worker to run, with its lifetime erased so it can be sent to a thread
+/// that outlives it. draw_mesh waits for every job it sends to finish before returning, so the closure is never
+/// used after it's dropped.
+///
+struct Job(&'static (dyn Fn() + Sync));
+
+////////////////////////////////////////////////////////////////////////////////
+// Constructor Implementations /////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl TiledRenderer {
+    /// Return a new renderer drawing tiles of the given size on a number of threads.
+    ///
+    pub fn new(threads: usize, tile_size: u32) -> TiledRenderer {
+        assert!(threads > 0, "the renderer needs at least 1 thread");
+        assert!(tile_size > 0, "tiles must be at least 1 pixel wide");
+
+        // A worker is only ever sent 1 job at a time, and each sends back 1 result for it.
+        let (finish, finished) = mpsc::sync_channel(threads);
+        let workers = (0..threads)
+            .map(|_| {
+                let (jobs, queue) = mpsc::sync_channel::<Job>(1);
+                let finish = finish.clone();
+                let handle = thread::spawn(move || {
+                    // Panics are passed back to draw_mesh, so a worker keeps running until the renderer is dropped.
+                    for job in queue {
+                        let result = panic::catch_unwind(AssertUnwindSafe(job.0));
+                        if finish.send(result).is_err() {
+                            break;
+                        }
+                    }
+                });
+                Worker { jobs, handle }
+            })
+            .collect();
+
+        TiledRenderer {
+            workers,
+            tile_size,
+            finished: Mutex::new(finished),
+            tiles: Vec::new(),
+            bins: Vec::new(),
+            edges: EdgeList::default(),
+        }
+    }
+
+    /// Return a new renderer drawing tiles of the given size on as many threads as the system can run at once.
+    ///
+    pub fn with_available_parallelism(tile_size: u32) -> TiledRenderer {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        TiledRenderer::new(threads, tile_size)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Method Implementations //////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl TiledRenderer {
+    /// Draw a mesh that has been run through the pipeline into a frame buffer, in the same way as the render
+    /// target's draw_mesh.
+    ///
+    pub fn draw_mesh<S>(
+        &mut self,
+        target: &mut FrameBuffer,
+        mesh: &Mesh,
+        draw_type: DrawType,
+        method: RasterMethod,
+        shader: &S,
+        line_style: &LineStyle,
+    ) where
+        S: FragmentShader + Sync,
+    {
+        let (width, height, tile_size) = (target.width(), target.height(), self.tile_size);
+        let columns = width.div_ceil(tile_size) as usize;
+
+        let transparency = shader.blend_mode() == BlendMode::WeightedBlended;
+        let mut tiles = release_tiles(mem::take(&mut self.tiles));
+        target.split_into_tiles(tile_size, transparency, &mut tiles);
+
+        // Bin the polygons, keeping them in the order they're drawn in.
+        self.bins.resize_with(tiles.len(), Vec::new);
+        self.bins.iter_mut().for_each(|bin| bin.clear());
+        for (index, polygon) in mesh.iter_visible_polygons().enumerate() {
+            if let Some((xrange, yrange)) = self.tile_range(&polygon.verticies, width, height) {
+                for y in yrange {
+                    for x in xrange.clone() {
+                        self.bins[(y as usize * columns) + x as usize].push(index);
+                    }
+                }
+            }
+        }
+
+        match draw_type {
+            DrawType::Fill => self.edges.clear(),
+            _ => mesh.find_visible_edges(&mut self.edges),
+        }
+
+        let work = Mutex::new(tiles.iter_mut().zip(self.bins.iter()));
+        let job = || loop {
+            let next = work.lock().unwrap().next();
+            let (tile, bin) = match next {
+                Some(next) => next,
+                None => break,
+            };
+
+            let (x, y) = self.tile_of(tile.region());
+            let polygons = bin.iter().map(|&index| mesh.visible_polygon(index));
+            let edges = mesh.iter_edges(&self.edges).filter(|edge| {
+                self.tile_range(&edge.verticies, width, height)
+                    .is_some_and(|(xrange, yrange)| xrange.contains(&x) && yrange.contains(&y))
+            });
+            tile.draw_primitives(polygons, edges, draw_type, method, shader, line_style);
+        };
+        self.run(&job, self.bins.len());
+        self.tiles = release_tiles(tiles);
+    }
+
+    /// Run a job on up to a number of workers at once, and wait for all of them to finish it. A panic in any of
+    /// the workers is resumed on the calling thread once they've all finished.
+    ///
+    fn run(&self, job: &(dyn Fn() + Sync), workers: usize) {
+        // The lock is taken before any job is sent so that nothing between sending and receiving can panic, and
+        // it's released before a worker's panic is resumed so that it's never poisoned.
+        let finished = self.finished.lock().unwrap();
+
+        // SAFETY: The job is only used by the workers it's sent to, and they've all finished with it by the time
+        // this returns, as a result is received for each of them. Workers catch panics so a result is always sent.
+        let job: &'static (dyn Fn() + Sync) = unsafe { std::mem::transmute(job) };
+        let sent = self
+            .workers
+            .iter()
+            .take(workers)
+            .filter(|worker| worker.jobs.send(Job(job)).is_ok())
+            .count();
+
+        let mut panicked = None;
+        for result in finished.iter().take(sent) {
+            if let Err(payload) = result {
+                panicked.get_or_insert(payload);
+            }
+        }
+        drop(finished);
+
+        if let Some(payload) = panicked {
+            panic::resume_unwind(payload);
+        }
+    }
+
+    /// Return the ranges of tile columns and rows overlapped by the bounding box of some screen space verticies, or
+    /// None if it lies outside the target. The box is padded by a pixel so that the pixels an edge touches are never
+    /// left out, even if it's anti-aliased.
+    ///
+    fn tile_range(
+        &self,
+        verticies: &[&Vertex],
+        width: u32,
+        height: u32,
+    ) -> Option<(std::ops::Range<u32>, std::ops::Range<u32>)> {
+        let mut min = [f64::MAX; 2];
+        let mut max = [f64::MIN; 2];
+        for vertex in verticies {
+            for (axis, coordinate) in [vertex[Dim::X], vertex[Dim::Y]].into_iter().enumerate() {
+                min[axis] = min[axis].min(coordinate);
+                max[axis] = max[axis].max(coordinate);
+            }
+        }
+
+        let mut ranges = [0..0, 0..0];
+        for (axis, size) in [width, height].into_iter().enumerate() {
+            let (first, last) = ((min[axis] - 1.0).floor(), (max[axis] + 1.0).ceil());
+            if last < 0.0 || first >= size as f64 {
+                return None;
+            }
+
+            let first = first.max(0.0) as u32 / self.tile_size;
+            let last = (last as u32).min(size - 1) / self.tile_size;
+            ranges[axis] = first..(last + 1);
+        }
+
+        let [xrange, yrange] = ranges;
+        Some((xrange, yrange))
+    }
+
+    /// Return the column and row of the tile covering a region.
+    ///
+    fn tile_of(&self, region: Rect) -> (u32, u32) {
+        (region.x / self.tile_size, region.y / self.tile_size)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Trait Implementations ///////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+impl Drop for TiledRenderer {
+    fn drop(&mut self) {
+        // Closing each worker's channel ends its loop.
+        for Worker { jobs, handle } in self.workers.drain(..) {
+            drop(jobs);
+            let _ = handle.join();
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests ///////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        golden::{assert_draws_match, overlapping_scene},
+        lighting::{Light, Material},
+        mesh::geometry::Vector,
+        rasterizer::{AntiAliasing, SampleCount, SamplePattern},
+        shader::{LitShader, ShadingMode},
+    };
+
+    /// Draw the overlapping scene into 2 frame buffers, one directly and one with a tiled renderer, and check
+    /// they're identical.
+    ///
+    fn assert_identical(
+        anti_aliasing: AntiAliasing,
+        method: RasterMethod,
+        draw_type: DrawType,
+        blend: BlendMode,
+    ) {
+        let meshes = overlapping_scene();
+        let lights = [
+            Light::Ambient {
+                colour: [0.2, 0.2, 0.2],
+            },
+            Light::Directional {
+                direction: Vector::new([1, -1, 1]),
+                colour: [0.8, 0.8, 0.8],
+            },
+        ];
+        let opaque = Material::new([0.0, 1.0, 0.0]);
+        let mut glass = Material::new([1.0, 0.3, 0.3]);
+        glass.opacity = 0.5;
+        glass.blend = blend;
+        let line_style = LineStyle::new([255, 255, 255, 255]);
+        let shaders = [&opaque, &glass]
+            .map(|material| LitShader::new(material, &lights, ShadingMode::Gouraud));
+
+        assert_draws_match(
+            (anti_aliasing, method, draw_type, blend),
+            anti_aliasing,
+            |direct| {
+                for (mesh, shader) in meshes.iter().zip(&shaders) {
+                    direct.draw_mesh(mesh, draw_type, method, shader, &line_style);
+                }
+                direct.composite_transparency();
+            },
+            |tiled| {
+                // Tiles that don't divide the buffer evenly.
+                let mut renderer = TiledRenderer::new(3, 24);
+                for (mesh, shader) in meshes.iter().zip(&shaders) {
+                    renderer.draw_mesh(tiled, mesh, draw_type, method, shader, &line_style);
+                }
+                tiled.composite_transparency();
+            },
+        );
+    }
+
+    #[test]
+    fn test_tiles_match_single_thread() {
+        for method in [RasterMethod::EdgeTable, RasterMethod::HalfSpace] {
+            for draw_type in [
+                DrawType::Fill,
+                DrawType::Wireframe,
+                DrawType::Both,
+                DrawType::HiddenLine,
+            ] {
+                assert_identical(AntiAliasing::Off, method, draw_type, BlendMode::Alpha);
+            }
+        }
+
+        for blend in [
+            BlendMode::Opaque,
+            BlendMode::Additive,
+            BlendMode::Multiply,
+            BlendMode::WeightedBlended,
+        ] {
+            assert_identical(
+                AntiAliasing::Off,
+                RasterMethod::HalfSpace,
+                DrawType::Fill,
+                blend,
+            );
+        }
+
+        for anti_aliasing in [
+            AntiAliasing::Supersample(SampleCount::X4, SamplePattern::RotatedGrid),
+            AntiAliasing::Multisample(SampleCount::X8, SamplePattern::Grid),
+        ] {
+            assert_identical(
+                anti_aliasing,
+                RasterMethod::HalfSpace,
+                DrawType::Both,
+                BlendMode::WeightedBlended,
+            );
+        }
+    }
+
+    #[test]
+    fn test_workers_are_reused() {
+        let renderer = TiledRenderer::new(2, 16);
+        let worker_ids = || {
+            let ids = Mutex::new(Vec::new());
+            renderer.run(&|| ids.lock().unwrap().push(thread::current().id()), 2);
+            let mut ids = ids.into_inner().unwrap();
+            ids.sort_by_key(|id| format!("{:?}", id));
+            ids
+        };
+        let first = worker_ids();
+        assert_eq!(first.len(), 2);
+        assert!(!first.contains(&thread::current().id()));
+        assert_eq!(worker_ids(), first);
+
+        // A panic in a worker is passed back to the caller, and the worker carries on running jobs.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            renderer.run(&|| panic!("job failed"), 2);
+        }));
+        assert!(result.is_err());
+        assert_eq!(worker_ids(), first);
+    }
+
+    #[test]
+    fn test_tile_range() {
+        let renderer = TiledRenderer::new(1, 16);
+        let (a, b) = (
+            Vertex::new([20.5, 3.0, 0.0, 1.0]),
+            Vertex::new([47.0, 15.5, 0.0, 1.0]),
+        );
+
+        // Padding pulls in the tile to the right, as the box ends next to its boundary.
+        assert_eq!(renderer.tile_range(&[&a, &b], 100, 100), Some((1..4, 0..2)));
+
+        // Boxes are clamped to the target, and ignored if they lie outside it.
+        let c = Vertex::new([-50.0, 200.0, 0.0, 1.0]);
+        assert_eq!(renderer.tile_range(&[&a, &c], 100, 100), Some((0..2, 0..7)));
+        let d = Vertex::new([-10.0, 50.0, 0.0, 1.0]);
+        assert_eq!(renderer.tile_range(&[&c, &d], 100, 100), None);
+    }
+}
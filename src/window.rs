@@ -1,7 +1,13 @@
 use crate::{
-    mesh::{geometry::Dim, Matrix4X4},
-    rasterizer::EdgeTable,
+    camera::Camera,
+    fog::Fog,
+    framebuffer::{Colour, DepthTest, FrameBuffer, RenderTarget},
+    image::{self, Image},
+    occlusion::DepthPyramid,
+    projection::Projection,
+    rasterizer::{AntiAliasing, EdgeTable},
 };
+use std::path::Path;
 
 use pixels::{Pixels, SurfaceTexture};
 use winit::{
@@ -10,27 +16,19 @@ use winit::{
     window::{Window, WindowBuilder},
 };
 
-type Colour = [u8; 4];
-
-#[derive(PartialEq)]
-pub enum DrawType {
-    Wireframe,
-    Fill,
-    Both,
-}
-
 pub struct GraphicsWindow {
     window: Window,
     pub width: u32,
     pub height: u32,
 
     pixel_buffer: Pixels,
-    zbuffer: Vec<Vec<f64>>,
+    frame_buffer: FrameBuffer,
+    depth_pyramid: DepthPyramid,
 
-    near_plane: f64,
-    far_plane: f64,
-    fov: f64,
-    pub projection_matrix: Matrix4X4,
+    /// The camera meshes are viewed from.
+    pub camera: Camera,
+    /// The projection meshes are drawn with. Its aspect ratio follows the window's size.
+    pub projection: Projection,
 }
 impl GraphicsWindow {
     ///
@@ -55,37 +53,21 @@ impl GraphicsWindow {
             Pixels::new(width, height, surface_texture).expect("Error: create pixel buffer")
         };
 
-        let zbuffer = vec![vec![0.0; (width + 1) as usize]; (height + 1) as usize];
-
-        // Create the transformation matrix to project camera space onto NDC space
-        let near_plane = 100.0;
-        let far_plane = 1000.0;
-        let fov = 45.0;
-
-        let projection_matrix = {
-            let aspect_ratio = width as f64 / height as f64;
-            let x_mul = (1.0 / f64::tan(fov / 2.0)) / aspect_ratio;
-            let y_mul = 1.0 / f64::tan(fov / 2.0);
-            let z1_mul = far_plane / (far_plane - near_plane);
-            let z2_mul = -1.0 * (far_plane * near_plane) / (far_plane - near_plane);
-            Matrix4X4([
-                [x_mul, 0.0, 0.0, 0.0],
-                [0.0, y_mul, 0.0, 0.0],
-                [0.0, 0.0, z1_mul, 1.0],
-                [0.0, 0.0, z2_mul, 0.0],
-            ])
-        };
+        // Create the frame buffer that gets drawn into and then presented by the pixel buffer.
+        let frame_buffer = FrameBuffer::new(width, height);
+
+        // Create the projection from view space onto NDC space
+        let projection = Projection::perspective(45.0, width as f64 / height as f64, 100.0, 1000.0);
 
         GraphicsWindow {
             window,
             width,
             height,
             pixel_buffer,
-            zbuffer,
-            near_plane,
-            far_plane,
-            fov,
-            projection_matrix,
+            frame_buffer,
+            depth_pyramid: DepthPyramid::new(),
+            camera: Camera::new(),
+            projection,
         }
     }
 
@@ -95,147 +77,71 @@ impl GraphicsWindow {
     pub fn resize(&mut self, width: u32, height: u32) {
         self.pixel_buffer.resize_surface(width, height);
         self.pixel_buffer.resize_buffer(width, height);
-        self.zbuffer = vec![vec![0.0; (width + 1) as usize]; (height + 1) as usize];
-
-        // Recalculate the projection matrix
-        self.projection_matrix = {
-            let aspect_ratio = width as f64 / height as f64;
-
-            let x_mul = (1.0 / f64::tan(self.fov / 2.0)) / aspect_ratio;
-            let y_mul = 1.0 / f64::tan(self.fov / 2.0);
-            let z1_mul = self.far_plane / (self.far_plane - self.near_plane);
-            let z2_mul =
-                -1.0 * (self.far_plane * self.near_plane) / (self.far_plane - self.near_plane);
-
-            Matrix4X4([
-                [x_mul, 0.0, 0.0, 0.0],
-                [0.0, y_mul, 0.0, 0.0],
-                [0.0, 0.0, z1_mul, 1.0],
-                [0.0, 0.0, z2_mul, 0.0],
-            ])
-        };
+        self.frame_buffer.resize(width, height);
+        self.projection.resize(width, height);
 
         self.width = width;
         self.height = height;
     }
 
     ///
-    /// Clear the pixel buffer and z buffer.
+    /// Change how the frame buffer is anti-aliased. Its contents are cleared.
     ///
-    pub fn clear(&mut self) {
-        for i in self.pixel_buffer.get_frame().iter_mut() {
-            *i = 0;
-        }
-        for element in self.zbuffer.iter_mut() {
-            for i in element {
-                *i = 0.0;
-            }
-        }
+    pub fn set_anti_aliasing(&mut self, anti_aliasing: AntiAliasing) {
+        self.frame_buffer.set_anti_aliasing(anti_aliasing);
     }
 
     ///
-    /// Draw a polygon using rasterization.
+    /// Change the test fragments must pass against the stored depth to be drawn.
     ///
-    pub fn draw_polygon(&mut self, edge_table: &EdgeTable, style: DrawType) {
-        // Calculate the green intensity from the z part of the polygons normal.
-        // the Z normal will be between -1 and 1 with -1 facing the camera
-        let colour = {
-            let intensity = ((-edge_table.normal[Dim::Z] + 1.0) * 127.0) as u8;
-            [0, intensity, 0, 255]
-        };
+    pub fn set_depth_test(&mut self, depth_test: DepthTest) {
+        self.frame_buffer.set_depth_test(depth_test);
+    }
 
-        // Draw a rasterized polygon
-        if style == DrawType::Fill || style == DrawType::Both {
-            // Find the first and last elements we want to iterate between in the edge table.
-            // We only want elements that will be within screen space.
-            let (first, ystart) = if edge_table.ymin < 0 {
-                (edge_table.ymin.abs() as usize, 0)
-            } else {
-                (0, edge_table.ymin)
-            };
-            let last = if edge_table.ymax > self.height as i32 {
-                (self.height as i32 - edge_table.ymin) as usize
-            } else {
-                (edge_table.ymax - edge_table.ymin) as usize
-            };
-
-            for (i, edges) in edge_table.iter_between(first, last).enumerate() {
-                let y = ystart + i as i32;
-                match edges.get_edges() {
-                    Ok(edges) => {
-                        let xrange = {
-                            let edge1 = edges[0].x.clamp(0, self.width as i32);
-                            let edge2 = edges[1].x.clamp(0, self.width as i32);
-                            edge1..edge2
-                        };
-
-                        // Find out how much Z changes for each X
-                        let zstep = {
-                            let dz = edges[1].z - edges[0].z;
-
-                            let x1 = edges[0].x.clamp(0, self.width as i32);
-                            let x2 = edges[1].x.clamp(0, self.width as i32);
-                            let dx = x2 - x1;
-
-                            dz as f64 / dx as f64
-                        };
-                        let mut z = edges[0].z as f64;
-
-                        // interpolate X between the 2 edges.
-                        for x in xrange {
-                            if z > self.zbuffer[y as usize][x as usize] {
-                                self.draw_pixel(x as u32, y as u32, colour);
-                                self.zbuffer[y as usize][x as usize] = z;
-                            }
-
-                            z += zstep;
-                        }
-                    }
-                    Err(_error) => {
-                        println!("No edges for Y coordinate {}", y);
-                    }
-                };
-            }
-        }
+    ///
+    /// Change the fog that shaded fragments are blended towards, or remove it.
+    ///
+    pub fn set_fog(&mut self, fog: Option<Fog>) {
+        self.frame_buffer.set_fog(fog);
+    }
 
-        // Draw a wireframe polygon
-        if style == DrawType::Wireframe || style == DrawType::Both {
-            let mut y = edge_table.ymin;
-            for edges in edge_table.iter() {
-                for xzpair in edges.iter() {
-                    if xzpair.x >= 0 && xzpair.x < self.width as i32 {
-                        self.draw_pixel(xzpair.x as u32, y as u32, [255, 0, 0, 255]);
-                    }
-                }
-                y += 1;
-            }
-        }
+    ///
+    /// Change the colour the frame buffer is cleared to.
+    ///
+    pub fn set_clear_colour(&mut self, colour: Colour) {
+        self.frame_buffer.set_clear_colour(colour);
     }
 
     ///
-    /// Set a pixels colour via x and y coordinates with the origin in the bottom left corner.
+    /// Rebuild the depth pyramid from the frame buffer's current depths.
     ///
-    pub fn draw_pixel(&mut self, x: u32, y: u32, colour: Colour) {
-        // Figure out which 4 elements we need from the x and y coordinates then get a
-        // pointer to the 4 elements which consitute the pixel.
-        let element = {
-            let y_invert = self.height - (y + 1);
-            (((y_invert * self.width) + x) * 4) as usize
-        };
+    pub fn build_depth_pyramid(&mut self) {
+        self.depth_pyramid.build(&self.frame_buffer);
+    }
 
-        let pixel = self
-            .pixel_buffer
-            .get_frame()
-            .get_mut(element..(element + 4))
-            .unwrap();
+    ///
+    /// Return the depth pyramid, as it was when it was last built.
+    ///
+    pub fn depth_pyramid(&self) -> &DepthPyramid {
+        &self.depth_pyramid
+    }
 
-        pixel.copy_from_slice(&colour);
+    ///
+    /// Return the frame buffer the window is drawn from.
+    ///
+    pub fn frame_buffer(&mut self) -> &mut FrameBuffer {
+        &mut self.frame_buffer
     }
 
     ///
-    /// Render the pixel buffer to the screen.
+    /// Resolve the frame buffer, copy it into the pixel buffer and render it to the screen.
     ///
     pub fn render(&mut self) {
+        self.frame_buffer.resolve();
+        self.pixel_buffer
+            .get_frame()
+            .copy_from_slice(self.frame_buffer.frame());
+
         match self.pixel_buffer.render() {
             Ok(_) => {}
             Err(_) => {
@@ -244,6 +150,20 @@ impl GraphicsWindow {
         }
     }
 
+    ///
+    /// Save the last rendered frame to an image file. The format is chosen from the file's extension.
+    ///
+    pub fn save_frame<P: AsRef<Path>>(&self, path: P) -> image::Result<()> {
+        Image::from_colour(&self.frame_buffer).save(path)
+    }
+
+    ///
+    /// Save the depth buffer of the last rendered frame to a greyscale image file.
+    ///
+    pub fn save_depth<P: AsRef<Path>>(&self, path: P) -> image::Result<()> {
+        Image::from_depth(&self.frame_buffer).save(path)
+    }
+
     ///
     /// Redraw the window.
     ///
@@ -252,3 +172,79 @@ impl GraphicsWindow {
         self.window.request_redraw();
     }
 }
+
+/// The window is a render target which draws into its frame buffer.
+///
+impl RenderTarget for GraphicsWindow {
+    fn width(&self) -> u32 {
+        self.frame_buffer.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.frame_buffer.height()
+    }
+
+    fn get_pixel(&self, x: u32, y: u32) -> Colour {
+        self.frame_buffer.get_pixel(x, y)
+    }
+
+    fn draw_pixel(&mut self, x: u32, y: u32, colour: Colour) {
+        self.frame_buffer.draw_pixel(x, y, colour);
+    }
+
+    fn get_depth(&self, x: u32, y: u32) -> f64 {
+        self.frame_buffer.get_depth(x, y)
+    }
+
+    fn set_depth(&mut self, x: u32, y: u32, depth: f64) {
+        self.frame_buffer.set_depth(x, y, depth);
+    }
+
+    fn clear(&mut self) {
+        self.frame_buffer.clear();
+    }
+
+    fn anti_aliasing(&self) -> AntiAliasing {
+        self.frame_buffer.anti_aliasing()
+    }
+
+    fn depth_test(&self) -> DepthTest {
+        self.frame_buffer.depth_test()
+    }
+
+    fn fog(&self) -> Option<Fog> {
+        self.frame_buffer.fog()
+    }
+
+    fn clear_colour(&self) -> Colour {
+        self.frame_buffer.clear_colour()
+    }
+
+    fn get_sample_depth(&self, x: u32, y: u32, sample: usize) -> f64 {
+        self.frame_buffer.get_sample_depth(x, y, sample)
+    }
+
+    fn get_sample(&self, x: u32, y: u32, sample: usize) -> Colour {
+        self.frame_buffer.get_sample(x, y, sample)
+    }
+
+    fn draw_sample(&mut self, x: u32, y: u32, sample: usize, colour: Colour, depth: f64) {
+        self.frame_buffer.draw_sample(x, y, sample, colour, depth);
+    }
+
+    fn accumulate(&mut self, x: u32, y: u32, colour: Colour, depth: f64, coverage: f64) {
+        self.frame_buffer.accumulate(x, y, colour, depth, coverage);
+    }
+
+    fn composite_transparency(&mut self) {
+        self.frame_buffer.composite_transparency();
+    }
+
+    fn take_edge_table(&mut self) -> EdgeTable {
+        self.frame_buffer.take_edge_table()
+    }
+
+    fn restore_edge_table(&mut self, edge_table: EdgeTable) {
+        self.frame_buffer.restore_edge_table(edge_table);
+    }
+}